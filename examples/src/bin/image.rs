@@ -58,7 +58,9 @@ fn main() {
         let usage = caps.supported_usage_flags;
 
         vulkano::swapchain::Swapchain::new(&device, &window.surface(), 3,
-                                           vulkano::format::B8G8R8A8Srgb, dimensions, 1,
+                                           vulkano::format::B8G8R8A8Srgb,
+                                           vulkano::swapchain::ColorSpace::SrgbNonLinear,
+                                           dimensions, 1,
                                            &usage, &queue, vulkano::swapchain::SurfaceTransform::Identity,
                                            vulkano::swapchain::CompositeAlpha::Opaque,
                                            present, true, None).expect("failed to create swapchain")
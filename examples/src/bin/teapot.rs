@@ -61,9 +61,10 @@ fn main() {
         let dimensions = caps.current_extent.unwrap_or([1280, 1024]);
         let present = caps.present_modes.iter().next().unwrap();
         let usage = caps.supported_usage_flags;
-        let format = caps.supported_formats[0].0;
+        let (format, color_space) = caps.supported_formats[0];
 
-        vulkano::swapchain::Swapchain::new(&device, &window.surface(), 3, format, dimensions, 1,
+        vulkano::swapchain::Swapchain::new(&device, &window.surface(), 3, format, color_space,
+                                           dimensions, 1,
                                            &usage, &queue, vulkano::swapchain::SurfaceTransform::Identity,
                                            vulkano::swapchain::CompositeAlpha::Opaque,
                                            present, true, None).expect("failed to create swapchain")
@@ -180,10 +180,10 @@ fn main() {
         let alpha = caps.supported_composite_alpha.iter().next().unwrap();
 
         // Choosing the internal format that the images will have.
-        let format = caps.supported_formats[0].0;
+        let (format, color_space) = caps.supported_formats[0];
 
         // Please take a look at the docs for the meaning of the parameters we didn't mention.
-        Swapchain::new(&device, &window.surface(), 2, format, dimensions, 1,
+        Swapchain::new(&device, &window.surface(), 2, format, color_space, dimensions, 1,
                        &caps.supported_usage_flags, &queue, SurfaceTransform::Identity, alpha,
                        present, true, None).expect("failed to create swapchain")
     };
@@ -16,6 +16,7 @@ use std::ffi::CStr;
 use std::ffi::CString;
 use std::fmt;
 use std::os::raw::c_char;
+use std::os::raw::c_int;
 use std::os::raw::c_void;
 
 pub type Flags = u32;
@@ -98,6 +99,7 @@ pub const SUBOPTIMAL_KHR: u32 = 1000001003;
 pub const ERROR_OUT_OF_DATE_KHR: u32 = -1000001004i32 as u32;
 pub const ERROR_INCOMPATIBLE_DISPLAY_KHR: u32 = -1000003001i32 as u32;
 pub const ERROR_VALIDATION_FAILED_EXT: u32 = -1000011001i32 as u32;
+pub const ERROR_INVALID_EXTERNAL_HANDLE_KHR: u32 = -1000072003i32 as u32;
 
 pub type StructureType = u32;
 pub const STRUCTURE_TYPE_APPLICATION_INFO: u32 = 0;
@@ -161,6 +163,50 @@ pub const STRUCTURE_TYPE_MIR_SURFACE_CREATE_INFO_KHR: u32 = 1000007000;
 pub const STRUCTURE_TYPE_ANDROID_SURFACE_CREATE_INFO_KHR: u32 = 1000008000;
 pub const STRUCTURE_TYPE_WIN32_SURFACE_CREATE_INFO_KHR: u32 = 1000009000;
 pub const STRUCTURE_TYPE_DEBUG_REPORT_CREATE_INFO_EXT: u32 = 1000011000;
+pub const STRUCTURE_TYPE_METAL_SURFACE_CREATE_INFO_EXT: u32 = 1000217000;
+pub const STRUCTURE_TYPE_DIRECTFB_SURFACE_CREATE_INFO_EXT: u32 = 1000346000;
+pub const STRUCTURE_TYPE_SCREEN_SURFACE_CREATE_INFO_QNX: u32 = 1000378000;
+pub const STRUCTURE_TYPE_DEBUG_MARKER_OBJECT_NAME_INFO_EXT: u32 = 1000022000;
+pub const STRUCTURE_TYPE_PHYSICAL_DEVICE_EXTERNAL_SEMAPHORE_INFO_KHR: u32 = 1000076000;
+pub const STRUCTURE_TYPE_EXTERNAL_SEMAPHORE_PROPERTIES_KHR: u32 = 1000076001;
+pub const STRUCTURE_TYPE_EXPORT_SEMAPHORE_CREATE_INFO_KHR: u32 = 1000077000;
+pub const STRUCTURE_TYPE_IMPORT_SEMAPHORE_WIN32_HANDLE_INFO_KHR: u32 = 1000078000;
+pub const STRUCTURE_TYPE_SEMAPHORE_GET_WIN32_HANDLE_INFO_KHR: u32 = 1000078003;
+pub const STRUCTURE_TYPE_IMPORT_SEMAPHORE_FD_INFO_KHR: u32 = 1000079000;
+pub const STRUCTURE_TYPE_SEMAPHORE_GET_FD_INFO_KHR: u32 = 1000079001;
+pub const STRUCTURE_TYPE_DEVICE_EVENT_INFO_EXT: u32 = 1000091001;
+pub const STRUCTURE_TYPE_DISPLAY_EVENT_INFO_EXT: u32 = 1000091002;
+pub const STRUCTURE_TYPE_SWAPCHAIN_COUNTER_CREATE_INFO_EXT: u32 = 1000091003;
+pub const STRUCTURE_TYPE_DEBUG_UTILS_LABEL_EXT: u32 = 1000128002;
+pub const STRUCTURE_TYPE_IMAGE_FORMAT_LIST_CREATE_INFO_KHR: u32 = 1000147000;
+pub const STRUCTURE_TYPE_PHYSICAL_DEVICE_SURFACE_INFO_2_KHR: u32 = 1000119000;
+pub const STRUCTURE_TYPE_SURFACE_CAPABILITIES_2_KHR: u32 = 1000119001;
+pub const STRUCTURE_TYPE_SURFACE_FORMAT_2_KHR: u32 = 1000119002;
+pub const STRUCTURE_TYPE_SURFACE_PROTECTED_CAPABILITIES_KHR: u32 = 1000239000;
+pub const STRUCTURE_TYPE_PHYSICAL_DEVICE_FEATURES_2: u32 = 1000059000;
+pub const STRUCTURE_TYPE_PHYSICAL_DEVICE_PROTECTED_MEMORY_FEATURES: u32 = 1000145000;
+pub const STRUCTURE_TYPE_SURFACE_PRESENT_MODE_EXT: u32 = 1000274000;
+pub const STRUCTURE_TYPE_SURFACE_PRESENT_SCALING_CAPABILITIES_EXT: u32 = 1000274001;
+pub const STRUCTURE_TYPE_PHYSICAL_DEVICE_TIMELINE_SEMAPHORE_FEATURES_KHR: u32 = 1000207000;
+pub const STRUCTURE_TYPE_SEMAPHORE_TYPE_CREATE_INFO_KHR: u32 = 1000207002;
+pub const STRUCTURE_TYPE_SEMAPHORE_WAIT_INFO_KHR: u32 = 1000207004;
+pub const STRUCTURE_TYPE_SEMAPHORE_SIGNAL_INFO_KHR: u32 = 1000207005;
+pub const STRUCTURE_TYPE_SWAPCHAIN_PRESENT_FENCE_INFO_EXT: u32 = 1000275001;
+pub const STRUCTURE_TYPE_PRESENT_ID_KHR: u32 = 1000294000;
+pub const STRUCTURE_TYPE_PRESENT_REGIONS_KHR: u32 = 1000084000;
+pub const STRUCTURE_TYPE_PRESENT_TIMES_INFO_GOOGLE: u32 = 1000092000;
+pub const STRUCTURE_TYPE_SWAPCHAIN_PRESENT_SCALING_CREATE_INFO_EXT: u32 = 1000275004;
+pub const STRUCTURE_TYPE_RELEASE_SWAPCHAIN_IMAGES_INFO_EXT: u32 = 1000275005;
+
+pub type DeviceEventTypeEXT = u32;
+pub const DEVICE_EVENT_TYPE_DISPLAY_HOTPLUG_EXT: u32 = 0;
+
+pub type DisplayEventTypeEXT = u32;
+pub const DISPLAY_EVENT_TYPE_FIRST_PIXEL_OUT_EXT: u32 = 0;
+
+pub type SurfaceCounterFlagBitsEXT = u32;
+pub const SURFACE_COUNTER_VBLANK_BIT_EXT: u32 = 0x00000001;
+pub type SurfaceCounterFlagsEXT = Flags;
 
 pub type SystemAllocationScope = u32;
 pub const SYSTEM_ALLOCATION_SCOPE_COMMAND: u32 = 0;
@@ -884,6 +930,21 @@ pub type DisplaySurfaceCreateFlagsKHR = Flags;
 
 pub type ColorSpaceKHR = u32;
 pub const COLORSPACE_SRGB_NONLINEAR_KHR: u32 = 0;
+pub const COLOR_SPACE_DISPLAY_P3_NONLINEAR_EXT: u32 = 1000104001;
+pub const COLOR_SPACE_EXTENDED_SRGB_LINEAR_EXT: u32 = 1000104002;
+pub const COLOR_SPACE_DISPLAY_P3_LINEAR_EXT: u32 = 1000104003;
+pub const COLOR_SPACE_DCI_P3_NONLINEAR_EXT: u32 = 1000104004;
+pub const COLOR_SPACE_BT709_LINEAR_EXT: u32 = 1000104005;
+pub const COLOR_SPACE_BT709_NONLINEAR_EXT: u32 = 1000104006;
+pub const COLOR_SPACE_BT2020_LINEAR_EXT: u32 = 1000104007;
+pub const COLOR_SPACE_HDR10_ST2084_EXT: u32 = 1000104008;
+pub const COLOR_SPACE_DOLBYVISION_EXT: u32 = 1000104009;
+pub const COLOR_SPACE_HDR10_HLG_EXT: u32 = 1000104010;
+pub const COLOR_SPACE_ADOBERGB_LINEAR_EXT: u32 = 1000104011;
+pub const COLOR_SPACE_ADOBERGB_NONLINEAR_EXT: u32 = 1000104012;
+pub const COLOR_SPACE_PASS_THROUGH_EXT: u32 = 1000104013;
+pub const COLOR_SPACE_EXTENDED_SRGB_NONLINEAR_EXT: u32 = 1000104014;
+pub const COLOR_SPACE_DISPLAY_NATIVE_AMD: u32 = 1000213000;
 
 pub type PresentModeKHR = u32;
 pub const PRESENT_MODE_IMMEDIATE_KHR: u32 = 0;
@@ -953,12 +1014,37 @@ pub const DEBUG_REPORT_ERROR_BIT_EXT: u32 = 0x00000008;
 pub const DEBUG_REPORT_DEBUG_BIT_EXT: u32 = 0x00000010;
 pub type DebugReportFlagsEXT = Flags;
 
+pub type ExternalSemaphoreHandleTypeFlagBitsKHR = u32;
+pub const EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_FD_BIT_KHR: u32 = 0x00000001;
+pub const EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_WIN32_BIT_KHR: u32 = 0x00000002;
+pub const EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_WIN32_KMT_BIT_KHR: u32 = 0x00000004;
+pub const EXTERNAL_SEMAPHORE_HANDLE_TYPE_D3D12_FENCE_BIT_KHR: u32 = 0x00000008;
+pub const EXTERNAL_SEMAPHORE_HANDLE_TYPE_SYNC_FD_BIT_KHR: u32 = 0x00000010;
+pub type ExternalSemaphoreHandleTypeFlagsKHR = Flags;
+
+pub type ExternalSemaphoreFeatureFlagBitsKHR = u32;
+pub const EXTERNAL_SEMAPHORE_FEATURE_EXPORTABLE_BIT_KHR: u32 = 0x00000001;
+pub const EXTERNAL_SEMAPHORE_FEATURE_IMPORTABLE_BIT_KHR: u32 = 0x00000002;
+pub type ExternalSemaphoreFeatureFlagsKHR = Flags;
+
+pub type SemaphoreImportFlagBitsKHR = u32;
+pub const SEMAPHORE_IMPORT_TEMPORARY_BIT_KHR: u32 = 0x00000001;
+pub type SemaphoreImportFlagsKHR = Flags;
+
+pub type SemaphoreTypeKHR = u32;
+pub const SEMAPHORE_TYPE_BINARY_KHR: u32 = 0;
+pub const SEMAPHORE_TYPE_TIMELINE_KHR: u32 = 1;
+
+pub type SemaphoreWaitFlagBitsKHR = u32;
+pub const SEMAPHORE_WAIT_ANY_BIT_KHR: u32 = 0x00000001;
+pub type SemaphoreWaitFlagsKHR = Flags;
+
 
 pub type PFN_vkAllocationFunction = extern "system" fn(*mut c_void, usize, usize, SystemAllocationScope) -> *mut c_void;
 pub type PFN_vkReallocationFunction = extern "system" fn(*mut c_void, *mut c_void, usize, usize, SystemAllocationScope) -> *mut c_void;
 pub type PFN_vkFreeFunction = extern "system" fn(*mut c_void, *mut c_void);
-pub type PFN_vkInternalAllocationNotification = extern "system" fn(*mut c_void, usize, InternalAllocationType, SystemAllocationScope) -> *mut c_void;
-pub type PFN_vkInternalFreeNotification = extern "system" fn(*mut c_void, usize, InternalAllocationType, SystemAllocationScope) -> *mut c_void;
+pub type PFN_vkInternalAllocationNotification = extern "system" fn(*mut c_void, usize, InternalAllocationType, SystemAllocationScope);
+pub type PFN_vkInternalFreeNotification = extern "system" fn(*mut c_void, usize, InternalAllocationType, SystemAllocationScope);
 pub type PFN_vkDebugReportCallbackEXT = extern "system" fn(DebugReportFlagsEXT, DebugReportObjectTypeEXT, u64, usize, i32, *const c_char, *const c_char, *mut c_void) -> Bool32;
 
 pub type PFN_vkVoidFunction = extern "system" fn() -> ();
@@ -992,8 +1078,8 @@ pub struct AllocationCallbacks {
     pub pfnAllocation: PFN_vkAllocationFunction,
     pub pfnReallocation: PFN_vkReallocationFunction,
     pub pfnFree: PFN_vkFreeFunction,
-    pub pfnInternalAllocation: PFN_vkInternalAllocationNotification,
-    pub pfnInternalFree: PFN_vkInternalFreeNotification,
+    pub pfnInternalAllocation: Option<PFN_vkInternalAllocationNotification>,
+    pub pfnInternalFree: Option<PFN_vkInternalFreeNotification>,
 }
 
 #[repr(C)]
@@ -2177,6 +2263,76 @@ pub struct SurfaceFormatKHR {
 }
 
 pub type SwapchainCreateFlagsKHR = Flags;
+pub const SWAPCHAIN_CREATE_SPLIT_INSTANCE_BIND_REGIONS_BIT_KHR: u32 = 0x00000001;
+pub const SWAPCHAIN_CREATE_PROTECTED_BIT_KHR: u32 = 0x00000002;
+pub const SWAPCHAIN_CREATE_MUTABLE_FORMAT_BIT_KHR: u32 = 0x00000004;
+pub const SWAPCHAIN_CREATE_DEFERRED_MEMORY_ALLOCATION_BIT_EXT: u32 = 0x00000008;
+
+pub type PresentScalingFlagBitsEXT = u32;
+pub const PRESENT_SCALING_ONE_TO_ONE_BIT_EXT: u32 = 0x00000001;
+pub const PRESENT_SCALING_ASPECT_RATIO_STRETCH_BIT_EXT: u32 = 0x00000002;
+pub const PRESENT_SCALING_STRETCH_BIT_EXT: u32 = 0x00000004;
+pub type PresentScalingFlagsEXT = Flags;
+
+pub type PresentGravityFlagBitsEXT = u32;
+pub const PRESENT_GRAVITY_MIN_BIT_EXT: u32 = 0x00000001;
+pub const PRESENT_GRAVITY_MAX_BIT_EXT: u32 = 0x00000002;
+pub const PRESENT_GRAVITY_CENTERED_BIT_EXT: u32 = 0x00000004;
+pub type PresentGravityFlagsEXT = Flags;
+
+#[repr(C)]
+pub struct PhysicalDeviceSurfaceInfo2KHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub surface: SurfaceKHR,
+}
+
+#[repr(C)]
+pub struct SurfaceCapabilities2KHR {
+    pub sType: StructureType,
+    pub pNext: *mut c_void,
+    pub surfaceCapabilities: SurfaceCapabilitiesKHR,
+}
+
+#[repr(C)]
+pub struct SurfaceFormat2KHR {
+    pub sType: StructureType,
+    pub pNext: *mut c_void,
+    pub surfaceFormat: SurfaceFormatKHR,
+}
+
+#[repr(C)]
+pub struct SurfaceProtectedCapabilitiesKHR {
+    pub sType: StructureType,
+    pub pNext: *mut c_void,
+    pub supportsProtected: Bool32,
+}
+
+#[repr(C)]
+pub struct SurfacePresentModeEXT {
+    pub sType: StructureType,
+    pub pNext: *mut c_void,
+    pub presentMode: PresentModeKHR,
+}
+
+#[repr(C)]
+pub struct SurfacePresentScalingCapabilitiesEXT {
+    pub sType: StructureType,
+    pub pNext: *mut c_void,
+    pub supportedPresentScaling: PresentScalingFlagsEXT,
+    pub supportedPresentGravityX: PresentGravityFlagsEXT,
+    pub supportedPresentGravityY: PresentGravityFlagsEXT,
+    pub minScaledImageExtent: Extent2D,
+    pub maxScaledImageExtent: Extent2D,
+}
+
+#[repr(C)]
+pub struct ImageFormatListCreateInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub viewFormatCount: u32,
+    pub pViewFormats: *const Format,
+}
 
 #[repr(C)]
 pub struct SwapchainCreateInfoKHR {
@@ -2355,6 +2511,38 @@ pub struct Win32SurfaceCreateInfoKHR {
     pub hwnd: *mut c_void,
 }
 
+pub type MetalSurfaceCreateFlagsEXT = Flags;
+
+#[repr(C)]
+pub struct MetalSurfaceCreateInfoEXT {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub flags: MetalSurfaceCreateFlagsEXT,
+    pub pLayer: *const c_void,
+}
+
+pub type DirectFBSurfaceCreateFlagsEXT = Flags;
+
+#[repr(C)]
+pub struct DirectFBSurfaceCreateInfoEXT {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub flags: DirectFBSurfaceCreateFlagsEXT,
+    pub dfb: *mut c_void,
+    pub surface: *mut c_void,
+}
+
+pub type ScreenSurfaceCreateFlagsQNX = Flags;
+
+#[repr(C)]
+pub struct ScreenSurfaceCreateInfoQNX {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub flags: ScreenSurfaceCreateFlagsQNX,
+    pub context: *mut c_void,
+    pub window: *mut c_void,
+}
+
 
 #[repr(C)]
 pub struct DebugReportCallbackCreateInfoEXT {
@@ -2365,6 +2553,229 @@ pub struct DebugReportCallbackCreateInfoEXT {
     pub pUserData: *mut c_void,
 }
 
+#[repr(C)]
+pub struct DebugMarkerObjectNameInfoEXT {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub objectType: DebugReportObjectTypeEXT,
+    pub object: u64,
+    pub pObjectName: *const c_char,
+}
+
+#[repr(C)]
+pub struct DebugUtilsLabelEXT {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub pLabelName: *const c_char,
+    pub color: [f32; 4],
+}
+
+#[repr(C)]
+pub struct PresentTimeGOOGLE {
+    pub presentID: u32,
+    pub desiredPresentTime: u64,
+}
+
+#[repr(C)]
+pub struct PresentTimesInfoGOOGLE {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub swapchainCount: u32,
+    pub pTimes: *const PresentTimeGOOGLE,
+}
+
+#[repr(C)]
+pub struct PastPresentationTimingGOOGLE {
+    pub presentID: u32,
+    pub desiredPresentTime: u64,
+    pub actualPresentTime: u64,
+    pub earliestPresentTime: u64,
+    pub presentMargin: u64,
+}
+
+#[repr(C)]
+pub struct PresentIdKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub swapchainCount: u32,
+    pub pPresentIds: *const u64,
+}
+
+#[repr(C)]
+pub struct RectLayerKHR {
+    pub offset: Offset2D,
+    pub extent: Extent2D,
+    pub layer: u32,
+}
+
+#[repr(C)]
+pub struct PresentRegionKHR {
+    pub rectangleCount: u32,
+    pub pRectangles: *const RectLayerKHR,
+}
+
+#[repr(C)]
+pub struct PresentRegionsKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub swapchainCount: u32,
+    pub pRegions: *const PresentRegionKHR,
+}
+
+#[repr(C)]
+pub struct SwapchainPresentFenceInfoEXT {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub swapchainCount: u32,
+    pub pFences: *const Fence,
+}
+
+#[repr(C)]
+pub struct SwapchainPresentScalingCreateInfoEXT {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub scalingBehavior: PresentScalingFlagsEXT,
+    pub presentGravityX: PresentGravityFlagsEXT,
+    pub presentGravityY: PresentGravityFlagsEXT,
+}
+
+#[repr(C)]
+pub struct ReleaseSwapchainImagesInfoEXT {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub swapchain: SwapchainKHR,
+    pub imageIndexCount: u32,
+    pub pImageIndices: *const u32,
+}
+
+#[repr(C)]
+pub struct PhysicalDeviceExternalSemaphoreInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub handleType: ExternalSemaphoreHandleTypeFlagBitsKHR,
+}
+
+#[repr(C)]
+pub struct ExternalSemaphorePropertiesKHR {
+    pub sType: StructureType,
+    pub pNext: *mut c_void,
+    pub exportFromImportedHandleTypes: ExternalSemaphoreHandleTypeFlagsKHR,
+    pub compatibleHandleTypes: ExternalSemaphoreHandleTypeFlagsKHR,
+    pub externalSemaphoreFeatures: ExternalSemaphoreFeatureFlagsKHR,
+}
+
+#[repr(C)]
+pub struct ExportSemaphoreCreateInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub handleTypes: ExternalSemaphoreHandleTypeFlagsKHR,
+}
+
+#[repr(C)]
+pub struct ImportSemaphoreWin32HandleInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub semaphore: Semaphore,
+    pub flags: SemaphoreImportFlagsKHR,
+    pub handleType: ExternalSemaphoreHandleTypeFlagBitsKHR,
+    pub handle: *mut c_void,
+    pub name: *const u16,
+}
+
+#[repr(C)]
+pub struct SemaphoreGetWin32HandleInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub semaphore: Semaphore,
+    pub handleType: ExternalSemaphoreHandleTypeFlagBitsKHR,
+}
+
+#[repr(C)]
+pub struct PhysicalDeviceFeatures2 {
+    pub sType: StructureType,
+    pub pNext: *mut c_void,
+    pub features: PhysicalDeviceFeatures,
+}
+
+#[repr(C)]
+pub struct PhysicalDeviceProtectedMemoryFeatures {
+    pub sType: StructureType,
+    pub pNext: *mut c_void,
+    pub protectedMemory: Bool32,
+}
+
+#[repr(C)]
+pub struct PhysicalDeviceTimelineSemaphoreFeaturesKHR {
+    pub sType: StructureType,
+    pub pNext: *mut c_void,
+    pub timelineSemaphore: Bool32,
+}
+
+#[repr(C)]
+pub struct SemaphoreTypeCreateInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub semaphoreType: SemaphoreTypeKHR,
+    pub initialValue: u64,
+}
+
+#[repr(C)]
+pub struct SemaphoreWaitInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub flags: SemaphoreWaitFlagsKHR,
+    pub semaphoreCount: u32,
+    pub pSemaphores: *const Semaphore,
+    pub pValues: *const u64,
+}
+
+#[repr(C)]
+pub struct SemaphoreSignalInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub semaphore: Semaphore,
+    pub value: u64,
+}
+
+#[repr(C)]
+pub struct ImportSemaphoreFdInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub semaphore: Semaphore,
+    pub flags: SemaphoreImportFlagsKHR,
+    pub handleType: ExternalSemaphoreHandleTypeFlagBitsKHR,
+    pub fd: c_int,
+}
+
+#[repr(C)]
+pub struct SemaphoreGetFdInfoKHR {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub semaphore: Semaphore,
+    pub handleType: ExternalSemaphoreHandleTypeFlagBitsKHR,
+}
+
+#[repr(C)]
+pub struct DeviceEventInfoEXT {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub deviceEvent: DeviceEventTypeEXT,
+}
+
+#[repr(C)]
+pub struct DisplayEventInfoEXT {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub displayEvent: DisplayEventTypeEXT,
+}
+
+#[repr(C)]
+pub struct SwapchainCounterCreateInfoEXT {
+    pub sType: StructureType,
+    pub pNext: *const c_void,
+    pub surfaceCounters: SurfaceCounterFlagsEXT,
+}
+
 macro_rules! ptrs {
     ($struct_name:ident, { $($name:ident => ($($param_n:ident: $param_ty:ty),*) -> $ret:ty,)+ }) => (
         pub struct $struct_name {
@@ -2428,6 +2839,7 @@ ptrs!(InstancePointers, {
     EnumerateDeviceLayerProperties => (physicalDevice: PhysicalDevice, pPropertyCount: *mut u32, pProperties: *mut LayerProperties) -> Result,
     CreateDevice => (physicalDevice: PhysicalDevice, pCreateInfo: *const DeviceCreateInfo, pAllocator: *const AllocationCallbacks, pDevice: *mut Device) -> Result,
     GetPhysicalDeviceFeatures => (physicalDevice: PhysicalDevice, pFeatures: *mut PhysicalDeviceFeatures) -> (),
+    GetPhysicalDeviceFeatures2 => (physicalDevice: PhysicalDevice, pFeatures: *mut PhysicalDeviceFeatures2) -> (),
     GetPhysicalDeviceFormatProperties => (physicalDevice: PhysicalDevice, format: Format, pFormatProperties: *mut FormatProperties) -> (),
     GetPhysicalDeviceImageFormatProperties => (physicalDevice: PhysicalDevice, format: Format, ty: ImageType, tiling: ImageTiling, usage: ImageUsageFlags, flags: ImageCreateFlags, pImageFormatProperties: *mut ImageFormatProperties) -> Result,
     GetPhysicalDeviceProperties => (physicalDevice: PhysicalDevice, pProperties: *mut PhysicalDeviceProperties) -> (),
@@ -2445,6 +2857,9 @@ ptrs!(InstancePointers, {
     GetPhysicalDeviceMirPresentationSupportKHR => (physicalDevice: PhysicalDevice, queueFamilyIndex: u32, connection: *mut c_void) -> Bool32,
     CreateAndroidSurfaceKHR => (instance: Instance, pCreateInfo: *const AndroidSurfaceCreateInfoKHR, pAllocator: *const AllocationCallbacks, pSurface: *mut SurfaceKHR) -> Result,
     CreateWin32SurfaceKHR => (instance: Instance, pCreateInfo: *const Win32SurfaceCreateInfoKHR, pAllocator: *const AllocationCallbacks, pSurface: *mut SurfaceKHR) -> Result,
+    CreateMetalSurfaceEXT => (instance: Instance, pCreateInfo: *const MetalSurfaceCreateInfoEXT, pAllocator: *const AllocationCallbacks, pSurface: *mut SurfaceKHR) -> Result,
+    CreateDirectFBSurfaceEXT => (instance: Instance, pCreateInfo: *const DirectFBSurfaceCreateInfoEXT, pAllocator: *const AllocationCallbacks, pSurface: *mut SurfaceKHR) -> Result,
+    CreateScreenSurfaceQNX => (instance: Instance, pCreateInfo: *const ScreenSurfaceCreateInfoQNX, pAllocator: *const AllocationCallbacks, pSurface: *mut SurfaceKHR) -> Result,
     GetPhysicalDeviceWin32PresentationSupportKHR => (physicalDevice: PhysicalDevice, queueFamilyIndex: u32) -> Bool32,
     GetPhysicalDeviceDisplayPropertiesKHR => (physicalDevice: PhysicalDevice, pPropertyCount: *mut u32, pProperties: *mut DisplayPropertiesKHR) -> Result,
     GetPhysicalDeviceDisplayPlanePropertiesKHR => (physicalDevice: PhysicalDevice, pPropertyCount: *mut u32, pProperties: *mut DisplayPlanePropertiesKHR) -> Result,
@@ -2455,11 +2870,16 @@ ptrs!(InstancePointers, {
     CreateDisplayPlaneSurfaceKHR => (instance: Instance, pCreateInfo: *const DisplaySurfaceCreateInfoKHR, pAllocator: *const AllocationCallbacks, pSurface: *mut SurfaceKHR) -> Result,
     GetPhysicalDeviceSurfaceSupportKHR => (physicalDevice: PhysicalDevice, queueFamilyIndex: u32, surface: SurfaceKHR, pSupported: *mut Bool32) -> Result,
     GetPhysicalDeviceSurfaceCapabilitiesKHR => (physicalDevice: PhysicalDevice, surface: SurfaceKHR, pSurfaceCapabilities: *mut SurfaceCapabilitiesKHR) -> Result,
+    GetPhysicalDeviceSurfaceCapabilities2KHR => (physicalDevice: PhysicalDevice, pSurfaceInfo: *const PhysicalDeviceSurfaceInfo2KHR, pSurfaceCapabilities: *mut SurfaceCapabilities2KHR) -> Result,
+    GetPhysicalDeviceSurfaceFormats2KHR => (physicalDevice: PhysicalDevice, pSurfaceInfo: *const PhysicalDeviceSurfaceInfo2KHR, pSurfaceFormatCount: *mut u32, pSurfaceFormats: *mut SurfaceFormat2KHR) -> Result,
     GetPhysicalDeviceSurfaceFormatsKHR => (physicalDevice: PhysicalDevice, surface: SurfaceKHR, pSurfaceFormatCount: *mut u32, pSurfaceFormats: *mut SurfaceFormatKHR) -> Result,
     GetPhysicalDeviceSurfacePresentModesKHR => (physicalDevice: PhysicalDevice, surface: SurfaceKHR, pPresentModeCount: *mut u32, pPresentModes: *mut PresentModeKHR) -> Result,
+    GetPhysicalDevicePresentRectanglesKHR => (physicalDevice: PhysicalDevice, surface: SurfaceKHR, pRectCount: *mut u32, pRects: *mut Rect2D) -> Result,
     CreateDebugReportCallbackEXT => (instance: Instance, pCreateInfo: *const DebugReportCallbackCreateInfoEXT, pAllocator: *const AllocationCallbacks, pCallback: *mut DebugReportCallbackEXT) -> Result,
     DestroyDebugReportCallbackEXT => (instance: Instance, callback: DebugReportCallbackEXT, pAllocator: *const AllocationCallbacks) -> (),
     DebugReportMessageEXT => (instance: Instance, flags: DebugReportFlagsEXT, objectType: DebugReportObjectTypeEXT, object: u64, location: usize, messageCode: i32, pLayerPrefix: *const c_char, pMessage: *const c_char) -> (),
+    GetPhysicalDeviceExternalSemaphoreProperties => (physicalDevice: PhysicalDevice, pExternalSemaphoreInfo: *const PhysicalDeviceExternalSemaphoreInfoKHR, pExternalSemaphoreProperties: *mut ExternalSemaphorePropertiesKHR) -> (),
+    GetPhysicalDeviceExternalSemaphorePropertiesKHR => (physicalDevice: PhysicalDevice, pExternalSemaphoreInfo: *const PhysicalDeviceExternalSemaphoreInfoKHR, pExternalSemaphoreProperties: *mut ExternalSemaphorePropertiesKHR) -> (),
 });
 
 ptrs!(DevicePointers, {
@@ -2589,4 +3009,22 @@ ptrs!(DevicePointers, {
     AcquireNextImageKHR => (device: Device, swapchain: SwapchainKHR, timeout: u64, semaphore: Semaphore, fence: Fence, pImageIndex: *mut u32) -> Result,
     QueuePresentKHR => (queue: Queue, pPresentInfo: *const PresentInfoKHR) -> Result,
     CreateSharedSwapchainsKHR => (device: Device, swapchainCount: u32, pCreateInfos: *const SwapchainCreateInfoKHR, pAllocator: *const AllocationCallbacks, pSwapchains: *mut SwapchainKHR) -> Result,
+    ImportSemaphoreFdKHR => (device: Device, pImportSemaphoreFdInfo: *const ImportSemaphoreFdInfoKHR) -> Result,
+    GetSemaphoreFdKHR => (device: Device, pGetFdInfo: *const SemaphoreGetFdInfoKHR, pFd: *mut c_int) -> Result,
+    ImportSemaphoreWin32HandleKHR => (device: Device, pImportSemaphoreWin32HandleInfo: *const ImportSemaphoreWin32HandleInfoKHR) -> Result,
+    GetSemaphoreWin32HandleKHR => (device: Device, pGetWin32HandleInfo: *const SemaphoreGetWin32HandleInfoKHR, pHandle: *mut *mut c_void) -> Result,
+    GetSemaphoreCounterValue => (device: Device, semaphore: Semaphore, pValue: *mut u64) -> Result,
+    GetSemaphoreCounterValueKHR => (device: Device, semaphore: Semaphore, pValue: *mut u64) -> Result,
+    WaitSemaphores => (device: Device, pWaitInfo: *const SemaphoreWaitInfoKHR, timeout: u64) -> Result,
+    WaitSemaphoresKHR => (device: Device, pWaitInfo: *const SemaphoreWaitInfoKHR, timeout: u64) -> Result,
+    SignalSemaphore => (device: Device, pSignalInfo: *const SemaphoreSignalInfoKHR) -> Result,
+    SignalSemaphoreKHR => (device: Device, pSignalInfo: *const SemaphoreSignalInfoKHR) -> Result,
+    DebugMarkerSetObjectNameEXT => (device: Device, pNameInfo: *const DebugMarkerObjectNameInfoEXT) -> Result,
+    QueueInsertDebugUtilsLabelEXT => (queue: Queue, pLabelInfo: *const DebugUtilsLabelEXT) -> (),
+    WaitForPresentKHR => (device: Device, swapchain: SwapchainKHR, presentId: u64, timeout: u64) -> Result,
+    ReleaseSwapchainImagesEXT => (device: Device, pReleaseInfo: *const ReleaseSwapchainImagesInfoEXT) -> Result,
+    GetPastPresentationTimingGOOGLE => (device: Device, swapchain: SwapchainKHR, pPresentationTimingCount: *mut u32, pPresentationTimings: *mut PastPresentationTimingGOOGLE) -> Result,
+    GetSwapchainCounterEXT => (device: Device, swapchain: SwapchainKHR, counter: SurfaceCounterFlagBitsEXT, pCounterValue: *mut u64) -> Result,
+    RegisterDeviceEventEXT => (device: Device, pDeviceEventInfo: *const DeviceEventInfoEXT, pAllocator: *const AllocationCallbacks, pFence: *mut Fence) -> Result,
+    RegisterDisplayEventEXT => (device: Device, display: DisplayKHR, pDisplayEventInfo: *const DisplayEventInfoEXT, pAllocator: *const AllocationCallbacks, pFence: *mut Fence) -> Result,
 });
@@ -532,6 +532,60 @@ impl InnerCommandBufferBuilder {
         self
     }
 
+    /// Records a barrier that transfers the ownership of a color image from a queue family to
+    /// another.
+    ///
+    /// The image must be in `layout` and stays in this layout.
+    ///
+    /// # Safety
+    ///
+    /// - Care must be taken to respect the rules about secondary command buffers.
+    /// - The same barrier must be recorded in a command buffer of `src_family` (the release)
+    ///   and in a command buffer of `dst_family` (the acquire), and the acquire must happen
+    ///   after the release.
+    ///
+    pub unsafe fn queue_family_ownership_transfer<I>(mut self, image: &Arc<I>,
+                                                     layout: ImageLayout, src_family: u32,
+                                                     dst_family: u32)
+                                                     -> InnerCommandBufferBuilder
+        where I: Image + 'static
+    {
+        debug_assert!(self.render_pass_staging_commands.is_empty());
+
+        self.keep_alive.push(image.clone() as Arc<_>);
+
+        let raw_image = image.inner_image().internal_object();
+        let levels = image.inner_image().mipmap_levels();
+        let layers = image.dimensions().array_layers();
+
+        self.staging_commands.push(Box::new(move |vk, cmd| {
+            let barrier = vk::ImageMemoryBarrier {
+                sType: vk::STRUCTURE_TYPE_IMAGE_MEMORY_BARRIER,
+                pNext: ptr::null(),
+                srcAccessMask: vk::ACCESS_COLOR_ATTACHMENT_WRITE_BIT,  // ignored by the acquire
+                dstAccessMask: 0,       // ignored by the release
+                oldLayout: layout as u32,
+                newLayout: layout as u32,
+                srcQueueFamilyIndex: src_family,
+                dstQueueFamilyIndex: dst_family,
+                image: raw_image,
+                subresourceRange: vk::ImageSubresourceRange {
+                    aspectMask: vk::IMAGE_ASPECT_COLOR_BIT,
+                    baseMipLevel: 0,
+                    levelCount: levels,
+                    baseArrayLayer: 0,
+                    layerCount: layers,
+                },
+            };
+
+            vk.CmdPipelineBarrier(cmd, vk::PIPELINE_STAGE_ALL_COMMANDS_BIT,
+                                  vk::PIPELINE_STAGE_ALL_COMMANDS_BIT, 0, 0, ptr::null(), 0,
+                                  ptr::null(), 1, &barrier);
+        }));
+
+        self
+    }
+
     /// Copies data from a buffer to a color image.
     ///
     /// This operation can be performed by any kind of queue.
@@ -1779,74 +1833,109 @@ pub struct InnerCommandBuffer {
 ///
 pub fn submit(me: &InnerCommandBuffer, me_arc: Arc<KeepAlive>,
               queue: &Arc<Queue>) -> Result<Arc<Submission>, OomError>   // TODO: wrong error type
+{
+    let mut submissions = try!(submit_multiple(&[(me, me_arc)], queue));
+    debug_assert_eq!(submissions.len(), 1);
+    Ok(submissions.pop().unwrap())
+}
+
+/// Submits multiple command buffers to a queue with a single call to `vkQueueSubmit`.
+///
+/// Returns one `Submission` per command buffer, in the same order. The command buffers are
+/// executed in order and all the submissions share the same fence.
+///
+/// # Panic
+///
+/// - Panicks if the queue doesn't belong to the device one of the command buffers was created
+///   with.
+/// - Panicks if the queue doesn't belong to the family the pool of one of the command buffers
+///   was created with.
+///
+pub fn submit_multiple(cmds: &[(&InnerCommandBuffer, Arc<KeepAlive>)], queue: &Arc<Queue>)
+                       -> Result<Vec<Arc<Submission>>, OomError>   // TODO: wrong error type
 {
     // TODO: see comment of GLOBAL_MUTEX
     let _global_lock = GLOBAL_MUTEX.lock().unwrap();
 
-    let vk = me.device.pointers();
+    let vk = queue.device().pointers();
 
-    assert_eq!(queue.device().internal_object(), me.pool.device().internal_object());
-    assert_eq!(queue.family().id(), me.pool.queue_family().id());
+    for &(me, _) in cmds.iter() {
+        assert_eq!(queue.device().internal_object(), me.pool.device().internal_object());
+        assert_eq!(queue.family().id(), me.pool.queue_family().id());
+    }
 
     // TODO: check if this change is okay (maybe the Arc can be omitted?) - Mixthos
     //let fence = try!(Fence::new(queue.device()));
     let fence = Arc::new(try!(Fence::raw(queue.device())));
 
-    let mut keep_alive_semaphores = SmallVec::<[_; 8]>::new();
-    let mut post_semaphores_ids = SmallVec::<[_; 8]>::new();
-    let mut pre_semaphores_ids = SmallVec::<[_; 8]>::new();
-    let mut pre_semaphores_stages = SmallVec::<[_; 8]>::new();
+    let mut pending = Vec::with_capacity(cmds.len());
+    let mut submissions = Vec::with_capacity(cmds.len());
 
-    // Each queue has a dedicated semaphore which must be signalled and waited upon by each
-    // command buffer submission.
-    // TODO: for now that's not true ^  as semaphores are only used once then destroyed ;
-    //       waiting on https://github.com/KhronosGroup/Vulkan-Docs/issues/155
-    {
-        // TODO: check if this change is okay (maybe the Arc can be omitted?) - Mixthos
-        //let signalled = try!(Semaphore::new(queue.device()));
-        let signalled = Arc::new(try!(Semaphore::raw(queue.device())));
-        let wait = unsafe { queue.dedicated_semaphore(signalled.clone()) };
-        if let Some(wait) = wait {
-            pre_semaphores_ids.push(wait.internal_object());
-            pre_semaphores_stages.push(vk::PIPELINE_STAGE_TOP_OF_PIPE_BIT);     // TODO:
-            keep_alive_semaphores.push(wait);
-        }
-        post_semaphores_ids.push(signalled.internal_object());
-        keep_alive_semaphores.push(signalled);
-    }
+    for &(me, ref me_arc) in cmds.iter() {
+        let mut keep_alive_semaphores = SmallVec::new();
+        let mut post_semaphores_ids = SmallVec::new();
+        let mut pre_semaphores_ids = SmallVec::new();
+        let mut pre_semaphores_stages = SmallVec::new();
 
-    // Creating additional semaphores, one for each queue transition.
-    let queue_transitions_hint: u32 = 2;        // TODO: get as function parameter
-    // TODO: use a pool
-    let semaphores_to_signal = {
-        let mut list = SmallVec::new();
-        for _ in 0 .. queue_transitions_hint {
+        // Each queue has a dedicated semaphore which must be signalled and waited upon by each
+        // command buffer submission.
+        // TODO: for now that's not true ^  as semaphores are only used once then destroyed ;
+        //       waiting on https://github.com/KhronosGroup/Vulkan-Docs/issues/155
+        {
             // TODO: check if this change is okay (maybe the Arc can be omitted?) - Mixthos
-            //let sem = try!(Semaphore::new(queue.device()));
-            let sem = Arc::new(try!(Semaphore::raw(queue.device())));
-            post_semaphores_ids.push(sem.internal_object());
-            keep_alive_semaphores.push(sem.clone());
-            list.push(sem);
+            //let signalled = try!(Semaphore::new(queue.device()));
+            let signalled = Arc::new(try!(Semaphore::raw(queue.device())));
+            let wait = unsafe { queue.dedicated_semaphore(signalled.clone()) };
+            if let Some(wait) = wait {
+                pre_semaphores_ids.push(wait.internal_object());
+                pre_semaphores_stages.push(vk::PIPELINE_STAGE_TOP_OF_PIPE_BIT);     // TODO:
+                keep_alive_semaphores.push(wait);
+            }
+            post_semaphores_ids.push(signalled.internal_object());
+            keep_alive_semaphores.push(signalled);
         }
-        list
-    };
 
-    // We can now create the `Submission` object.
-    // We need to create it early because we pass it when calling `gpu_access`.
-    let submission = Arc::new(Submission {
-        fence: fence.clone(),
-        queue: queue.clone(),
-        guarded: Mutex::new(SubmissionGuarded {
-            signalled_semaphores: semaphores_to_signal,
-            signalled_queues: SmallVec::new(),
-        }),
-        keep_alive_cb: Mutex::new({ let mut v = SmallVec::new(); v.push(me_arc); v }),
-        keep_alive_semaphores: Mutex::new(SmallVec::new()),
-    });
-
-    // List of command buffers to submit before and after the main one.
-    let mut before_command_buffers: SmallVec<[_; 4]> = SmallVec::new();
-    let mut after_command_buffers: SmallVec<[_; 4]> = SmallVec::new();
+        // Creating additional semaphores, one for each queue transition.
+        let queue_transitions_hint: u32 = 2;        // TODO: get as function parameter
+        // TODO: use a pool
+        let semaphores_to_signal = {
+            let mut list = SmallVec::new();
+            for _ in 0 .. queue_transitions_hint {
+                // TODO: check if this change is okay (maybe the Arc can be omitted?) - Mixthos
+                //let sem = try!(Semaphore::new(queue.device()));
+                let sem = Arc::new(try!(Semaphore::raw(queue.device())));
+                post_semaphores_ids.push(sem.internal_object());
+                keep_alive_semaphores.push(sem.clone());
+                list.push(sem);
+            }
+            list
+        };
+
+        // We can now create the `Submission` object.
+        // We need to create it early because we pass it when calling `gpu_access`.
+        submissions.push(Arc::new(Submission {
+            fence: fence.clone(),
+            queue: queue.clone(),
+            guarded: Mutex::new(SubmissionGuarded {
+                signalled_semaphores: semaphores_to_signal,
+                signalled_queues: SmallVec::new(),
+            }),
+            keep_alive_cb: Mutex::new({ let mut v = SmallVec::new(); v.push(me_arc.clone()); v }),
+            keep_alive_semaphores: Mutex::new(SmallVec::new()),
+        }));
+
+        pending.push(PendingSubmit {
+            cmd: me,
+            keep_alive_semaphores: keep_alive_semaphores,
+            post_semaphores_ids: post_semaphores_ids,
+            pre_semaphores_ids: pre_semaphores_ids,
+            pre_semaphores_stages: pre_semaphores_stages,
+            before_command_buffers: SmallVec::new(),
+            after_command_buffers: SmallVec::new(),
+            before_semaphore: 0,
+            after_semaphore: 0,
+        });
+    }
 
     {
         // There is a possibility that a parallel thread is currently submitting a command buffer to
@@ -1856,184 +1945,212 @@ pub fn submit(me: &InnerCommandBuffer, me_arc: Arc<KeepAlive>,
         //
         // Since we need to access the content of `guarded` in our dependencies, we lock our own
         // `guarded` here to avoid being used as a dependency before being submitted.
-        // TODO: this needs a new block (https://github.com/rust-lang/rfcs/issues/811)
-        let _submission_lock = submission.guarded.lock().unwrap();
-
-        // Now we determine which earlier submissions we must depend upon.
-        let mut dependencies = SmallVec::<[Arc<Submission>; 6]>::new();
+        let _submission_locks = submissions.iter().map(|submission| {
+            submission.guarded.lock().unwrap()
+        }).collect::<Vec<_>>();
+
+        for (p, submission) in pending.iter_mut().zip(submissions.iter()) {
+            let me = p.cmd;
+
+            // Now we determine which earlier submissions we must depend upon.
+            let mut dependencies = SmallVec::<[Arc<Submission>; 6]>::new();
+
+            // Buffers first.
+            for &(ref resource, ref ranges) in me.extern_buffers_sync.iter() {
+                let result = unsafe { resource.gpu_access(&mut ranges.iter().cloned(), submission) };
+                if let Some(semaphore) = result.additional_wait_semaphore {
+                    semaphore.consume_temporary_payload();
+                    semaphore.debug_track_wait();
+                    p.pre_semaphores_ids.push(semaphore.internal_object());
+                    p.pre_semaphores_stages.push(vk::PIPELINE_STAGE_TOP_OF_PIPE_BIT);     // TODO:
+                    p.keep_alive_semaphores.push(semaphore);
+                }
 
-        // Buffers first.
-        for &(ref resource, ref ranges) in me.extern_buffers_sync.iter() {
-            let result = unsafe { resource.gpu_access(&mut ranges.iter().cloned(), &submission) };
-            if let Some(semaphore) = result.additional_wait_semaphore {
-                pre_semaphores_ids.push(semaphore.internal_object());
-                pre_semaphores_stages.push(vk::PIPELINE_STAGE_TOP_OF_PIPE_BIT);     // TODO:
-                keep_alive_semaphores.push(semaphore);
-            }
+                if let Some(semaphore) = result.additional_signal_semaphore {
+                    semaphore.debug_track_signal();
+                    p.post_semaphores_ids.push(semaphore.internal_object());
+                    p.keep_alive_semaphores.push(semaphore);
+                }
 
-            if let Some(semaphore) = result.additional_signal_semaphore {
-                post_semaphores_ids.push(semaphore.internal_object());
-                keep_alive_semaphores.push(semaphore);
+                dependencies.extend(result.dependencies.into_iter());
             }
 
-            dependencies.extend(result.dependencies.into_iter());
-        }
+            // Then images.
+            for &(ref resource, ref ranges) in me.extern_images_sync.iter() {
+                let result = unsafe { resource.gpu_access(&mut ranges.iter().cloned(), submission) };
 
-        // Then images.
-        for &(ref resource, ref ranges) in me.extern_images_sync.iter() {
-            let result = unsafe { resource.gpu_access(&mut ranges.iter().cloned(), &submission) };
+                if let Some(semaphore) = result.additional_wait_semaphore {
+                    semaphore.consume_temporary_payload();
+                    semaphore.debug_track_wait();
+                    p.pre_semaphores_ids.push(semaphore.internal_object());
+                    p.pre_semaphores_stages.push(vk::PIPELINE_STAGE_TOP_OF_PIPE_BIT);     // TODO:
+                    p.keep_alive_semaphores.push(semaphore);
+                }
 
-            if let Some(semaphore) = result.additional_wait_semaphore {
-                pre_semaphores_ids.push(semaphore.internal_object());
-                pre_semaphores_stages.push(vk::PIPELINE_STAGE_TOP_OF_PIPE_BIT);     // TODO:
-                keep_alive_semaphores.push(semaphore);
-            }
+                if let Some(semaphore) = result.additional_signal_semaphore {
+                    semaphore.debug_track_signal();
+                    p.post_semaphores_ids.push(semaphore.internal_object());
+                    p.keep_alive_semaphores.push(semaphore);
+                }
 
-            if let Some(semaphore) = result.additional_signal_semaphore {
-                post_semaphores_ids.push(semaphore.internal_object());
-                keep_alive_semaphores.push(semaphore);
-            }
+                for transition in result.before_transitions {
+                    let cb = transition_cb(&me.pool, resource.clone(), transition.block, transition.from, transition.to).unwrap();
+                    p.before_command_buffers.push(cb.cmd);
+                    submission.keep_alive_cb.lock().unwrap().push(Arc::new(cb));
+                }
 
-            for transition in result.before_transitions {
-                let cb = transition_cb(&me.pool, resource.clone(), transition.block, transition.from, transition.to).unwrap();
-                before_command_buffers.push(cb.cmd);
-                submission.keep_alive_cb.lock().unwrap().push(Arc::new(cb));
-            }
+                for transition in result.after_transitions {
+                    let cb = transition_cb(&me.pool, resource.clone(), transition.block, transition.from, transition.to).unwrap();
+                    p.after_command_buffers.push(cb.cmd);
+                    submission.keep_alive_cb.lock().unwrap().push(Arc::new(cb));
+                }
 
-            for transition in result.after_transitions {
-                let cb = transition_cb(&me.pool, resource.clone(), transition.block, transition.from, transition.to).unwrap();
-                after_command_buffers.push(cb.cmd);
-                submission.keep_alive_cb.lock().unwrap().push(Arc::new(cb));
+                dependencies.extend(result.dependencies.into_iter());
             }
 
-            dependencies.extend(result.dependencies.into_iter());
-        }
-
-        // For each dependency, we either wait on one of its semaphores, or create a new one.
-        for dependency in dependencies.iter() {
-            let current_queue_id = (queue.family().id(), queue.id_within_family());
+            // For each dependency, we either wait on one of its semaphores, or create a new one.
+            for dependency in dependencies.iter() {
+                let current_queue_id = (queue.family().id(), queue.id_within_family());
 
-            // If we submit to the same queue as your dependency, no need to worry about this.
-            if current_queue_id == (dependency.queue.family().id(),
-                                    dependency.queue.id_within_family())
-            {
-                continue;
-            }
+                // If we submit to the same queue as your dependency, no need to worry about this.
+                // This is also the case of dependencies that are part of the same batch.
+                if current_queue_id == (dependency.queue.family().id(),
+                                        dependency.queue.id_within_family())
+                {
+                    continue;
+                }
 
-            let mut guard = dependency.guarded.lock().unwrap();
+                let mut guard = dependency.guarded.lock().unwrap();
 
-            // If the current queue is in the list of already-signalled queue of the dependency, we
-            // ignore it.
-            if guard.signalled_queues.iter().find(|&&elem| elem == current_queue_id).is_some() {
-                continue;
-            }
+                // If the current queue is in the list of already-signalled queue of the dependency, we
+                // ignore it.
+                if guard.signalled_queues.iter().find(|&&elem| elem == current_queue_id).is_some() {
+                    continue;
+                }
 
-            // Otherwise, try to extract a semaphore from the semaphores that were signalled by the
-            // dependency.
-            let semaphore = guard.signalled_semaphores.pop();
-            guard.signalled_queues.push(current_queue_id);
+                // Otherwise, try to extract a semaphore from the semaphores that were signalled by the
+                // dependency.
+                let semaphore = guard.signalled_semaphores.pop();
+                guard.signalled_queues.push(current_queue_id);
 
-            let semaphore = if let Some(semaphore) = semaphore {
-                semaphore
+                let semaphore = if let Some(semaphore) = semaphore {
+                    semaphore
 
-            } else {
-                // This path is the slow path in the case where the user gave the wrong hint about
-                // the number of queue transitions.
-                // The only thing left to do is submit a dummy command buffer and a dummy semaphore
-                // in the source queue.
-                unimplemented!()    // FIXME:
-            };
+                } else {
+                    // This path is the slow path in the case where the user gave the wrong hint about
+                    // the number of queue transitions.
+                    // The only thing left to do is submit a dummy command buffer and a dummy semaphore
+                    // in the source queue.
+                    unimplemented!()    // FIXME:
+                };
 
-            pre_semaphores_ids.push(semaphore.internal_object());
-            pre_semaphores_stages.push(vk::PIPELINE_STAGE_TOP_OF_PIPE_BIT);     // TODO:
-            keep_alive_semaphores.push(semaphore);
-
-            // Note that it may look dangerous to unlock the dependency's mutex here, because the
-            // queue has already been added to the list of signalled queues but the command that
-            // signals the semaphore hasn't been sent yet.
-            //
-            // However submitting to a queue must lock the queue, which guarantees that no other
-            // parallel queue submission should happen on this same queue. This means that the problem
-            // is non-existing.
-        }
+                p.pre_semaphores_ids.push(semaphore.internal_object());
+                p.pre_semaphores_stages.push(vk::PIPELINE_STAGE_TOP_OF_PIPE_BIT);     // TODO:
+                p.keep_alive_semaphores.push(semaphore);
+
+                // Note that it may look dangerous to unlock the dependency's mutex here, because the
+                // queue has already been added to the list of signalled queues but the command that
+                // signals the semaphore hasn't been sent yet.
+                //
+                // However submitting to a queue must lock the queue, which guarantees that no other
+                // parallel queue submission should happen on this same queue. This means that the problem
+                // is non-existing.
+            }
 
-        unsafe {
-            let mut infos = SmallVec::<[_; 3]>::new();
+            if !p.before_command_buffers.is_empty() {
+                // TODO: Use try!()? - Mixthos
+                let semaphore = Semaphore::new(queue.device());
+                p.before_semaphore = semaphore.internal_object();
+                p.pre_semaphores_stages.push(vk::PIPELINE_STAGE_TOP_OF_PIPE_BIT);     // TODO:
+                p.pre_semaphores_ids.push(semaphore.internal_object());
+                p.keep_alive_semaphores.push(semaphore);
+            }
 
-            if !before_command_buffers.is_empty() {
+            if !p.after_command_buffers.is_empty() {
                 // TODO: Use try!()? - Mixthos
                 let semaphore = Semaphore::new(queue.device());
-                let semaphore_id = semaphore.internal_object();
-                pre_semaphores_stages.push(vk::PIPELINE_STAGE_TOP_OF_PIPE_BIT);     // TODO:
-                pre_semaphores_ids.push(semaphore.internal_object());
-                keep_alive_semaphores.push(semaphore);
+                p.after_semaphore = semaphore.internal_object();
+                p.keep_alive_semaphores.push(semaphore);
+            }
 
+            debug_assert_eq!(p.pre_semaphores_ids.len(), p.pre_semaphores_stages.len());
+        }
+
+        // The `SubmitInfo` structs point inside of `pending`, which must not be modified anymore.
+        let after_stage = vk::PIPELINE_STAGE_TOP_OF_PIPE_BIT;     // TODO:
+        let mut infos = SmallVec::<[_; 3]>::new();
+
+        for p in pending.iter() {
+            if !p.before_command_buffers.is_empty() {
                 infos.push(vk::SubmitInfo {
                     sType: vk::STRUCTURE_TYPE_SUBMIT_INFO,
                     pNext: ptr::null(),
                     waitSemaphoreCount: 0,
                     pWaitSemaphores: ptr::null(),
                     pWaitDstStageMask: ptr::null(),
-                    commandBufferCount: before_command_buffers.len() as u32,
-                    pCommandBuffers: before_command_buffers.as_ptr(),
+                    commandBufferCount: p.before_command_buffers.len() as u32,
+                    pCommandBuffers: p.before_command_buffers.as_ptr(),
                     signalSemaphoreCount: 1,
-                    pSignalSemaphores: &semaphore_id,
+                    pSignalSemaphores: &p.before_semaphore,
                 });
             }
 
-            let after_semaphore = if !after_command_buffers.is_empty() {
-                // TODO: Use try!()? - Mixthos
-                let semaphore = Semaphore::new(queue.device());
-                let semaphore_id = semaphore.internal_object();
-                post_semaphores_ids.push(semaphore.internal_object());
-                keep_alive_semaphores.push(semaphore);
-                semaphore_id
-            } else {
-                0
-            };
-
-            debug_assert_eq!(pre_semaphores_ids.len(), pre_semaphores_stages.len());
             infos.push(vk::SubmitInfo {
                 sType: vk::STRUCTURE_TYPE_SUBMIT_INFO,
                 pNext: ptr::null(),
-                waitSemaphoreCount: pre_semaphores_ids.len() as u32,
-                pWaitSemaphores: pre_semaphores_ids.as_ptr(),
-                pWaitDstStageMask: pre_semaphores_stages.as_ptr(),
+                waitSemaphoreCount: p.pre_semaphores_ids.len() as u32,
+                pWaitSemaphores: p.pre_semaphores_ids.as_ptr(),
+                pWaitDstStageMask: p.pre_semaphores_stages.as_ptr(),
                 commandBufferCount: 1,
-                pCommandBuffers: &me.cmd,
-                signalSemaphoreCount: if after_command_buffers.is_empty() { post_semaphores_ids.len() as u32 } else { 1 },
-                pSignalSemaphores: if after_command_buffers.is_empty() { post_semaphores_ids.as_ptr() } else { &after_semaphore },
+                pCommandBuffers: &p.cmd.cmd,
+                signalSemaphoreCount: if p.after_command_buffers.is_empty() { p.post_semaphores_ids.len() as u32 } else { 1 },
+                pSignalSemaphores: if p.after_command_buffers.is_empty() { p.post_semaphores_ids.as_ptr() } else { &p.after_semaphore },
             });
 
-            if !after_command_buffers.is_empty() {
-                let stage = vk::PIPELINE_STAGE_TOP_OF_PIPE_BIT;     // TODO:
+            if !p.after_command_buffers.is_empty() {
                 infos.push(vk::SubmitInfo {
                     sType: vk::STRUCTURE_TYPE_SUBMIT_INFO,
                     pNext: ptr::null(),
                     waitSemaphoreCount: 1,
-                    pWaitSemaphores: &after_semaphore,
-                    pWaitDstStageMask: &stage,
-                    commandBufferCount: after_command_buffers.len() as u32,
-                    pCommandBuffers: after_command_buffers.as_ptr(),
-                    signalSemaphoreCount: post_semaphores_ids.len() as u32,
-                    pSignalSemaphores: post_semaphores_ids.as_ptr(),
+                    pWaitSemaphores: &p.after_semaphore,
+                    pWaitDstStageMask: &after_stage,
+                    commandBufferCount: p.after_command_buffers.len() as u32,
+                    pCommandBuffers: p.after_command_buffers.as_ptr(),
+                    signalSemaphoreCount: p.post_semaphores_ids.len() as u32,
+                    pSignalSemaphores: p.post_semaphores_ids.as_ptr(),
                 });
             }
+        }
 
+        unsafe {
             let fence = fence.internal_object();
             try!(check_errors(vk.QueueSubmit(*queue.internal_object_guard(), infos.len() as u32,
                                              infos.as_ptr(), fence)));
         }
+    }
 
-        // Don't forget to add all the semaphores in the list of semaphores that must be kept alive.
-        {
-            let mut ka_sem = submission.keep_alive_semaphores.lock().unwrap();
-            *ka_sem = keep_alive_semaphores;
-        }
-
+    // Don't forget to add all the semaphores in the list of semaphores that must be kept alive.
+    for (p, submission) in pending.into_iter().zip(submissions.iter()) {
+        let mut ka_sem = submission.keep_alive_semaphores.lock().unwrap();
+        *ka_sem = p.keep_alive_semaphores;
     }
 
-    Ok(submission)
+    Ok(submissions)
+}
+
+// State of a command buffer that is being submitted by `submit_multiple`.
+struct PendingSubmit<'a> {
+    cmd: &'a InnerCommandBuffer,
+    keep_alive_semaphores: SmallVec<[Arc<Semaphore>; 8]>,
+    post_semaphores_ids: SmallVec<[vk::Semaphore; 8]>,
+    pre_semaphores_ids: SmallVec<[vk::Semaphore; 8]>,
+    pre_semaphores_stages: SmallVec<[vk::PipelineStageFlags; 8]>,
+    before_command_buffers: SmallVec<[vk::CommandBuffer; 4]>,
+    after_command_buffers: SmallVec<[vk::CommandBuffer; 4]>,
+    // Semaphore signalled by `before_command_buffers`, or 0 if there is none.
+    before_semaphore: vk::Semaphore,
+    // Semaphore signalled by the command buffer if there are `after_command_buffers`, or 0.
+    after_semaphore: vk::Semaphore,
 }
 
 impl Drop for InnerCommandBuffer {
@@ -43,6 +43,7 @@
 
 pub use self::inner::Submission;
 pub use self::outer::submit;
+pub use self::outer::submit_multiple;
 pub use self::outer::DynamicState;
 pub use self::outer::PrimaryCommandBufferBuilder;
 pub use self::outer::PrimaryCommandBufferBuilderInlineDraw;
@@ -20,6 +20,7 @@ use command_buffer::inner::InnerCommandBufferBuilder;
 use command_buffer::inner::InnerCommandBuffer;
 use command_buffer::inner::Submission;
 use command_buffer::inner::submit as inner_submit;
+use command_buffer::inner::submit_multiple as inner_submit_multiple;
 use descriptor::descriptor_set::DescriptorSetsCollection;
 use descriptor::PipelineLayout;
 use device::Queue;
@@ -30,6 +31,7 @@ use framebuffer::RenderPass;
 use framebuffer::RenderPassDesc;
 use framebuffer::RenderPassClearValues;
 use framebuffer::Subpass;
+use image::sys::Layout;
 use image::traits::Image;
 use image::traits::ImageClearValue;
 use image::traits::ImageContent;
@@ -200,6 +202,22 @@ impl PrimaryCommandBufferBuilder {
         }
     }
 
+    /// Records a barrier that transfers the ownership of an image between queue families.
+    ///
+    /// See `SwapchainImage::queue_family_ownership_transfer`.
+    #[doc(hidden)]
+    #[inline]
+    pub unsafe fn queue_family_ownership_transfer<I>(self, image: &Arc<I>, layout: Layout,
+                                                     src_family: u32, dst_family: u32)
+                                                     -> PrimaryCommandBufferBuilder
+        where I: Image + 'static
+    {
+        PrimaryCommandBufferBuilder {
+            inner: self.inner.queue_family_ownership_transfer(image, layout, src_family,
+                                                              dst_family),
+        }
+    }
+
     /// Executes secondary compute command buffers within this primary command buffer.
     #[inline]
     pub fn execute_commands(self, cb: &Arc<SecondaryComputeCommandBuffer>)
@@ -539,6 +557,23 @@ pub fn submit(cmd: &Arc<PrimaryCommandBuffer>, queue: &Arc<Queue>)
     inner_submit(&cmd.inner, cmd.clone() as Arc<_>, queue)
 }
 
+/// Submits multiple command buffers to a queue with a single call to `vkQueueSubmit`.
+///
+/// The command buffers are executed in order. Returns one `Submission` per command buffer, in
+/// the same order. Fences and semaphores are automatically handled.
+///
+/// # Panic
+///
+/// - Panicks if the queue doesn't belong to the device the command buffers were created with.
+/// - Panicks if the queue doesn't belong to the family the pools were created with.
+///
+pub fn submit_multiple(cmds: &[Arc<PrimaryCommandBuffer>], queue: &Arc<Queue>)
+                       -> Result<Vec<Arc<Submission>>, OomError>
+{       // TODO: wrong error type
+    let cmds = cmds.iter().map(|cmd| (&cmd.inner, cmd.clone() as Arc<_>)).collect::<Vec<_>>();
+    inner_submit_multiple(&cmds, queue)
+}
+
 /// A prototype of a secondary compute command buffer.
 pub struct SecondaryGraphicsCommandBufferBuilder<R> {
     inner: InnerCommandBufferBuilder,
@@ -0,0 +1,353 @@
+// Copyright (c) 2016 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+//! Attaching debug names to Vulkan objects and inserting markers in queues.
+//!
+//! Debugging tools such as graphics debuggers and validation layers can display human-readable
+//! names for the objects they report about. Naming objects requires the `VK_EXT_debug_marker`
+//! device extension to be enabled.
+//!
+//! Labels can also be inserted in a queue with `insert_label`, in order to correlate events that
+//! happen on the CPU with the GPU timeline in a capture. This requires the `VK_EXT_debug_utils`
+//! instance extension to be enabled. Colors for the labels can be picked with `DebugColor`.
+
+use std::error;
+use std::ffi::CString;
+use std::fmt;
+use std::hash::Hasher;
+use std::ptr;
+use std::sync::Arc;
+use fnv::FnvHasher;
+
+use device::Device;
+use device::Queue;
+
+use check_errors;
+use Error;
+use OomError;
+use SynchronizedVulkanObject;
+use VulkanObject;
+use VulkanPointers;
+use vk;
+
+/// Gives a debug name to an object.
+///
+/// The `VK_EXT_debug_marker` extension must be enabled on the device that owns the object.
+///
+/// # Panic
+///
+/// - Panicks if `name` contains a nul character.
+///
+pub fn set_name<T: ?Sized>(object: &T, name: &str) -> Result<(), DebugMarkerError>
+    where T: DebugMarkerObject
+{
+    if !object.device().loaded_extensions().ext_debug_marker {
+        return Err(DebugMarkerError::MissingExtension);
+    }
+
+    unsafe { set_name_unchecked(object, name) }
+}
+
+/// Gives a debug name to each object of a list.
+///
+/// The presence of the `VK_EXT_debug_marker` extension is checked for all the objects before
+/// any of them is named. If the extension is missing on one of the devices, no object is named.
+///
+/// # Panic
+///
+/// - Panicks if one of the names contains a nul character.
+///
+pub fn set_names(objects: &mut [(&mut DebugMarkerObject, &str)]) -> Result<(), DebugMarkerError> {
+    if objects.iter().any(|&(ref obj, _)| !obj.device().loaded_extensions().ext_debug_marker) {
+        return Err(DebugMarkerError::MissingExtension);
+    }
+
+    for &mut (ref obj, name) in objects.iter_mut() {
+        unsafe { try!(set_name_unchecked(&**obj, name)); }
+    }
+
+    Ok(())
+}
+
+// Same as `set_name`, but doesn't check whether the extension is enabled.
+unsafe fn set_name_unchecked<T: ?Sized>(object: &T, name: &str) -> Result<(), DebugMarkerError>
+    where T: DebugMarkerObject
+{
+    let device = object.device();
+    let name = CString::new(name).unwrap();
+    let vk = device.pointers();
+
+    let infos = vk::DebugMarkerObjectNameInfoEXT {
+        sType: vk::STRUCTURE_TYPE_DEBUG_MARKER_OBJECT_NAME_INFO_EXT,
+        pNext: ptr::null(),
+        objectType: object.debug_report_object_type(),
+        object: object.debug_marker_handle(),
+        pObjectName: name.as_ptr(),
+    };
+
+    try!(check_errors(vk.DebugMarkerSetObjectNameEXT(device.internal_object(), &infos)));
+    Ok(())
+}
+
+/// Inserts a single label in a queue.
+///
+/// Contrary to markers recorded in a command buffer, the label is inserted directly in the queue
+/// timeline between the submissions. `color` is an optional RGBA color for the label, which is
+/// ignored by tools if all its components are 0.0. It can be a `[f32; 4]` or a `DebugColor`.
+///
+/// The `VK_EXT_debug_utils` extension must be enabled on the instance.
+///
+/// # Panic
+///
+/// - Panicks if `name` contains a nul character.
+///
+pub fn insert_label<C>(queue: &Queue, name: &str, color: C) -> Result<(), DebugMarkerError>
+    where C: Into<[f32; 4]>
+{
+    let device = queue.device();
+
+    if !device.instance().loaded_extensions().ext_debug_utils {
+        return Err(DebugMarkerError::MissingExtension);
+    }
+
+    let name = CString::new(name).unwrap();
+    let vk = device.pointers();
+
+    let infos = vk::DebugUtilsLabelEXT {
+        sType: vk::STRUCTURE_TYPE_DEBUG_UTILS_LABEL_EXT,
+        pNext: ptr::null(),
+        pLabelName: name.as_ptr(),
+        color: color.into(),
+    };
+
+    unsafe {
+        vk.QueueInsertDebugUtilsLabelEXT(*queue.internal_object_guard(), &infos);
+    }
+
+    Ok(())
+}
+
+/// Color of a debug label.
+///
+/// Converts to the RGBA `[f32; 4]` expected by the functions of this module.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DebugColor {
+    Red,
+    Green,
+    Blue,
+    Yellow,
+    Cyan,
+    Magenta,
+    Orange,
+    Purple,
+    White,
+    Gray,
+    /// Custom RGBA color.
+    Rgba([f32; 4]),
+}
+
+impl DebugColor {
+    /// Derives a color from a label.
+    ///
+    /// The same label always produces the same color, including between runs of the program,
+    /// so that regions that are inserted repeatedly get the same color in captures. The color
+    /// is never too dark to be read.
+    pub fn from_hash(name: &str) -> DebugColor {
+        let mut hasher = FnvHasher::default();
+        hasher.write(name.as_bytes());
+        let hash = hasher.finish();
+
+        // Each component is between 0.25 and 1.0.
+        let component = |shift: u64| 0.25 + ((hash >> shift) & 0xff) as f32 / 255.0 * 0.75;
+        DebugColor::Rgba([component(0), component(8), component(16), 1.0])
+    }
+
+    /// Returns the RGBA components of the color.
+    #[inline]
+    pub fn to_rgba(&self) -> [f32; 4] {
+        match *self {
+            DebugColor::Red => [1.0, 0.0, 0.0, 1.0],
+            DebugColor::Green => [0.0, 1.0, 0.0, 1.0],
+            DebugColor::Blue => [0.0, 0.0, 1.0, 1.0],
+            DebugColor::Yellow => [1.0, 1.0, 0.0, 1.0],
+            DebugColor::Cyan => [0.0, 1.0, 1.0, 1.0],
+            DebugColor::Magenta => [1.0, 0.0, 1.0, 1.0],
+            DebugColor::Orange => [1.0, 0.5, 0.0, 1.0],
+            DebugColor::Purple => [0.5, 0.0, 1.0, 1.0],
+            DebugColor::White => [1.0, 1.0, 1.0, 1.0],
+            DebugColor::Gray => [0.5, 0.5, 0.5, 1.0],
+            DebugColor::Rgba(rgba) => rgba,
+        }
+    }
+}
+
+impl From<DebugColor> for [f32; 4] {
+    #[inline]
+    fn from(color: DebugColor) -> [f32; 4] {
+        color.to_rgba()
+    }
+}
+
+/// Objects that can be given a debug name.
+///
+/// This trait is implemented on the vulkano objects that wrap a Vulkan handle, but can also be
+/// implemented on your own types that wrap such an object, for example:
+///
+/// ```
+/// use std::sync::Arc;
+/// use vulkano::debug_marker::DebugMarkerObject;
+/// use vulkano::device::Device;
+/// use vulkano::sync::Semaphore;
+///
+/// struct FrameSemaphore(Semaphore);
+///
+/// unsafe impl DebugMarkerObject for FrameSemaphore {
+///     fn debug_report_object_type(&self) -> u32 {
+///         self.0.debug_report_object_type()
+///     }
+///
+///     fn debug_marker_handle(&self) -> u64 {
+///         self.0.debug_marker_handle()
+///     }
+///
+///     fn device(&self) -> &Arc<Device> {
+///         DebugMarkerObject::device(&self.0)
+///     }
+/// }
+/// ```
+///
+/// # Safety
+///
+/// - `debug_marker_handle` must return a handle that belongs to the device returned by `device`.
+/// - `debug_report_object_type` must return the type that corresponds to this handle.
+///
+pub unsafe trait DebugMarkerObject {
+    /// Returns the type of the object, as passed to `vkDebugMarkerSetObjectNameEXT`.
+    ///
+    /// The value is one of the `VkDebugReportObjectTypeEXT` constants, whose values are fixed by
+    /// the Vulkan specification and will not change. For example `5` is a semaphore, `7` is a
+    /// fence, `9` is a buffer, `10` is an image and `27` is a swapchain. The constants are
+    /// available in the `vk-sys` crate as `DEBUG_REPORT_OBJECT_TYPE_*_EXT`.
+    fn debug_report_object_type(&self) -> vk::DebugReportObjectTypeEXT;
+
+    /// Returns the raw handle of the object, cast to a `u64`.
+    ///
+    /// Dispatchable handles (pointers) must be converted to a `u64` with an `as` cast.
+    fn debug_marker_handle(&self) -> u64;
+
+    /// Returns the device that owns the object.
+    fn device(&self) -> &Arc<Device>;
+}
+
+/// Error that can happen when giving a debug name to an object.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DebugMarkerError {
+    /// Not enough memory.
+    OomError(OomError),
+
+    /// The extension required for this function was not enabled.
+    MissingExtension,
+}
+
+impl error::Error for DebugMarkerError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            DebugMarkerError::OomError(_) => "not enough memory available",
+            DebugMarkerError::MissingExtension => "the extension required for this function was \
+                                                   not enabled",
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            DebugMarkerError::OomError(ref err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for DebugMarkerError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+impl From<OomError> for DebugMarkerError {
+    #[inline]
+    fn from(err: OomError) -> DebugMarkerError {
+        DebugMarkerError::OomError(err)
+    }
+}
+
+impl From<Error> for DebugMarkerError {
+    #[inline]
+    fn from(err: Error) -> DebugMarkerError {
+        match err {
+            err @ Error::OutOfHostMemory => DebugMarkerError::OomError(OomError::from(err)),
+            err @ Error::OutOfDeviceMemory => DebugMarkerError::OomError(OomError::from(err)),
+            _ => panic!("unexpected error: {:?}", err)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use debug_marker;
+    use debug_marker::DebugColor;
+    use debug_marker::DebugMarkerError;
+    use sync::Semaphore;
+
+    #[test]
+    fn debug_color_from_hash() {
+        let a = DebugColor::from_hash("shadow pass");
+        assert_eq!(a, DebugColor::from_hash("shadow pass"));
+        assert!(a != DebugColor::from_hash("main pass"));
+
+        let rgba = a.to_rgba();
+        assert!(rgba[.. 3].iter().all(|&c| c >= 0.25 && c <= 1.0));
+        assert_eq!(rgba[3], 1.0);
+    }
+
+    #[test]
+    fn debug_color_into_array() {
+        let rgba: [f32; 4] = DebugColor::Red.into();
+        assert_eq!(rgba, [1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(DebugColor::Rgba([0.1, 0.2, 0.3, 0.4]).to_rgba(), [0.1, 0.2, 0.3, 0.4]);
+    }
+
+    #[test]
+    fn set_names_empty() {
+        assert_eq!(debug_marker::set_names(&mut []), Ok(()));
+    }
+
+    #[test]
+    fn set_names_ext_missing() {
+        let (device, _) = gfx_dev_and_queue!();
+        let mut a = Semaphore::raw(&device).unwrap();
+        let mut b = Semaphore::raw(&device).unwrap();
+
+        match debug_marker::set_names(&mut [(&mut a, "a"), (&mut b, "b")]) {
+            Err(DebugMarkerError::MissingExtension) => (),
+            _ => panic!()
+        }
+    }
+
+    #[test]
+    fn insert_label_ext_missing() {
+        let (_, queue) = gfx_dev_and_queue!();
+
+        match debug_marker::insert_label(&queue, "label", [0.0; 4]) {
+            Err(DebugMarkerError::MissingExtension) => (),
+            _ => panic!()
+        }
+    }
+}
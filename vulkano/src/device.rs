@@ -16,10 +16,12 @@ use std::ffi::CString;
 use std::fmt;
 use std::error;
 use std::mem;
+use std::os::raw::c_void;
 use std::ptr;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::sync::MutexGuard;
+use std::time::Duration;
 use smallvec::SmallVec;
 
 use instance::Features;
@@ -27,10 +29,17 @@ use instance::Instance;
 use instance::PhysicalDevice;
 use instance::QueueFamily;
 use memory::pool::StdMemoryPool;
+use sync::Fence;
 use sync::Semaphore;
+use sync::timeline_feature_required;
+use instance::Version;
+use swapchain::display::Display;
+use swapchain::display::DisplayEventType;
 
 use Error;
 use OomError;
+use SafeDeref;
+use Success;
 use SynchronizedVulkanObject;
 use VulkanObject;
 use VulkanPointers;
@@ -46,8 +55,11 @@ pub struct Device {
     device: vk::Device,
     vk: vk::DevicePointers,
     standard_pool: Mutex<Option<Arc<StdMemoryPool>>>,
+    fence_pool: Mutex<Vec<vk::Fence>>,
     features: Features,
     extensions: DeviceExtensions,
+    protected_memory: bool,
+    allocation_callbacks: Option<AllocationCallbacks>,
 }
 
 impl Device {
@@ -73,15 +85,81 @@ impl Device {
     /// - Panicks if one of the priorities is outside of the `[0.0 ; 1.0]` range.
     ///
     // TODO: return Arc<Queue> and handle synchronization in the Queue
+    #[inline]
     pub fn new<'a, I, L>(phys: &'a PhysicalDevice, requested_features: &Features,
                          extensions: &DeviceExtensions, layers: L, queue_families: I)
                          -> Result<(Arc<Device>, QueuesIter), DeviceCreationError>
         where I: IntoIterator<Item = (QueueFamily<'a>, f32)>,
               L: IntoIterator<Item = &'a &'a str>
+    {
+        Device::new_inner(phys, requested_features, extensions, layers, queue_families, false,
+                          None)
+    }
+
+    /// Same as `new`, but also enables the `protectedMemory` feature, which is required to
+    /// create protected swapchains.
+    ///
+    /// Use `supports_protected_memory` to check whether the physical device supports the
+    /// feature. Supporting it requires Vulkan 1.1.
+    ///
+    /// # Panic
+    ///
+    /// - Panicks if the physical device doesn't support the `protectedMemory` feature.
+    /// - Panicks for the same reasons as `new`.
+    ///
+    #[inline]
+    pub fn with_protected_memory<'a, I, L>(phys: &'a PhysicalDevice,
+                                           requested_features: &Features,
+                                           extensions: &DeviceExtensions, layers: L,
+                                           queue_families: I)
+                                           -> Result<(Arc<Device>, QueuesIter),
+                                                     DeviceCreationError>
+        where I: IntoIterator<Item = (QueueFamily<'a>, f32)>,
+              L: IntoIterator<Item = &'a &'a str>
+    {
+        Device::new_inner(phys, requested_features, extensions, layers, queue_families, true,
+                          None)
+    }
+
+    /// Same as `new`, but the Vulkan implementation will use the given callbacks to allocate
+    /// host memory for the device.
+    ///
+    /// The callbacks are also used when creating and destroying the semaphores, fences, events
+    /// and swapchains of this device. Other objects use the implementation's allocator.
+    ///
+    /// # Safety
+    ///
+    /// - The callbacks must follow the rules of the Vulkan specification. In particular they can
+    ///   be called from multiple threads at once.
+    /// - `user_data` must stay valid for as long as the device or one of its objects is alive.
+    ///
+    #[inline]
+    pub unsafe fn with_allocation_callbacks<'a, I, L>(phys: &'a PhysicalDevice,
+                                                      requested_features: &Features,
+                                                      extensions: &DeviceExtensions, layers: L,
+                                                      queue_families: I,
+                                                      callbacks: AllocationCallbacks)
+                                                      -> Result<(Arc<Device>, QueuesIter),
+                                                                DeviceCreationError>
+        where I: IntoIterator<Item = (QueueFamily<'a>, f32)>,
+              L: IntoIterator<Item = &'a &'a str>
+    {
+        Device::new_inner(phys, requested_features, extensions, layers, queue_families, false,
+                          Some(callbacks))
+    }
+
+    fn new_inner<'a, I, L>(phys: &'a PhysicalDevice, requested_features: &Features,
+                           extensions: &DeviceExtensions, layers: L, queue_families: I,
+                           protected_memory: bool,
+                           allocation_callbacks: Option<AllocationCallbacks>)
+                           -> Result<(Arc<Device>, QueuesIter), DeviceCreationError>
+        where I: IntoIterator<Item = (QueueFamily<'a>, f32)>,
+              L: IntoIterator<Item = &'a &'a str>
     {
         let queue_families = queue_families.into_iter();
 
         assert!(phys.supported_features().superset_of(&requested_features));
+        assert!(!protected_memory || Device::supports_protected_memory(phys));
 
         let vk_i = phys.instance().pointers();
 
@@ -155,9 +233,34 @@ impl Device {
                 features
             };
 
+            // Timeline semaphores are only available if the corresponding feature is enabled.
+            // The feature is always supported when the functionality is available.
+            // Protected memory is only enabled if it was requested with `with_protected_memory`.
+            let mut protected_features = vk::PhysicalDeviceProtectedMemoryFeatures {
+                sType: vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_PROTECTED_MEMORY_FEATURES,
+                pNext: ptr::null_mut(),
+                protectedMemory: vk::TRUE,
+            };
+
+            let timeline_features = vk::PhysicalDeviceTimelineSemaphoreFeaturesKHR {
+                sType: vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_TIMELINE_SEMAPHORE_FEATURES_KHR,
+                pNext: if protected_memory {
+                    &mut protected_features as *mut _ as *mut _
+                } else {
+                    ptr::null_mut()
+                },
+                timelineSemaphore: vk::TRUE,
+            };
+
             let infos = vk::DeviceCreateInfo {
                 sType: vk::STRUCTURE_TYPE_DEVICE_CREATE_INFO,
-                pNext: ptr::null(),
+                pNext: if timeline_feature_required(phys, extensions) {
+                    &timeline_features as *const _ as *const _
+                } else if protected_memory {
+                    &protected_features as *const _ as *const _
+                } else {
+                    ptr::null()
+                },
                 flags: 0,   // reserved
                 queueCreateInfoCount: queues.len() as u32,
                 pQueueCreateInfos: queues.as_ptr(),
@@ -168,9 +271,14 @@ impl Device {
                 pEnabledFeatures: &features,
             };
 
+            let allocator = match allocation_callbacks {
+                Some(ref callbacks) => callbacks as *const AllocationCallbacks as *const _,
+                None => ptr::null(),
+            };
+
             let mut output = mem::uninitialized();
             try!(check_errors(vk_i.CreateDevice(phys.internal_object(), &infos,
-                                                ptr::null(), &mut output)));
+                                                allocator, &mut output)));
             output
         };
 
@@ -185,8 +293,11 @@ impl Device {
             device: device,
             vk: vk,
             standard_pool: Mutex::new(None),
+            fence_pool: Mutex::new(Vec::new()),
             features: requested_features.clone(),
             extensions: extensions.clone(),
+            protected_memory: protected_memory,
+            allocation_callbacks: allocation_callbacks,
         });
 
         // Creating the memory pool.
@@ -230,6 +341,115 @@ impl Device {
         self.wait_raw().unwrap();
     }
 
+    /// Waits until all the fences, or at least one of them if `wait_all` is false, are signaled.
+    ///
+    /// Returns `Ok(true)` if the condition is fulfilled, or `Ok(false)` if the timeout has been
+    /// reached first. A timeout of `None` means that the function waits forever. Passing an empty
+    /// list of fences returns `Ok(true)` immediately.
+    ///
+    /// Waiting for any fence is useful to manage a pool of frames in flight, as it returns as
+    /// soon as one of the slots is free.
+    ///
+    /// # Panic
+    ///
+    /// - Panicks if one of the fences doesn't belong to this device.
+    ///
+    pub fn wait_for_fences<D>(&self, fences: &[&Fence<D>], wait_all: bool,
+                              timeout: Option<Duration>) -> Result<bool, OomError>
+        where D: SafeDeref<Target = Device>
+    {
+        for fence in fences {
+            assert_eq!(fence.device().internal_object(), self.device);
+        }
+
+        if fences.is_empty() {
+            return Ok(true);
+        }
+
+        let raw_fences: SmallVec<[vk::Fence; 8]> = fences.iter()
+                                                         .map(|f| f.internal_object())
+                                                         .collect();
+
+        let timeout_ns = match timeout {
+            Some(timeout) => timeout.as_secs().saturating_mul(1_000_000_000)
+                                              .saturating_add(timeout.subsec_nanos() as u64),
+            None => u64::max_value(),
+        };
+
+        let r = unsafe {
+            try!(check_errors(self.vk.WaitForFences(self.device, raw_fences.len() as u32,
+                                                    raw_fences.as_ptr(),
+                                                    if wait_all { vk::TRUE } else { vk::FALSE },
+                                                    timeout_ns)))
+        };
+
+        match r {
+            Success::Success => Ok(true),
+            Success::Timeout => Ok(false),
+            s => panic!("unexpected success value: {:?}", s)
+        }
+    }
+
+    /// Registers a fence that is signaled the next time `event` happens on the device.
+    ///
+    /// The returned fence is signaled only once. To keep being notified, register a new fence
+    /// after the previous one has been signaled.
+    ///
+    /// The `VK_EXT_display_control` extension must be enabled on the device.
+    pub fn register_device_event(me: &Arc<Device>, event: DeviceEventType)
+                                 -> Result<Fence, DisplayControlError>
+    {
+        if !me.extensions.ext_display_control {
+            return Err(DisplayControlError::MissingExtension { name: "VK_EXT_display_control" });
+        }
+
+        let vk = me.pointers();
+
+        let infos = vk::DeviceEventInfoEXT {
+            sType: vk::STRUCTURE_TYPE_DEVICE_EVENT_INFO_EXT,
+            pNext: ptr::null(),
+            deviceEvent: event.to_vk(),
+        };
+
+        unsafe {
+            let mut output = mem::uninitialized();
+            try!(check_errors(vk.RegisterDeviceEventEXT(me.device, &infos,
+                                                        me.allocation_callbacks(),
+                                                        &mut output)));
+            Ok(Fence::from_raw(me, output))
+        }
+    }
+
+    /// Registers a fence that is signaled the next time `event` happens on `display`.
+    ///
+    /// The returned fence is signaled only once. To be notified at each vertical blanking
+    /// period, register a new fence after the previous one has been signaled.
+    ///
+    /// The `VK_EXT_display_control` extension must be enabled on the device.
+    pub fn register_display_event(me: &Arc<Device>, display: &Display, event: DisplayEventType)
+                                  -> Result<Fence, DisplayControlError>
+    {
+        if !me.extensions.ext_display_control {
+            return Err(DisplayControlError::MissingExtension { name: "VK_EXT_display_control" });
+        }
+
+        let vk = me.pointers();
+
+        let infos = vk::DisplayEventInfoEXT {
+            sType: vk::STRUCTURE_TYPE_DISPLAY_EVENT_INFO_EXT,
+            pNext: ptr::null(),
+            displayEvent: event.to_vk(),
+        };
+
+        unsafe {
+            let mut output = mem::uninitialized();
+            try!(check_errors(vk.RegisterDisplayEventEXT(me.device, display.internal_object(),
+                                                         &infos, me.allocation_callbacks(),
+                                                         &mut output)));
+            Ok(Fence::from_raw(me, output))
+        }
+    }
+
     /// Returns the instance used to create this device.
     #[inline]
     pub fn instance(&self) -> &Arc<Instance> {
@@ -254,11 +474,85 @@ impl Device {
         &self.extensions
     }
 
+    /// Returns true if the `protectedMemory` feature is enabled on the device, in other words if
+    /// the device was created with `with_protected_memory`.
+    #[inline]
+    pub fn protected_memory_enabled(&self) -> bool {
+        self.protected_memory
+    }
+
+    /// Returns true if the physical device supports the `protectedMemory` feature, which can
+    /// then be enabled with `with_protected_memory`.
+    ///
+    /// Querying the feature requires Vulkan 1.1 for both the instance and the physical device.
+    /// Returns false otherwise.
+    pub fn supports_protected_memory(physical_device: &PhysicalDevice) -> bool {
+        let v1_1 = Version { major: 1, minor: 1, patch: 0 };
+        if physical_device.instance().api_version() < v1_1 ||
+           physical_device.api_version() < v1_1
+        {
+            return false;
+        }
+
+        unsafe {
+            let vk_i = physical_device.instance().pointers();
+
+            let mut protected = vk::PhysicalDeviceProtectedMemoryFeatures {
+                sType: vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_PROTECTED_MEMORY_FEATURES,
+                pNext: ptr::null_mut(),
+                protectedMemory: vk::FALSE,
+            };
+
+            let mut features = vk::PhysicalDeviceFeatures2 {
+                sType: vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_FEATURES_2,
+                pNext: &mut protected as *mut _ as *mut _,
+                features: mem::uninitialized(),
+            };
+
+            vk_i.GetPhysicalDeviceFeatures2(physical_device.internal_object(), &mut features);
+            protected.protectedMemory != vk::FALSE
+        }
+    }
+
+    /// Returns true if debug names and labels can be used with this device, in other words if
+    /// the `VK_EXT_debug_marker` device extension or the `VK_EXT_debug_utils` instance extension
+    /// is enabled.
+    ///
+    /// These extensions are usually only enabled when a debugging tool or a validation layer
+    /// is present. Checking this once lets you skip building the names and labels entirely
+    /// when they would be ignored. Note that each function of the `debug_marker` module still
+    /// requires its specific extension.
+    #[inline]
+    pub fn debug_markers_available(&self) -> bool {
+        self.extensions.ext_debug_marker || self.instance.loaded_extensions().ext_debug_utils
+    }
+
+    /// Returns the `pAllocator` parameter to pass when creating or destroying an object of this
+    /// device, which is null if the device doesn't have allocation callbacks.
+    #[doc(hidden)]
+    #[inline]
+    pub fn allocation_callbacks(&self) -> *const vk::AllocationCallbacks {
+        match self.allocation_callbacks {
+            Some(ref callbacks) => callbacks as *const AllocationCallbacks as *const _,
+            None => ptr::null(),
+        }
+    }
+
     /// Returns the standard memory pool used by default if you don't provide any other pool.
     #[inline]
     pub fn standard_pool(&self) -> Arc<StdMemoryPool> {
         self.standard_pool.lock().unwrap().clone().unwrap()
     }
+
+    /// Returns the pool of fences that are ready to be reused.
+    ///
+    /// Fences created with `Fence::from_pool` are put back in this pool when they are destroyed
+    /// instead of being destroyed.
+    #[doc(hidden)]
+    #[inline]
+    pub fn fence_pool(&self) -> &Mutex<Vec<vk::Fence>> {
+        &self.fence_pool
+    }
 }
 
 impl fmt::Debug for Device {
@@ -268,6 +562,42 @@ impl fmt::Debug for Device {
     }
 }
 
+/// Callbacks that the Vulkan implementation calls to allocate host memory, instead of using its
+/// own allocator.
+///
+/// Each callback receives `user_data` as its first parameter. See the documentation of
+/// `VkAllocationCallbacks` for the meaning of the other parameters.
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct AllocationCallbacks {
+    /// Pointer passed to all the callbacks.
+    pub user_data: *mut c_void,
+    /// Allocates memory. Parameters are the size, the alignment and the allocation scope.
+    pub allocation: extern "system" fn(*mut c_void, usize, usize, u32) -> *mut c_void,
+    /// Reallocates memory. Parameters are the original allocation, the new size, the alignment
+    /// and the allocation scope.
+    pub reallocation: extern "system" fn(*mut c_void, *mut c_void, usize, usize, u32)
+                                         -> *mut c_void,
+    /// Frees memory that was returned by `allocation` or `reallocation`.
+    pub free: extern "system" fn(*mut c_void, *mut c_void),
+    /// Optional notification that the implementation allocated memory by itself. Parameters are
+    /// the size, the allocation type and the allocation scope.
+    pub internal_allocation: Option<extern "system" fn(*mut c_void, usize, u32, u32)>,
+    /// Optional notification that the implementation freed memory by itself.
+    pub internal_free: Option<extern "system" fn(*mut c_void, usize, u32, u32)>,
+}
+
+// The Vulkan specs require the callbacks to be thread-safe.
+unsafe impl Send for AllocationCallbacks {}
+unsafe impl Sync for AllocationCallbacks {}
+
+impl fmt::Debug for AllocationCallbacks {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "<Vulkan allocation callbacks>")
+    }
+}
+
 unsafe impl VulkanObject for Device {
     type Object = vk::Device;
 
@@ -293,7 +623,12 @@ impl Drop for Device {
 
         unsafe {
             self.vk.DeviceWaitIdle(self.device);
-            self.vk.DestroyDevice(self.device, ptr::null());
+
+            for &raw_fence in self.fence_pool.lock().unwrap().iter() {
+                self.vk.DestroyFence(self.device, raw_fence, self.allocation_callbacks());
+            }
+
+            self.vk.DestroyDevice(self.device, self.allocation_callbacks());
         }
     }
 }
@@ -377,6 +712,89 @@ impl From<Error> for DeviceCreationError {
     }
 }
 
+/// Event of a device that a fence can be registered for.
+///
+/// See `Device::register_device_event`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DeviceEventType {
+    /// A display was plugged into or unplugged from the device. Use `Display::enumerate` to
+    /// get the new list of displays.
+    DisplayHotplug,
+}
+
+impl DeviceEventType {
+    #[inline]
+    #[doc(hidden)]
+    pub fn to_vk(&self) -> vk::DeviceEventTypeEXT {
+        match *self {
+            DeviceEventType::DisplayHotplug => vk::DEVICE_EVENT_TYPE_DISPLAY_HOTPLUG_EXT,
+        }
+    }
+}
+
+/// Error that can be returned when registering a display or device event.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DisplayControlError {
+    /// Not enough memory.
+    OomError(OomError),
+
+    /// The device extension required for this function was not enabled. This is checked
+    /// before calling into the Vulkan implementation.
+    MissingExtension {
+        /// Name of the extension.
+        name: &'static str
+    },
+}
+
+impl error::Error for DisplayControlError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            DisplayControlError::OomError(_) => "not enough memory available",
+            DisplayControlError::MissingExtension { .. } => "the extension required for this \
+                                                             function was not enabled",
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            DisplayControlError::OomError(ref err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for DisplayControlError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            DisplayControlError::MissingExtension { name } => {
+                write!(fmt, "the `{}` extension was not enabled", name)
+            },
+            _ => write!(fmt, "{}", error::Error::description(self)),
+        }
+    }
+}
+
+impl From<OomError> for DisplayControlError {
+    #[inline]
+    fn from(err: OomError) -> DisplayControlError {
+        DisplayControlError::OomError(err)
+    }
+}
+
+impl From<Error> for DisplayControlError {
+    #[inline]
+    fn from(err: Error) -> DisplayControlError {
+        match err {
+            err @ Error::OutOfHostMemory => DisplayControlError::OomError(OomError::from(err)),
+            err @ Error::OutOfDeviceMemory => DisplayControlError::OomError(OomError::from(err)),
+            _ => panic!("unexpected error: {:?}", err)
+        }
+    }
+}
+
 /// Represents a queue where commands can be submitted.
 // TODO: should use internal synchronization
 #[derive(Debug)]
@@ -472,7 +890,27 @@ unsafe impl SynchronizedVulkanObject for Queue {
 
 #[cfg(test)]
 mod tests {
+    use std::mem;
     use std::sync::Arc;
+    use std::time::Duration;
+    use device::AllocationCallbacks;
+    use sync::Fence;
+    use vk;
+
+    #[test]
+    fn debug_markers_not_available() {
+        let (device, _) = gfx_dev_and_queue!();
+        assert!(!device.debug_markers_available());
+    }
+
+    #[test]
+    fn allocation_callbacks_layout() {
+        // `AllocationCallbacks` is passed to Vulkan as a `VkAllocationCallbacks`.
+        assert_eq!(mem::size_of::<AllocationCallbacks>(),
+                   mem::size_of::<vk::AllocationCallbacks>());
+        assert_eq!(mem::align_of::<AllocationCallbacks>(),
+                   mem::align_of::<vk::AllocationCallbacks>());
+    }
 
     #[test]
     #[ignore]   // FIXME: failing, fix me
@@ -480,4 +918,23 @@ mod tests {
         let (mut device, _) = gfx_dev_and_queue!();
         assert!(Arc::get_mut(&mut device).is_some());
     }
+
+    #[test]
+    fn wait_for_fences_any() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let signaled = Fence::signaled(&device);
+        let unsignaled = Fence::new(&device);
+        let fences = [&*signaled, &*unsignaled];
+
+        assert_eq!(device.wait_for_fences(&fences, false, Some(Duration::new(0, 0))), Ok(true));
+        assert_eq!(device.wait_for_fences(&fences, true, Some(Duration::new(0, 0))), Ok(false));
+    }
+
+    #[test]
+    fn wait_for_fences_empty() {
+        let (device, _) = gfx_dev_and_queue!();
+        let fences: &[&Fence] = &[];
+        assert_eq!(device.wait_for_fences(fences, true, None), Ok(true));
+    }
 }
@@ -7,40 +7,88 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+use std::cell::UnsafeCell;
+use std::error;
+use std::fmt;
 use std::mem;
 use std::ops::Range;
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Weak;
 
+use buffer::CpuAccessibleBuffer;
+use buffer::BufferUsage;
+use command_buffer::PrimaryCommandBufferBuilder;
 use command_buffer::Submission;
 use format::Format;
+use image::attachment::AttachmentImage;
 use image::traits::AccessRange;
 use image::traits::GpuAccessResult;
 use image::traits::Image;
 use image::traits::ImageContent;
 use image::traits::ImageView;
 use image::traits::Transition;
+use image::sys::ImageCreationError;
 use image::sys::Layout;
 use image::sys::UnsafeImage;
 use image::sys::UnsafeImageView;
+use instance::QueueFamily;
+use swapchain::ColorSpace;
 use swapchain::Swapchain;
 use sync::Semaphore;
 
 use OomError;
-
+use VulkanObject;
+
+/// An image that belongs to a swapchain.
+///
+/// # Layout
+///
+/// The content of a swapchain image is undefined when it is acquired for the first time, and the
+/// image must be in the `PresentSrc` layout when it is presented. Command buffers that use a
+/// swapchain image therefore always expect it to be in the `PresentSrc` layout at their start
+/// and put it back in this layout at their end. The first time the image is used by a
+/// submission, a transition from `Undefined` to `PresentSrc` is automatically added before the
+/// command buffers. You don't need to add any barrier yourself.
+///
+/// # Array layers
+///
+/// If the swapchain was created with more than one layer, for example for a stereo display,
+/// the view of the image covers all the layers. Use `SwapchainImage::layer_view` to draw to a
+/// single layer.
+///
+/// # Deferred memory allocation
+///
+/// If the swapchain was created with the `deferred_memory_allocation` flag, the memory of the
+/// image is only allocated when it is acquired for the first time, and no view of the image can
+/// be created before that. This means that framebuffers that use the image can only be built
+/// once the image has been acquired. See `Swapchain::is_image_allocated`.
+///
+/// The view of the image is then created at its first use. Call `try_inner_view` after
+/// acquiring the image in order to create the view and handle errors, otherwise using the
+/// image panics if creating the view fails.
 // TODO: #[derive(Debug)] (needs https://github.com/aturon/crossbeam/issues/62)
 pub struct SwapchainImage {
     image: UnsafeImage,
-    view: UnsafeImageView,
+    // Written only once, while `view_lock` is locked. Always `Some` once `view_ready` is true.
+    view: UnsafeCell<Option<UnsafeImageView>>,
+    view_ready: AtomicBool,
+    view_lock: Mutex<()>,
     format: Format,
     swapchain: Arc<Swapchain>,
     id: u32,
     guarded: Mutex<Guarded>,
 }
 
+// `view` is only written once while `view_lock` is locked, and only read after `view_ready` has
+// been set.
+unsafe impl Sync for SwapchainImage {}
+
 #[derive(Debug)]
 struct Guarded {
+    // False if the image has never been used by a submission, in which case it is still in the
+    // `Undefined` layout.
     present_layout: bool,
     latest_submission: Option<Weak<Submission>>,    // TODO: can use `Weak::new()` once it's stabilized
 }
@@ -49,11 +97,22 @@ impl SwapchainImage {
     pub unsafe fn from_raw(image: UnsafeImage, format: Format, swapchain: &Arc<Swapchain>, id: u32)
                            -> Result<Arc<SwapchainImage>, OomError>
     {
-        let view = try!(UnsafeImageView::raw(&image, 0 .. 1, 0 .. 1));
+        // With deferred memory allocation, views can only be created once the image has been
+        // acquired.
+        let view = if swapchain.flags().deferred_memory_allocation {
+            None
+        } else {
+            let layers = image.dimensions().array_layers();
+            Some(try!(UnsafeImageView::raw(&image, 0 .. 1, 0 .. layers)))
+        };
+
+        let view_ready = AtomicBool::new(view.is_some());
 
         Ok(Arc::new(SwapchainImage {
             image: image,
-            view: view,
+            view: UnsafeCell::new(view),
+            view_ready: view_ready,
+            view_lock: Mutex::new(()),
             format: format,
             swapchain: swapchain.clone(),
             id: id,
@@ -64,6 +123,35 @@ impl SwapchainImage {
         }))
     }
 
+    /// Returns the view of the image that covers all its layers, and creates it first if
+    /// needed.
+    ///
+    /// The view always exists, except if the swapchain was created with the
+    /// `deferred_memory_allocation` flag. In that case, the view is created by the first call
+    /// to this function after the image has been acquired, and this function returns `None` if
+    /// the image has never been acquired. See the docs of `SwapchainImage`.
+    ///
+    /// Using the image as an `ImageView` calls this function and panics if it fails.
+    pub fn try_inner_view(&self) -> Result<Option<&UnsafeImageView>, OomError> {
+        if !self.view_ready.load(Ordering::Acquire) {
+            if !self.swapchain.is_image_allocated(self.id as usize) {
+                return Ok(None);
+            }
+
+            let _lock = self.view_lock.lock().unwrap();
+            if !self.view_ready.load(Ordering::Acquire) {
+                unsafe {
+                    let layers = self.array_layers();
+                    let view = try!(UnsafeImageView::raw(&self.image, 0 .. 1, 0 .. layers));
+                    *self.view.get() = Some(view);
+                }
+                self.view_ready.store(true, Ordering::Release);
+            }
+        }
+
+        unsafe { Ok((*self.view.get()).as_ref()) }
+    }
+
     /// Returns the dimensions of the image.
     ///
     /// A `SwapchainImage` is always two-dimensional.
@@ -73,12 +161,267 @@ impl SwapchainImage {
         [dims.width(), dims.height()]
     }
 
+    /// Returns the number of array layers of the image.
+    ///
+    /// This is the number of layers that was passed when creating the swapchain.
+    #[inline]
+    pub fn array_layers(&self) -> u32 {
+        self.image.dimensions().array_layers()
+    }
+
     /// Returns the format of the image.
     // TODO: return `ColorFormat` or something like this instead, for stronger typing
     #[inline]
     pub fn format(&self) -> Format {
         self.format
     }
+
+    /// Builds a view of the image that interprets its content with another format.
+    ///
+    /// This is typically used to draw with an sRGB view on a swapchain whose format is UNORM.
+    /// The format must be one of the `mutable_format_view_formats` that were passed when
+    /// creating the swapchain.
+    ///
+    /// # Panic
+    ///
+    /// - Panicks if `format` isn't in the `view_formats()` of the swapchain.
+    /// - Panicks if the memory of the image hasn't been allocated yet. See the docs of
+    ///   `SwapchainImage`.
+    ///
+    pub fn view_with_format(me: &Arc<SwapchainImage>, format: Format)
+                            -> Result<Arc<SwapchainImageView>, OomError>
+    {
+        assert!(me.swapchain.view_formats().contains(&format));
+        assert!(me.swapchain.is_image_allocated(me.id as usize),
+                "Trying to use a swapchain image that was never acquired");
+
+        let view = unsafe {
+            let layers = me.array_layers();
+            try!(UnsafeImageView::raw_with_format(&me.image, 0 .. 1, 0 .. layers, format))
+        };
+
+        Ok(Arc::new(SwapchainImageView {
+            image: me.clone(),
+            view: view,
+        }))
+    }
+
+    /// Builds a view of a single array layer of the image.
+    ///
+    /// This is typically used with a stereo swapchain to draw each eye to its own layer.
+    ///
+    /// # Panic
+    ///
+    /// - Panicks if `layer` isn't lower than `array_layers()`.
+    /// - Panicks if the memory of the image hasn't been allocated yet. See the docs of
+    ///   `SwapchainImage`.
+    ///
+    pub fn layer_view(me: &Arc<SwapchainImage>, layer: u32)
+                      -> Result<Arc<SwapchainImageView>, OomError>
+    {
+        assert!(layer < me.array_layers());
+        assert!(me.swapchain.is_image_allocated(me.id as usize),
+                "Trying to use a swapchain image that was never acquired");
+
+        let view = unsafe {
+            try!(UnsafeImageView::raw(&me.image, 0 .. 1, layer .. layer + 1))
+        };
+
+        Ok(Arc::new(SwapchainImageView {
+            image: me.clone(),
+            view: view,
+        }))
+    }
+
+    /// Records in `builder` the barrier that transfers the ownership of the image from
+    /// `src_family` to `dst_family`.
+    ///
+    /// With the exclusive sharing mode, an image that is drawn by a queue and then presented
+    /// by a queue of another family must be transferred between the two families, otherwise
+    /// its content is undefined. The transfer is made of two halves that use the same
+    /// barrier: a release in a command buffer submitted to a queue of `src_family`, after the
+    /// draw, and an acquire in a command buffer submitted to a queue of `dst_family`, before the
+    /// presentation. Call this function once with the builder of each of these two command
+    /// buffers, and make the acquire wait for the release with a semaphore.
+    ///
+    /// This isn't needed with the concurrent sharing mode, or if both queues belong to the same
+    /// family.
+    ///
+    /// # Panic
+    ///
+    /// - Panicks if one of the families doesn't belong to the physical device of the swapchain.
+    ///
+    /// # Safety
+    ///
+    /// - The function must be called once with a command buffer submitted to a queue of
+    ///   `src_family` (the release) and once with a command buffer submitted to a queue of
+    ///   `dst_family` (the acquire), and the acquire must wait for the release.
+    /// - The image must be in the `PresentSrc` layout when the barriers are executed.
+    ///
+    pub unsafe fn queue_family_ownership_transfer(me: &Arc<SwapchainImage>,
+                                                  builder: PrimaryCommandBufferBuilder,
+                                                  src_family: QueueFamily,
+                                                  dst_family: QueueFamily)
+                                                  -> PrimaryCommandBufferBuilder
+    {
+        let physical_device = me.swapchain.device().physical_device().internal_object();
+        assert_eq!(src_family.physical_device().internal_object(), physical_device);
+        assert_eq!(dst_family.physical_device().internal_object(), physical_device);
+
+        builder.queue_family_ownership_transfer(me, Layout::PresentSrc, src_family.id(),
+                                                dst_family.id())
+    }
+
+    /// Records in `builder` the commands that copy the content of the image to a buffer, with
+    /// each pixel converted to the `R8G8B8A8Srgb` format.
+    ///
+    /// Once the command buffer has been executed, the buffer contains the pixels of the image
+    /// row by row, each of them made of four sRGB-encoded bytes in the RGBA order. This is what
+    /// image files such as PNG expect, whatever the format of the swapchain.
+    ///
+    /// The conversion is done with a blit to an intermediate image, and takes the format and the
+    /// color space of the swapchain into account. If the values of the image are linear, which
+    /// is the case for sRGB and floating-point formats or for linear color spaces, they are
+    /// encoded to sRGB. Otherwise they are already encoded and are only converted to 8 bits per
+    /// component. The values of HDR color spaces aren't tone-mapped: they are clamped to the
+    /// `[0.0, 1.0]` range, and the values of `Hdr10St2084` and `Hdr10Hlg` are treated as if they
+    /// were sRGB-encoded.
+    ///
+    /// Returns an error if the swapchain wasn't created with the `transfer_source` usage, or if
+    /// the memory of the image hasn't been allocated yet. See the docs of `SwapchainImage`.
+    pub fn capture_image_as_srgb8(me: &Arc<SwapchainImage>, builder: PrimaryCommandBufferBuilder)
+                                  -> Result<(PrimaryCommandBufferBuilder,
+                                             Arc<CpuAccessibleBuffer<[[u8; 4]]>>),
+                                            CaptureImageError>
+    {
+        let device = me.swapchain.device();
+
+        let intermediate_format = try!(capture_intermediate_format(me.swapchain.usage()
+                                                                     .transfer_source,
+                                                                   me.format,
+                                                                   me.swapchain.color_space()));
+        if try!(me.try_inner_view()).is_none() {
+            return Err(CaptureImageError::ImageNotAllocated);
+        }
+
+        let (width, height) = (me.dimensions()[0], me.dimensions()[1]);
+
+        let intermediate = try!(AttachmentImage::new(device, [width, height],
+                                                     intermediate_format));
+
+        let buffer = try!(CpuAccessibleBuffer::array(device, (width * height) as usize,
+                                                     &BufferUsage { transfer_dest: true,
+                                                                    .. BufferUsage::none() },
+                                                     None));
+
+        let coords = [0 .. width as i32, 0 .. height as i32, 0 .. 1];
+        let builder = builder.blit(me, 0, 0 .. 1, coords.clone(), &intermediate, 0, 0 .. 1,
+                                   coords)
+                             .copy_color_image_to_buffer(&buffer, &intermediate, 0, 0 .. 1,
+                                                         [0, 0, 0], [width, height, 1]);
+
+        Ok((builder, buffer))
+    }
+}
+
+// Returns the format of the intermediate image that `capture_image_as_srgb8` blits a swapchain
+// image to, or an error if the swapchain image can't be captured.
+fn capture_intermediate_format(usage_transfer_src: bool, format: Format,
+                               color_space: ColorSpace) -> Result<Format, CaptureImageError>
+{
+    if !usage_transfer_src {
+        return Err(CaptureImageError::MissingTransferSourceUsage);
+    }
+
+    // Blitting to a `Unorm` image copies the values as they are, while blitting to a `Srgb`
+    // image encodes them.
+    if is_srgb_encoded(format, color_space) {
+        Ok(Format::R8G8B8A8Unorm)
+    } else {
+        Ok(Format::R8G8B8A8Srgb)
+    }
+}
+
+/// Error that can happen when capturing the content of a swapchain image.
+///
+/// See `SwapchainImage::capture_image_as_srgb8`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CaptureImageError {
+    /// The swapchain wasn't created with the `transfer_source` usage.
+    MissingTransferSourceUsage,
+    /// The memory of the image hasn't been allocated yet, because the swapchain was created with
+    /// the `deferred_memory_allocation` flag and the image has never been acquired.
+    ImageNotAllocated,
+    /// Error while creating the intermediate image.
+    ImageCreationError(ImageCreationError),
+}
+
+impl error::Error for CaptureImageError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            CaptureImageError::MissingTransferSourceUsage => {
+                "the swapchain wasn't created with the `transfer_source` usage"
+            },
+            CaptureImageError::ImageNotAllocated => {
+                "the memory of the image hasn't been allocated yet"
+            },
+            CaptureImageError::ImageCreationError(_) => {
+                "error while creating the intermediate image"
+            },
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            CaptureImageError::ImageCreationError(ref err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for CaptureImageError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+impl From<ImageCreationError> for CaptureImageError {
+    #[inline]
+    fn from(err: ImageCreationError) -> CaptureImageError {
+        CaptureImageError::ImageCreationError(err)
+    }
+}
+
+impl From<OomError> for CaptureImageError {
+    #[inline]
+    fn from(err: OomError) -> CaptureImageError {
+        CaptureImageError::ImageCreationError(ImageCreationError::OomError(err))
+    }
+}
+
+// Returns true if the values of an image with this format and this color space are already
+// encoded with a non-linear transfer function, in which case they must be copied as they are
+// instead of being encoded to sRGB.
+fn is_srgb_encoded(format: Format, color_space: ColorSpace) -> bool {
+    let linear_format = match format {
+        Format::R8G8B8Srgb | Format::B8G8R8Srgb | Format::R8G8B8A8Srgb |
+        Format::B8G8R8A8Srgb | Format::A8B8G8R8SrgbPack32 => true,
+        Format::R16G16B16A16Sfloat | Format::R32G32B32A32Sfloat |
+        Format::B10G11R11UfloatPack32 | Format::E5B9G9R9UfloatPack32 => true,
+        _ => false,
+    };
+
+    let linear_color_space = match color_space {
+        ColorSpace::ExtendedSrgbLinear | ColorSpace::DisplayP3Linear |
+        ColorSpace::Bt709Linear | ColorSpace::Bt2020Linear |
+        ColorSpace::AdobeRgbLinear => true,
+        _ => false,
+    };
+
+    !linear_format && !linear_color_space
 }
 
 unsafe impl Image for SwapchainImage {
@@ -99,11 +442,13 @@ unsafe impl Image for SwapchainImage {
 
     #[inline]
     fn block_array_layers_range(&self, block: (u32, u32)) -> Range<u32> {
-        0 .. 1
+        0 .. self.array_layers()
     }
 
     #[inline]
     fn initial_layout(&self, _: (u32, u32), _: Layout) -> (Layout, bool, bool) {
+        // If the image is still in the `Undefined` layout, `gpu_access` adds a transition before
+        // the command buffer.
         (Layout::PresentSrc, false, true)
     }
 
@@ -128,21 +473,17 @@ unsafe impl Image for SwapchainImage {
         let signal = Semaphore::new(submission.queue().device());
         let wait = self.swapchain.image_semaphore(self.id, signal.clone()).expect("Try to render to a swapchain image that was not acquired first");
 
-        if guarded.present_layout {
-            return GpuAccessResult {
-                dependencies: if let Some(dependency) = dependency {
-                    vec![dependency]
-                } else {
-                    vec![]
-                },
-                additional_wait_semaphore: Some(wait),
-                additional_signal_semaphore: Some(signal),
-                before_transitions: vec![],
-                after_transitions: vec![],
-            };
-        }
-
-        guarded.present_layout = true;
+        // The first submission that uses the image must transition it out of `Undefined`.
+        let before_transitions = if guarded.present_layout {
+            vec![]
+        } else {
+            guarded.present_layout = true;
+            vec![Transition {
+                block: (0, 0),
+                from: Layout::Undefined,
+                to: Layout::PresentSrc,
+            }]
+        };
 
         GpuAccessResult {
             dependencies: if let Some(dependency) = dependency {
@@ -152,11 +493,7 @@ unsafe impl Image for SwapchainImage {
             },
             additional_wait_semaphore: Some(wait),
             additional_signal_semaphore: Some(signal),
-            before_transitions: vec![Transition {
-                block: (0, 0),
-                from: Layout::Undefined,
-                to: Layout::PresentSrc,
-            }],
+            before_transitions: before_transitions,
             after_transitions: vec![],
         }
     }
@@ -169,6 +506,69 @@ unsafe impl<P> ImageContent<P> for SwapchainImage {
     }
 }
 
+/// View of a `SwapchainImage` with a different format than the image.
+///
+/// See `SwapchainImage::view_with_format`.
+pub struct SwapchainImageView {
+    image: Arc<SwapchainImage>,
+    view: UnsafeImageView,
+}
+
+impl SwapchainImageView {
+    /// Returns the image that this view is about.
+    #[inline]
+    pub fn image(&self) -> &Arc<SwapchainImage> {
+        &self.image
+    }
+}
+
+unsafe impl ImageView for SwapchainImageView {
+    #[inline]
+    fn parent(&self) -> &Image {
+        &*self.image
+    }
+
+    #[inline]
+    fn parent_arc(me: &Arc<Self>) -> Arc<Image> where Self: Sized {
+        me.image.clone() as Arc<_>
+    }
+
+    #[inline]
+    fn blocks(&self) -> Vec<(u32, u32)> {
+        ImageView::blocks(&*self.image)
+    }
+
+    #[inline]
+    fn inner_view(&self) -> &UnsafeImageView {
+        &self.view
+    }
+
+    #[inline]
+    fn descriptor_set_storage_image_layout(&self) -> Layout {
+        self.image.descriptor_set_storage_image_layout()
+    }
+
+    #[inline]
+    fn descriptor_set_combined_image_sampler_layout(&self) -> Layout {
+        self.image.descriptor_set_combined_image_sampler_layout()
+    }
+
+    #[inline]
+    fn descriptor_set_sampled_image_layout(&self) -> Layout {
+        self.image.descriptor_set_sampled_image_layout()
+    }
+
+    #[inline]
+    fn descriptor_set_input_attachment_layout(&self) -> Layout {
+        self.image.descriptor_set_input_attachment_layout()
+    }
+
+    #[inline]
+    fn identity_swizzle(&self) -> bool {
+        true
+    }
+}
+
 unsafe impl ImageView for SwapchainImage {
     #[inline]
     fn parent(&self) -> &Image {
@@ -185,9 +585,12 @@ unsafe impl ImageView for SwapchainImage {
         vec![(0, 0)]
     }
 
-    #[inline]
     fn inner_view(&self) -> &UnsafeImageView {
-        &self.view
+        match self.try_inner_view() {
+            Ok(Some(view)) => view,
+            Ok(None) => panic!("Trying to use a swapchain image that was never acquired"),
+            Err(err) => panic!("Failed to create the view of a swapchain image: {}", err),
+        }
     }
 
     #[inline]
@@ -215,3 +618,40 @@ unsafe impl ImageView for SwapchainImage {
         true
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use format::Format;
+    use swapchain::ColorSpace;
+    use super::capture_intermediate_format;
+    use super::is_srgb_encoded;
+    use super::CaptureImageError;
+
+    #[test]
+    fn srgb_encoded_values() {
+        assert!(is_srgb_encoded(Format::B8G8R8A8Unorm, ColorSpace::SrgbNonLinear));
+        assert!(!is_srgb_encoded(Format::B8G8R8A8Srgb, ColorSpace::SrgbNonLinear));
+        assert!(!is_srgb_encoded(Format::R16G16B16A16Sfloat, ColorSpace::ExtendedSrgbLinear));
+        assert!(!is_srgb_encoded(Format::A2B10G10R10UnormPack32, ColorSpace::Bt2020Linear));
+    }
+
+    #[test]
+    fn capture_without_transfer_source() {
+        assert_eq!(capture_intermediate_format(false, Format::B8G8R8A8Unorm,
+                                               ColorSpace::SrgbNonLinear),
+                   Err(CaptureImageError::MissingTransferSourceUsage));
+    }
+
+    #[test]
+    fn capture_intermediate_formats() {
+        assert_eq!(capture_intermediate_format(true, Format::B8G8R8A8Unorm,
+                                               ColorSpace::SrgbNonLinear),
+                   Ok(Format::R8G8B8A8Unorm));
+        assert_eq!(capture_intermediate_format(true, Format::B8G8R8A8Srgb,
+                                               ColorSpace::SrgbNonLinear),
+                   Ok(Format::R8G8B8A8Srgb));
+        assert_eq!(capture_intermediate_format(true, Format::R16G16B16A16Sfloat,
+                                               ColorSpace::ExtendedSrgbLinear),
+                   Ok(Format::R8G8B8A8Srgb));
+    }
+}
@@ -629,8 +629,22 @@ pub struct UnsafeImageView {
 
 impl UnsafeImageView {
     /// See the docs of new().
+    #[inline]
     pub unsafe fn raw(image: &UnsafeImage, mipmap_levels: Range<u32>, array_layers: Range<u32>)
                       -> Result<UnsafeImageView, OomError>
+    {
+        UnsafeImageView::raw_with_format(image, mipmap_levels, array_layers, image.format)
+    }
+
+    /// Same as `raw`, but the view interprets the content of the image with another format.
+    ///
+    /// # Safety
+    ///
+    /// Same as `raw`. In addition, the image must have been created with the mutable format
+    /// flag, and `format` must be compatible with the format of the image.
+    pub unsafe fn raw_with_format(image: &UnsafeImage, mipmap_levels: Range<u32>,
+                                  array_layers: Range<u32>, format: Format)
+                                  -> Result<UnsafeImageView, OomError>
     {
         let vk = image.device.pointers();
 
@@ -663,7 +677,7 @@ impl UnsafeImageView {
                     (Dimensions::Dim2dArray { .. }, _) => vk::IMAGE_VIEW_TYPE_2D_ARRAY,
                     (Dimensions::Dim3d { .. }, _) => vk::IMAGE_VIEW_TYPE_3D,
                 },      // TODO: cube
-                format: image.format as u32,
+                format: format as u32,
                 components: vk::ComponentMapping { r: 0, g: 0, b: 0, a: 0 },     // FIXME:
                 subresourceRange: vk::ImageSubresourceRange {
                     aspectMask: aspect_mask,
@@ -685,7 +699,7 @@ impl UnsafeImageView {
             device: image.device.clone(),
             usage: image.usage,
             identity_swizzle: true,     // FIXME:
-            format: image.format,
+            format: format,
         })
     }
     
@@ -117,13 +117,38 @@ instance_extensions! {
     khr_mir_surface => b"VK_KHR_mir_surface",
     khr_android_surface => b"VK_KHR_android_surface",
     khr_win32_surface => b"VK_KHR_win32_surface",
+    ext_metal_surface => b"VK_EXT_metal_surface",
+    ext_directfb_surface => b"VK_EXT_directfb_surface",
+    qnx_screen_surface => b"VK_QNX_screen_surface",
     ext_debug_report => b"VK_EXT_debug_report",
+    khr_external_semaphore_capabilities => b"VK_KHR_external_semaphore_capabilities",
+    ext_debug_utils => b"VK_EXT_debug_utils",
+    ext_swapchain_colorspace => b"VK_EXT_swapchain_colorspace",
+    khr_get_surface_capabilities2 => b"VK_KHR_get_surface_capabilities2",
+    ext_surface_maintenance1 => b"VK_EXT_surface_maintenance1",
+    khr_surface_protected_capabilities => b"VK_KHR_surface_protected_capabilities",
+    khr_device_group_creation => b"VK_KHR_device_group_creation",
+    ext_display_surface_counter => b"VK_EXT_display_surface_counter",
 }
 
 extensions! {
     DeviceExtensions,
     khr_swapchain => b"VK_KHR_swapchain",
     khr_display_swapchain => b"VK_KHR_display_swapchain",
+    khr_external_semaphore => b"VK_KHR_external_semaphore",
+    khr_external_semaphore_fd => b"VK_KHR_external_semaphore_fd",
+    khr_external_semaphore_win32 => b"VK_KHR_external_semaphore_win32",
+    ext_debug_marker => b"VK_EXT_debug_marker",
+    google_display_timing => b"VK_GOOGLE_display_timing",
+    ext_swapchain_maintenance1 => b"VK_EXT_swapchain_maintenance1",
+    khr_present_id => b"VK_KHR_present_id",
+    khr_present_wait => b"VK_KHR_present_wait",
+    khr_image_format_list => b"VK_KHR_image_format_list",
+    khr_swapchain_mutable_format => b"VK_KHR_swapchain_mutable_format",
+    khr_timeline_semaphore => b"VK_KHR_timeline_semaphore",
+    khr_incremental_present => b"VK_KHR_incremental_present",
+    khr_device_group => b"VK_KHR_device_group",
+    ext_display_control => b"VK_EXT_display_control",
 }
 
 #[cfg(test)]
@@ -14,10 +14,12 @@ use std::fmt;
 use std::mem;
 use std::ptr;
 use std::sync::Arc;
+use std::vec::IntoIter;
 use smallvec::SmallVec;
 
 //use alloc::Alloc;
 use instance::loader;
+use swapchain::display::Display;
 use check_errors;
 use Error;
 use OomError;
@@ -37,6 +39,7 @@ pub struct Instance {
     physical_devices: Vec<PhysicalDeviceInfos>,
     vk: vk::InstancePointers,
     extensions: InstanceExtensions,
+    api_version: Version,
 }
 
 impl Instance {
@@ -58,6 +61,13 @@ impl Instance {
             None
         };
 
+        // Without an `ApplicationInfo`, the implementation assumes that we use Vulkan 1.0.
+        // Otherwise we request the highest version supported by the loader.
+        let api_version = match app_infos {
+            Some(_) => loader_api_version(),
+            None => Version { major: 1, minor: 0, patch: 0 },
+        };
+
         // Building the `vk::ApplicationInfo` if required.
         let app_infos = if let Some(app_infos) = app_infos {
             Some(vk::ApplicationInfo {
@@ -67,7 +77,7 @@ impl Instance {
                 applicationVersion: app_infos.application_version,
                 pEngineName: app_infos_strings.as_ref().unwrap().1.as_ptr(),
                 engineVersion: app_infos.engine_version,
-                apiVersion: Version { major: 1, minor: 0, patch: 0 }.into_vulkan_version(), // TODO: 
+                apiVersion: api_version.into_vulkan_version(),
             })
 
         } else {
@@ -182,6 +192,7 @@ impl Instance {
             physical_devices: physical_devices,
             vk: vk,
             extensions: extensions.clone(),
+            api_version: api_version,
         }))
     }
 
@@ -198,6 +209,16 @@ impl Instance {
     pub fn loaded_extensions(&self) -> &InstanceExtensions {
         &self.extensions
     }
+
+    /// Returns the version of Vulkan that was requested when creating the instance.
+    ///
+    /// This is the highest version supported by the Vulkan loader if an `ApplicationInfo` was
+    /// passed to `new`, and Vulkan 1.0 otherwise. The functionalities of this version can only be
+    /// used with physical devices whose `api_version()` is at least as high.
+    #[inline]
+    pub fn api_version(&self) -> Version {
+        self.api_version
+    }
 }
 
 impl fmt::Debug for Instance {
@@ -246,6 +267,32 @@ pub struct ApplicationInfo<'a> {
     pub engine_version: u32,
 }
 
+// Returns the highest version of Vulkan supported by the loader. Loaders that only support
+// Vulkan 1.0 don't provide `vkEnumerateInstanceVersion`.
+fn loader_api_version() -> Version {
+    let v1_0 = Version { major: 1, minor: 0, patch: 0 };
+
+    let f = match loader::static_functions() {
+        Ok(f) => f,
+        Err(_) => return v1_0,
+    };
+
+    unsafe {
+        let name = CString::new("vkEnumerateInstanceVersion").unwrap();
+        let ptr = f.GetInstanceProcAddr(0, name.as_ptr()) as *const ();
+        if ptr.is_null() {
+            return v1_0;
+        }
+
+        let enumerate: extern "system" fn(*mut u32) -> vk::Result = mem::transmute(ptr);
+        let mut version = 0;
+        match enumerate(&mut version) {
+            vk::SUCCESS => Version::from_vulkan_version(version),
+            _ => v1_0,
+        }
+    }
+}
+
 /// Error that can happen when creating an instance.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum InstanceCreationError {
@@ -409,6 +456,19 @@ impl<'a> PhysicalDevice<'a> {
         }
     }
 
+    /// Enumerates the displays that are connected to this physical device.
+    ///
+    /// The `VK_KHR_display` extension must be enabled on the instance.
+    ///
+    /// # Panic
+    ///
+    /// - Panicks if the device or host ran out of memory.
+    ///
+    #[inline]
+    pub fn displays(&self) -> IntoIter<Display> {
+        Display::enumerate(self)
+    }
+
     /// Returns the queue family with the given index, or `None` if out of range.
     #[inline]
     pub fn queue_family_by_id(&self, id: u32) -> Option<QueueFamily<'a>> {
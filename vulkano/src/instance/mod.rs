@@ -44,6 +44,7 @@
 //! you can create a `Device` object from it. See the `device` module for more info.
 //!
 pub use features::Features;
+pub use version::Version;
 pub use self::extensions::DeviceExtensions;
 pub use self::extensions::InstanceExtensions;
 pub use self::instance::Instance;
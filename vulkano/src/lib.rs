@@ -52,6 +52,9 @@ extern crate crossbeam;
 extern crate fnv;
 #[macro_use]
 extern crate lazy_static;
+#[cfg(feature = "log")]
+#[macro_use]
+extern crate log;
 extern crate shared_library;
 extern crate smallvec;
 extern crate vk_sys as vk;
@@ -64,6 +67,7 @@ mod version;
 
 pub mod buffer;
 pub mod command_buffer;
+pub mod debug_marker;
 pub mod descriptor;
 pub mod device;
 pub mod format;
@@ -204,6 +208,7 @@ pub enum Error {
     OutOfDate = vk::ERROR_OUT_OF_DATE_KHR,
     IncompatibleDisplay = vk::ERROR_INCOMPATIBLE_DISPLAY_KHR,
     ValidationFailed = vk::ERROR_VALIDATION_FAILED_EXT,
+    InvalidExternalHandle = vk::ERROR_INVALID_EXTERNAL_HANDLE_KHR,
 }
 
 /// Checks whether the result returned correctly.
@@ -232,6 +237,7 @@ fn check_errors(result: vk::Result) -> Result<Success, Error> {
         vk::ERROR_OUT_OF_DATE_KHR => Err(Error::OutOfDate),
         vk::ERROR_INCOMPATIBLE_DISPLAY_KHR => Err(Error::IncompatibleDisplay),
         vk::ERROR_VALIDATION_FAILED_EXT => Err(Error::ValidationFailed),
+        vk::ERROR_INVALID_EXTERNAL_HANDLE_KHR => Err(Error::InvalidExternalHandle),
         c => unreachable!("Unexpected error code returned by Vulkan: {}", c)
     }
 }
@@ -12,15 +12,20 @@
 //! As far as the author knows, no existing device supports these features. Therefore the code here
 //! is mostly a draft and needs rework in both the API and the implementation.
 
+use std::error;
 use std::ffi::CStr;
+use std::fmt;
+use std::mem;
 use std::ptr;
 use std::sync::Arc;
 use std::vec::IntoIter;
 
 use instance::Instance;
 use instance::PhysicalDevice;
+use swapchain::SupportedSurfaceTransforms;
 
 use check_errors;
+use Error;
 use OomError;
 use VulkanObject;
 use VulkanPointers;
@@ -28,8 +33,9 @@ use vk;
 
 // TODO: extract this to a `display` module and solve the visibility problems
 
-/// ?
-// TODO: plane capabilities
+/// One of the planes of a physical device.
+///
+/// A plane is an image layer that the physical device can scan out and composite on a display.
 pub struct DisplayPlane {
     instance: Arc<Instance>,
     physical_device: usize,
@@ -62,32 +68,37 @@ impl DisplayPlane {
             planes
         };
 
-        Ok(planes.into_iter().enumerate().map(|(index, prop)| {
+        let mut output = Vec::with_capacity(planes.len());
+
+        for (index, prop) in planes.into_iter().enumerate() {
             let num = unsafe {
                 let mut num: u32 = 0;
-                check_errors(vk.GetDisplayPlaneSupportedDisplaysKHR(device.internal_object(), index as u32,
-                                                                    &mut num, ptr::null_mut())).unwrap();       // TODO: shouldn't unwrap
+                try!(check_errors(vk.GetDisplayPlaneSupportedDisplaysKHR(device.internal_object(),
+                                                                         index as u32, &mut num,
+                                                                         ptr::null_mut())));
                 num
             };
 
             let supported_displays: Vec<vk::DisplayKHR> = unsafe {
                 let mut displays = Vec::with_capacity(num as usize);
                 let mut num = num;
-                check_errors(vk.GetDisplayPlaneSupportedDisplaysKHR(device.internal_object(),
-                                                                    index as u32, &mut num,
-                                                                    displays.as_mut_ptr())).unwrap();       // TODO: shouldn't unwrap
+                try!(check_errors(vk.GetDisplayPlaneSupportedDisplaysKHR(device.internal_object(),
+                                                                         index as u32, &mut num,
+                                                                         displays.as_mut_ptr())));
                 displays.set_len(num as usize);
                 displays
             };
 
-            DisplayPlane {
+            output.push(DisplayPlane {
                 instance: device.instance().clone(),
                 physical_device: device.index(),
                 index: index as u32,
                 properties: prop,
                 supported_displays: supported_displays,
-            }
-        }).collect::<Vec<_>>().into_iter())
+            });
+        }
+
+        Ok(output.into_iter())
     }
     
     /// Enumerates all the display planes that are available on a given physical device.
@@ -108,6 +119,33 @@ impl DisplayPlane {
         PhysicalDevice::from_index(&self.instance, self.physical_device).unwrap()
     }
 
+    /// Returns the index of the plane.
+    #[inline]
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    /// Returns the current position of the plane in the stack of planes that are composited on
+    /// the display it is currently associated with. Planes with a higher stack index are drawn
+    /// on top of planes with a lower stack index.
+    #[inline]
+    pub fn current_stack_index(&self) -> u32 {
+        self.properties.currentStackIndex
+    }
+
+    /// Returns the list of displays that this plane can be used with.
+    ///
+    /// # Panic
+    ///
+    /// - Panicks if the device or host ran out of memory.
+    ///
+    pub fn supported_displays(&self) -> IntoIter<Display> {
+        let supported = &self.supported_displays;
+        Display::enumerate(&self.physical_device()).filter(|display| {
+            supported.iter().any(|&d| d == display.internal_object())
+        }).collect::<Vec<_>>().into_iter()
+    }
+
     /// Returns true if this plane supports the given display.
     #[inline]
     pub fn supports(&self, display: &Display) -> bool {
@@ -187,6 +225,13 @@ impl Display {
         PhysicalDevice::from_index(&self.instance, self.physical_device).unwrap()
     }
 
+    /// Returns the physical dimensions of the display, in millimeters.
+    #[inline]
+    pub fn physical_dimensions(&self) -> [u32; 2] {
+        let ref r = self.properties.physicalDimensions;
+        [r.width, r.height]
+    }
+
     /// Returns the physical resolution of the display.
     #[inline]
     pub fn physical_resolution(&self) -> [u32; 2] {
@@ -194,6 +239,12 @@ impl Display {
         [r.width, r.height]
     }
 
+    /// Returns the transforms that are supported by the display.
+    #[inline]
+    pub fn supported_transforms(&self) -> SupportedSurfaceTransforms {
+        SupportedSurfaceTransforms::from_bits(self.properties.supportedTransforms)
+    }
+
     /// See the docs of display_modes().
     pub fn display_modes_raw(&self) -> Result<IntoIter<DisplayMode>, OomError> {
         let vk = self.instance.pointers();
@@ -236,6 +287,51 @@ impl Display {
     pub fn display_modes(&self) -> IntoIter<DisplayMode> {
         self.display_modes_raw().unwrap()
     }
+
+    /// Creates a new display mode for this display.
+    ///
+    /// The `visible_region` is the dimensions of the region of the display that is visible, and
+    /// the `refresh_rate` is the number of times the display is refreshed every thousand
+    /// seconds (ie. in millihertz).
+    ///
+    /// Returns `InitializationFailed` if the display doesn't support these parameters.
+    ///
+    /// # Panic
+    ///
+    /// - Panicks if one of the dimensions of `visible_region` or `refresh_rate` is 0.
+    ///
+    pub fn create_mode(&self, visible_region: [u32; 2], refresh_rate: u32)
+                       -> Result<DisplayMode, DisplayModeCreationError>
+    {
+        assert!(visible_region[0] != 0 && visible_region[1] != 0);
+        assert!(refresh_rate != 0);
+
+        let vk = self.instance.pointers();
+
+        let infos = vk::DisplayModeCreateInfoKHR {
+            sType: vk::STRUCTURE_TYPE_DISPLAY_MODE_CREATE_INFO_KHR,
+            pNext: ptr::null(),
+            flags: 0,   // reserved
+            parameters: vk::DisplayModeParametersKHR {
+                visibleRegion: vk::Extent2D { width: visible_region[0], height: visible_region[1] },
+                refreshRate: refresh_rate,
+            },
+        };
+
+        let display_mode = unsafe {
+            let mut output = mem::uninitialized();
+            try!(check_errors(vk.CreateDisplayModeKHR(self.physical_device().internal_object(),
+                                                      self.properties.display, &infos,
+                                                      ptr::null(), &mut output)));
+            output
+        };
+
+        Ok(DisplayMode {
+            display: self.clone(),
+            display_mode: display_mode,
+            parameters: infos.parameters,
+        })
+    }
 }
 
 unsafe impl VulkanObject for Display {
@@ -255,37 +351,6 @@ pub struct DisplayMode {
 }
 
 impl DisplayMode {
-    /*pub fn new(display: &Display) -> Result<Arc<DisplayMode>, OomError> {
-        let vk = instance.pointers();
-        assert!(device.instance().loaded_extensions().khr_display);     // TODO: return error instead
-
-        let parameters = vk::DisplayModeParametersKHR {
-            visibleRegion: vk::Extent2D { width: , height:  },
-            refreshRate: ,
-        };
-
-        let display_mode = {
-            let infos = vk::DisplayModeCreateInfoKHR {
-                sType: vk::STRUCTURE_TYPE_DISPLAY_MODE_CREATE_INFO_KHR,
-                pNext: ptr::null(),
-                flags: 0,   // reserved
-                parameters: parameters,
-            };
-
-            let mut output = mem::uninitialized();
-            try!(check_errors(vk.CreateDisplayModeKHR(display.device.internal_object(),
-                                                      display.display, &infos, ptr::null(),
-                                                      &mut output)));
-            output
-        };
-
-        Ok(Arc::new(DisplayMode {
-            instance: display.device.instance().clone(),
-            display_mode: display_mode,
-            parameters: ,
-        }))
-    }*/
-
     /// Returns the display corresponding to this mode.
     #[inline]
     pub fn display(&self) -> &Display {
@@ -304,6 +369,49 @@ impl DisplayMode {
     pub fn refresh_rate(&self) -> u32 {
         self.parameters.refreshRate
     }
+
+    /// See the docs of plane_capabilities().
+    pub fn plane_capabilities_raw(&self, plane: &DisplayPlane)
+                                  -> Result<DisplayPlaneCapabilities, OomError>
+    {
+        assert_eq!(self.display.physical_device().internal_object(),
+                   plane.physical_device().internal_object());
+
+        let vk = self.display.instance.pointers();
+
+        let caps = unsafe {
+            let mut output: vk::DisplayPlaneCapabilitiesKHR = mem::uninitialized();
+            try!(check_errors(vk.GetDisplayPlaneCapabilitiesKHR(self.display.physical_device()
+                                                                    .internal_object(),
+                                                                self.display_mode, plane.index,
+                                                                &mut output)));
+            output
+        };
+
+        Ok(DisplayPlaneCapabilities {
+            supported_alpha: SupportedDisplayPlaneAlpha::from_bits(caps.supportedAlpha),
+            min_src_position: [caps.minSrcPosition.x, caps.minSrcPosition.y],
+            max_src_position: [caps.maxSrcPosition.x, caps.maxSrcPosition.y],
+            min_src_extent: [caps.minSrcExtent.width, caps.minSrcExtent.height],
+            max_src_extent: [caps.maxSrcExtent.width, caps.maxSrcExtent.height],
+            min_dst_position: [caps.minDstPosition.x, caps.minDstPosition.y],
+            max_dst_position: [caps.maxDstPosition.x, caps.maxDstPosition.y],
+            min_dst_extent: [caps.minDstExtent.width, caps.minDstExtent.height],
+            max_dst_extent: [caps.maxDstExtent.width, caps.maxDstExtent.height],
+        })
+    }
+
+    /// Returns the capabilities of a plane when it is used with this mode.
+    ///
+    /// # Panic
+    ///
+    /// - Panicks if the device or host ran out of memory.
+    /// - Panicks if this mode and `plane` don't belong to the same physical device.
+    ///
+    #[inline]
+    pub fn plane_capabilities(&self, plane: &DisplayPlane) -> DisplayPlaneCapabilities {
+        self.plane_capabilities_raw(plane).unwrap()
+    }
 }
 
 unsafe impl VulkanObject for DisplayMode {
@@ -314,3 +422,143 @@ unsafe impl VulkanObject for DisplayMode {
         self.display_mode
     }
 }
+
+/// The capabilities of a display plane when used with a display mode.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DisplayPlaneCapabilities {
+    /// List of alpha blending modes supported by the plane.
+    pub supported_alpha: SupportedDisplayPlaneAlpha,
+
+    /// Minimum position of the region of the image that is shown on the display.
+    pub min_src_position: [i32; 2],
+
+    /// Maximum position of the region of the image that is shown on the display.
+    pub max_src_position: [i32; 2],
+
+    /// Minimum dimensions of the region of the image that is shown on the display.
+    pub min_src_extent: [u32; 2],
+
+    /// Maximum dimensions of the region of the image that is shown on the display.
+    pub max_src_extent: [u32; 2],
+
+    /// Minimum position of the region of the display where the plane is shown.
+    pub min_dst_position: [i32; 2],
+
+    /// Maximum position of the region of the display where the plane is shown.
+    pub max_dst_position: [i32; 2],
+
+    /// Minimum dimensions of the region of the display where the plane is shown.
+    pub min_dst_extent: [u32; 2],
+
+    /// Maximum dimensions of the region of the display where the plane is shown.
+    pub max_dst_extent: [u32; 2],
+}
+
+/// List of alpha blending modes supported by a display plane.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SupportedDisplayPlaneAlpha {
+    /// The plane is shown opaque, and the alpha of its pixels is ignored.
+    pub opaque: bool,
+    /// The plane is blended with what is below it using a single alpha value for the whole
+    /// plane.
+    pub global: bool,
+    /// The plane is blended using the alpha of each of its pixels, whose color components
+    /// aren't multiplied by the alpha.
+    pub per_pixel: bool,
+    /// The plane is blended using the alpha of each of its pixels, whose color components are
+    /// already multiplied by the alpha.
+    pub per_pixel_premultiplied: bool,
+}
+
+impl SupportedDisplayPlaneAlpha {
+    /// Builds a `SupportedDisplayPlaneAlpha` with all fields set to false.
+    #[inline]
+    pub fn none() -> SupportedDisplayPlaneAlpha {
+        SupportedDisplayPlaneAlpha {
+            opaque: false,
+            global: false,
+            per_pixel: false,
+            per_pixel_premultiplied: false,
+        }
+    }
+
+    #[inline]
+    fn from_bits(val: u32) -> SupportedDisplayPlaneAlpha {
+        let mut result = SupportedDisplayPlaneAlpha::none();
+        if (val & vk::DISPLAY_PLANE_ALPHA_OPAQUE_BIT_KHR) != 0 { result.opaque = true; }
+        if (val & vk::DISPLAY_PLANE_ALPHA_GLOBAL_BIT_KHR) != 0 { result.global = true; }
+        if (val & vk::DISPLAY_PLANE_ALPHA_PER_PIXEL_BIT_KHR) != 0 { result.per_pixel = true; }
+        if (val & vk::DISPLAY_PLANE_ALPHA_PER_PIXEL_PREMULTIPLIED_BIT_KHR) != 0 {
+            result.per_pixel_premultiplied = true;
+        }
+        result
+    }
+}
+
+/// Event of a display that a fence can be registered for.
+///
+/// See `Device::register_display_event`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum DisplayEventType {
+    /// The first pixel of a new frame is scanned out of the display. This happens once per
+    /// vertical blanking period.
+    FirstPixelOut,
+}
+
+impl DisplayEventType {
+    #[inline]
+    #[doc(hidden)]
+    pub fn to_vk(&self) -> vk::DisplayEventTypeEXT {
+        match *self {
+            DisplayEventType::FirstPixelOut => vk::DISPLAY_EVENT_TYPE_FIRST_PIXEL_OUT_EXT,
+        }
+    }
+}
+
+/// Error that can happen when creating a display mode.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DisplayModeCreationError {
+    /// Not enough memory.
+    OomError(OomError),
+
+    /// The display doesn't support the requested parameters.
+    InitializationFailed,
+}
+
+impl error::Error for DisplayModeCreationError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            DisplayModeCreationError::OomError(_) => "not enough memory available",
+            DisplayModeCreationError::InitializationFailed => "the display doesn't support the \
+                                                               requested parameters",
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            DisplayModeCreationError::OomError(ref err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for DisplayModeCreationError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+impl From<Error> for DisplayModeCreationError {
+    #[inline]
+    fn from(err: Error) -> DisplayModeCreationError {
+        match err {
+            err @ Error::OutOfHostMemory => DisplayModeCreationError::OomError(OomError::from(err)),
+            err @ Error::OutOfDeviceMemory => DisplayModeCreationError::OomError(OomError::from(err)),
+            Error::InitializationFailed => DisplayModeCreationError::InitializationFailed,
+            _ => panic!("unexpected error: {:?}", err)
+        }
+    }
+}
@@ -22,6 +22,20 @@
 //! shown on screen. In order to show the output on screen, you have to *present* the swapchain
 //! by using the method with the same name.
 //!
+//! # Image layouts
+//!
+//! The images of a swapchain are in the `Undefined` layout when they are acquired for the first
+//! time, and must be in the `PresentSrc` layout when they are presented. You don't need to
+//! record any barrier for this: the first submission that uses an image automatically
+//! transitions it to `PresentSrc`, and command buffers transition the image from and back to
+//! this layout around each use. See the docs of `SwapchainImage` for more details.
+//!
+//! # Logging
+//!
+//! If the `log` feature of vulkano is enabled, the parameters passed to `vkCreateSwapchainKHR`
+//! are logged at the debug level every time a swapchain is created or recreated. This includes
+//! the number of images after it has been adjusted to the capabilities of the surface.
+//!
 //! # Extensions
 //! 
 //! Theses capabilities depend on some extensions:
@@ -36,19 +50,51 @@
 //! - `VK_KHR_mir_surface`
 //! - `VK_KHR_android_surface`
 //! - `VK_KHR_win32_surface`
+//! - `VK_EXT_metal_surface`
+//! - `VK_EXT_directfb_surface`
+//! - `VK_QNX_screen_surface`
 //!
 
 pub use self::surface::Capabilities;
+pub use self::surface::CapabilitiesError;
+pub use self::surface::ImageCountRange;
 pub use self::surface::Surface;
+pub use self::surface::SurfaceApi;
 pub use self::surface::PresentMode;
+pub use self::surface::SurfaceCounter;
 pub use self::surface::SurfaceTransform;
+pub use self::surface::SupportedSurfaceTransforms;
+pub use self::surface::SupportedPresentGravity;
+pub use self::surface::SupportedPresentModes;
+pub use self::surface::SupportedScalingBehaviors;
 pub use self::surface::CompositeAlpha;
 pub use self::surface::ColorSpace;
 pub use self::surface::SurfaceCreationError;
+pub use self::surface::SurfaceFormat2;
+pub use self::surface::present_capable_devices;
+pub use self::pacing::FramePacer;
+pub use self::pacing::FrameThrottle;
+pub use self::pacing::FrameThrottleError;
+pub use self::present_region::PresentRegion;
+pub use self::present_region::RectangleLayer;
+pub use self::swapchain::ImageCount;
+pub use self::swapchain::PastPresentationTiming;
 pub use self::swapchain::Swapchain;
+pub use self::swapchain::SwapchainCreateFlags;
+pub use self::swapchain::SwapchainCreationError;
+pub use self::swapchain::SwapchainOptions;
 pub use self::swapchain::AcquireError;
+pub use self::swapchain::acquire_next_image_or_recreate;
 pub use self::swapchain::PresentError;
+pub use self::swapchain::PresentGravity;
+pub use self::swapchain::present_multiple;
+pub use self::swapchain::PresentWaitError;
+pub use self::swapchain::ReleaseImagesError;
+pub use self::swapchain::ScalingBehavior;
+pub use self::swapchain::SwapchainCounterError;
 
 pub mod display;
+mod pacing;
+mod present_region;
 mod surface;
 mod swapchain;
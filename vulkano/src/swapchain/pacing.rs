@@ -0,0 +1,317 @@
+// Copyright (c) 2016 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::collections::VecDeque;
+use std::error;
+use std::fmt;
+use std::sync::Arc;
+use std::sync::Weak;
+use std::time::Duration;
+
+use device::Queue;
+use swapchain::PastPresentationTiming;
+use swapchain::PresentError;
+use swapchain::PresentWaitError;
+use swapchain::Swapchain;
+
+/// Keeps track of the intervals between the most recent presentations of a swapchain.
+///
+/// Each frame, pass the result of `Swapchain::past_presentation_timing()` to `add_timings`. You
+/// can then query the average time between two presentations and how much this time varies,
+/// which is a good indicator of how smooth the animation looks to the user.
+#[derive(Debug, Clone)]
+pub struct FramePacer {
+    // Intervals in nanoseconds between consecutive presentations, oldest first.
+    intervals: VecDeque<u64>,
+    // Maximum number of intervals to keep.
+    capacity: usize,
+    // Time at which the latest presentation happened.
+    last_present_time: Option<u64>,
+}
+
+impl FramePacer {
+    /// Builds a new `FramePacer` that keeps track of the `capacity` most recent intervals.
+    ///
+    /// # Panic
+    ///
+    /// - Panicks if `capacity` is 0.
+    ///
+    #[inline]
+    pub fn new(capacity: usize) -> FramePacer {
+        assert!(capacity >= 1);
+
+        FramePacer {
+            intervals: VecDeque::with_capacity(capacity),
+            capacity: capacity,
+            last_present_time: None,
+        }
+    }
+
+    /// Adds timing information about presentations that have completed.
+    ///
+    /// The timings must be passed in the order in which the presentations happened, which is the
+    /// order returned by `Swapchain::past_presentation_timing()`.
+    pub fn add_timings(&mut self, timings: &[PastPresentationTiming]) {
+        for timing in timings {
+            let time = timing.actual_present_time;
+
+            if let Some(last) = self.last_present_time {
+                if time > last {
+                    if self.intervals.len() == self.capacity {
+                        self.intervals.pop_front();
+                    }
+                    self.intervals.push_back(time - last);
+                }
+            }
+
+            self.last_present_time = Some(time);
+        }
+    }
+
+    /// Returns the average time between two presentations.
+    ///
+    /// Returns `None` if fewer than two presentations have been recorded.
+    pub fn average_frame_time(&self) -> Option<Duration> {
+        self.average().map(|avg| duration_from_nanos(avg as u64))
+    }
+
+    /// Returns the standard deviation of the time between two presentations.
+    ///
+    /// A value close to zero means that the frames are shown at a steady rate.
+    ///
+    /// Returns `None` if fewer than two presentations have been recorded.
+    pub fn jitter(&self) -> Option<Duration> {
+        let avg = match self.average() {
+            Some(avg) => avg,
+            None => return None,
+        };
+
+        let variance = self.intervals.iter().fold(0.0, |sum, &i| {
+            let diff = i as f64 - avg;
+            sum + diff * diff
+        }) / self.intervals.len() as f64;
+
+        Some(duration_from_nanos(variance.sqrt() as u64))
+    }
+
+    /// Forgets all the presentations that have been recorded.
+    #[inline]
+    pub fn reset(&mut self) {
+        self.intervals.clear();
+        self.last_present_time = None;
+    }
+
+    // Returns the average interval in nanoseconds.
+    fn average(&self) -> Option<f64> {
+        if self.intervals.is_empty() {
+            return None;
+        }
+
+        let sum = self.intervals.iter().fold(0.0, |sum, &i| sum + i as f64);
+        Some(sum / self.intervals.len() as f64)
+    }
+}
+
+/// Presents images and prevents the CPU from getting too many frames ahead of the screen.
+///
+/// Each call to `present` presents an image with `Swapchain::present_with_id`, using an
+/// identifier that increases every frame, and then calls `Swapchain::wait_for_present` with the
+/// identifier of the frame that was presented `max_frames_ahead` frames earlier. This bounds the
+/// latency between the moment the application reads the user's input and the moment the
+/// corresponding frame is shown.
+///
+/// The `VK_KHR_present_id` and `VK_KHR_present_wait` device extensions must be enabled,
+/// otherwise `present` returns an error wrapping the corresponding `MissingExtension` error.
+///
+/// The same `FrameThrottle` can keep being used after the swapchain has been recreated. Frames
+/// presented to the old swapchain are not waited for.
+#[derive(Debug, Clone)]
+pub struct FrameThrottle {
+    max_frames_ahead: u64,
+    // Identifier to use for the next presentation.
+    next_id: u64,
+    // Swapchain that was passed to the latest call to `present`.
+    swapchain: Weak<Swapchain>,
+    // Identifier of the first presentation to `swapchain`.
+    first_id: u64,
+}
+
+impl FrameThrottle {
+    /// Builds a new `FrameThrottle` that lets the CPU be at most `max_frames_ahead` frames ahead
+    /// of the screen.
+    ///
+    /// With 0, each call to `present` waits until the image is shown to the user.
+    #[inline]
+    pub fn new(max_frames_ahead: u32) -> FrameThrottle {
+        FrameThrottle {
+            max_frames_ahead: max_frames_ahead as u64,
+            // Identifiers start at 1, as 0 means that there's no identifier.
+            next_id: 1,
+            swapchain: Weak::new(),
+            first_id: 1,
+        }
+    }
+
+    /// Presents an image, then waits until the frame that was presented `max_frames_ahead`
+    /// frames earlier has been shown to the user, or until `timeout` is reached.
+    ///
+    /// See `Swapchain::present`.
+    ///
+    /// Returns `FrameThrottleError::Present(PresentError::NonMonotonicPresentId { .. })` if the
+    /// swapchain was presented to with a higher identifier outside of this `FrameThrottle`.
+    pub fn present(&mut self, swapchain: &Arc<Swapchain>, queue: &Arc<Queue>, index: usize,
+                   timeout: Duration) -> Result<(), FrameThrottleError>
+    {
+        let same_swapchain = match self.swapchain.upgrade() {
+            Some(s) => &*s as *const Swapchain == &**swapchain as *const Swapchain,
+            None => false,
+        };
+
+        if !same_swapchain {
+            self.swapchain = Arc::downgrade(swapchain);
+            self.first_id = self.next_id;
+        }
+
+        let id = self.next_id;
+        self.next_id += 1;
+        try!(swapchain.present_with_id(queue, index, id));
+
+        if let Some(wait_id) = self.id_to_wait(id) {
+            try!(swapchain.wait_for_present(wait_id, timeout));
+        }
+
+        Ok(())
+    }
+
+    // Returns the identifier of the presentation to wait for after presenting `id`, if any.
+    #[inline]
+    fn id_to_wait(&self, id: u64) -> Option<u64> {
+        if id >= self.first_id + self.max_frames_ahead {
+            Some(id - self.max_frames_ahead)
+        } else {
+            None
+        }
+    }
+}
+
+/// Error that can happen when calling `FrameThrottle::present`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FrameThrottleError {
+    /// Presenting the image failed.
+    Present(PresentError),
+
+    /// Waiting for a previous frame to be shown failed.
+    Wait(PresentWaitError),
+}
+
+impl error::Error for FrameThrottleError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            FrameThrottleError::Present(_) => "failed to present the image",
+            FrameThrottleError::Wait(_) => "failed to wait for a previous frame to be shown",
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            FrameThrottleError::Present(ref err) => Some(err),
+            FrameThrottleError::Wait(ref err) => Some(err),
+        }
+    }
+}
+
+impl fmt::Display for FrameThrottleError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+impl From<PresentError> for FrameThrottleError {
+    #[inline]
+    fn from(err: PresentError) -> FrameThrottleError {
+        FrameThrottleError::Present(err)
+    }
+}
+
+impl From<PresentWaitError> for FrameThrottleError {
+    #[inline]
+    fn from(err: PresentWaitError) -> FrameThrottleError {
+        FrameThrottleError::Wait(err)
+    }
+}
+
+#[inline]
+fn duration_from_nanos(nanos: u64) -> Duration {
+    Duration::new(nanos / 1_000_000_000, (nanos % 1_000_000_000) as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+    use swapchain::FramePacer;
+    use swapchain::FrameThrottle;
+    use swapchain::PastPresentationTiming;
+
+    fn timing(actual_present_time: u64) -> PastPresentationTiming {
+        PastPresentationTiming {
+            present_id: 0,
+            desired_present_time: 0,
+            actual_present_time: actual_present_time,
+            earliest_present_time: 0,
+            present_margin: 0,
+        }
+    }
+
+    #[test]
+    fn empty() {
+        let mut pacer = FramePacer::new(8);
+        assert_eq!(pacer.average_frame_time(), None);
+        pacer.add_timings(&[timing(1000)]);
+        assert_eq!(pacer.jitter(), None);
+    }
+
+    #[test]
+    fn steady() {
+        let mut pacer = FramePacer::new(8);
+        pacer.add_timings(&[timing(0), timing(16_000_000), timing(32_000_000)]);
+        pacer.add_timings(&[timing(48_000_000)]);
+        assert_eq!(pacer.average_frame_time(), Some(Duration::new(0, 16_000_000)));
+        assert_eq!(pacer.jitter(), Some(Duration::new(0, 0)));
+    }
+
+    #[test]
+    fn jitter() {
+        let mut pacer = FramePacer::new(8);
+        pacer.add_timings(&[timing(0), timing(10_000_000), timing(30_000_000)]);
+        assert_eq!(pacer.average_frame_time(), Some(Duration::new(0, 15_000_000)));
+        assert_eq!(pacer.jitter(), Some(Duration::new(0, 5_000_000)));
+    }
+
+    #[test]
+    fn capacity() {
+        let mut pacer = FramePacer::new(2);
+        pacer.add_timings(&[timing(0), timing(50), timing(60), timing(70)]);
+        assert_eq!(pacer.average_frame_time(), Some(Duration::new(0, 10)));
+    }
+
+    #[test]
+    fn throttle_ids() {
+        let throttle = FrameThrottle::new(2);
+        assert_eq!(throttle.id_to_wait(1), None);
+        assert_eq!(throttle.id_to_wait(2), None);
+        assert_eq!(throttle.id_to_wait(3), Some(1));
+        assert_eq!(throttle.id_to_wait(10), Some(8));
+
+        let throttle = FrameThrottle::new(0);
+        assert_eq!(throttle.id_to_wait(1), Some(1));
+    }
+}
@@ -0,0 +1,188 @@
+// Copyright (c) 2016 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use swapchain::SurfaceTransform;
+use vk;
+
+/// Region of a swapchain image that has changed since the image was last presented.
+///
+/// Passed to `Swapchain::present_incremental`. The presentation engine may use it to only
+/// update the parts of the screen that have changed, but this is only a hint: the
+/// implementation is free to ignore it and to update the whole image. An empty list of
+/// rectangles means that the whole image has changed.
+///
+/// # Coordinates
+///
+/// The rectangles are in the coordinate space of the swapchain image, before the surface
+/// transform of the swapchain is applied. With pre-rotation, where the swapchain uses a
+/// transform other than `Identity` and the application renders rotated content, this isn't the
+/// coordinate space of the screen. Use `transformed` to convert rectangles expressed in screen
+/// coordinates.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PresentRegion {
+    /// List of rectangles that have changed.
+    pub rectangles: Vec<RectangleLayer>,
+}
+
+impl PresentRegion {
+    /// Converts the rectangles from the coordinate space of the screen to the coordinate space
+    /// of the swapchain image, for a swapchain that uses the given surface transform.
+    ///
+    /// The presentation engine applies `transform` to the image to display it: for example
+    /// with `Rotate90`, the image is rotated by 90 degrees clockwise. This function applies the
+    /// inverse transformation to the rectangles, so that they designate the parts of the image
+    /// that end up in the given parts of the screen. `extent` is the width and height of the
+    /// swapchain image, which are swapped compared to the screen for the transforms that
+    /// rotate by 90 or 270 degrees.
+    ///
+    /// `Inherit` is treated like `Identity`, as the actual transform isn't known.
+    pub fn transformed(&self, transform: SurfaceTransform, extent: [u32; 2]) -> PresentRegion {
+        PresentRegion {
+            rectangles: self.rectangles.iter().map(|r| r.transformed(transform, extent))
+                                       .collect(),
+        }
+    }
+
+    /// Returns the list of rectangles, as passed to Vulkan.
+    #[doc(hidden)]
+    #[inline]
+    pub fn to_vk(&self) -> Vec<vk::RectLayerKHR> {
+        self.rectangles.iter().map(|r| r.to_vk()).collect()
+    }
+}
+
+/// Rectangle of a layer of a swapchain image.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct RectangleLayer {
+    /// Coordinates in pixels of the top-left corner of the rectangle.
+    pub offset: [i32; 2],
+
+    /// Dimensions in pixels of the rectangle.
+    pub extent: [u32; 2],
+
+    /// Layer of the image. For non-stereoscopic images, this is always 0.
+    pub layer: u32,
+}
+
+impl RectangleLayer {
+    /// Builds a `RectangleLayer` from its top-left corner, its dimensions and its layer.
+    #[inline]
+    pub fn new(offset: [i32; 2], extent: [u32; 2], layer: u32) -> RectangleLayer {
+        RectangleLayer {
+            offset: offset,
+            extent: extent,
+            layer: layer,
+        }
+    }
+
+    // See `PresentRegion::transformed`.
+    fn transformed(&self, transform: SurfaceTransform, extent: [u32; 2]) -> RectangleLayer {
+        let (w, h) = (extent[0] as i32, extent[1] as i32);
+        let (x, y) = (self.offset[0], self.offset[1]);
+        let (rw, rh) = (self.extent[0] as i32, self.extent[1] as i32);
+
+        let (offset, swapped) = match transform {
+            SurfaceTransform::Identity | SurfaceTransform::Inherit => ([x, y], false),
+            SurfaceTransform::Rotate90 => ([y, h - x - rw], true),
+            SurfaceTransform::Rotate180 => ([w - x - rw, h - y - rh], false),
+            SurfaceTransform::Rotate270 => ([w - y - rh, x], true),
+            SurfaceTransform::HorizontalMirror => ([w - x - rw, y], false),
+            SurfaceTransform::HorizontalMirrorRotate90 => ([w - y - rh, h - x - rw], true),
+            SurfaceTransform::HorizontalMirrorRotate180 => ([x, h - y - rh], false),
+            SurfaceTransform::HorizontalMirrorRotate270 => ([y, x], true),
+        };
+
+        RectangleLayer {
+            offset: offset,
+            extent: if swapped { [self.extent[1], self.extent[0]] } else { self.extent },
+            layer: self.layer,
+        }
+    }
+
+    #[inline]
+    fn to_vk(&self) -> vk::RectLayerKHR {
+        vk::RectLayerKHR {
+            offset: vk::Offset2D { x: self.offset[0], y: self.offset[1] },
+            extent: vk::Extent2D { width: self.extent[0], height: self.extent[1] },
+            layer: self.layer,
+        }
+    }
+}
+
+/// Builds a rectangle of the first layer from an `[x, y, width, height]` array.
+///
+/// # Panic
+///
+/// - Panicks if the width or the height is negative.
+///
+impl From<[i32; 4]> for RectangleLayer {
+    #[inline]
+    fn from(rect: [i32; 4]) -> RectangleLayer {
+        assert!(rect[2] >= 0 && rect[3] >= 0);
+        RectangleLayer::new([rect[0], rect[1]], [rect[2] as u32, rect[3] as u32], 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use swapchain::PresentRegion;
+    use swapchain::RectangleLayer;
+    use swapchain::SurfaceTransform;
+
+    #[test]
+    fn present_region_to_vk() {
+        let region = PresentRegion {
+            rectangles: vec![RectangleLayer::new([-4, 8], [16, 32], 1)],
+        };
+
+        let rects = region.to_vk();
+        assert_eq!(rects.len(), 1);
+        assert_eq!((rects[0].offset.x, rects[0].offset.y), (-4, 8));
+        assert_eq!((rects[0].extent.width, rects[0].extent.height), (16, 32));
+        assert_eq!(rects[0].layer, 1);
+    }
+
+    #[test]
+    fn transformed_rectangles() {
+        // A 10x20 rectangle at the top-left corner of a 100x50 screen.
+        let region = PresentRegion { rectangles: vec![RectangleLayer::from([0, 0, 10, 20])] };
+        let rect = |transform, extent| region.transformed(transform, extent).rectangles[0];
+
+        assert_eq!(rect(SurfaceTransform::Identity, [100, 50]),
+                   RectangleLayer::from([0, 0, 10, 20]));
+        assert_eq!(rect(SurfaceTransform::Rotate180, [100, 50]),
+                   RectangleLayer::from([90, 30, 10, 20]));
+        assert_eq!(rect(SurfaceTransform::HorizontalMirror, [100, 50]),
+                   RectangleLayer::from([90, 0, 10, 20]));
+        assert_eq!(rect(SurfaceTransform::HorizontalMirrorRotate180, [100, 50]),
+                   RectangleLayer::from([0, 30, 10, 20]));
+
+        // The image of a rotated swapchain is 50x100.
+        assert_eq!(rect(SurfaceTransform::Rotate90, [50, 100]),
+                   RectangleLayer::from([0, 90, 20, 10]));
+        assert_eq!(rect(SurfaceTransform::Rotate270, [50, 100]),
+                   RectangleLayer::from([30, 0, 20, 10]));
+        assert_eq!(rect(SurfaceTransform::HorizontalMirrorRotate90, [50, 100]),
+                   RectangleLayer::from([30, 90, 20, 10]));
+        assert_eq!(rect(SurfaceTransform::HorizontalMirrorRotate270, [50, 100]),
+                   RectangleLayer::from([0, 0, 20, 10]));
+    }
+
+    #[test]
+    fn rectangle_from_array() {
+        let rect = RectangleLayer::from([10, -20, 30, 40]);
+        assert_eq!(rect, RectangleLayer::new([10, -20], [30, 40], 0));
+    }
+
+    #[test]
+    #[should_panic]
+    fn rectangle_from_array_negative_extent() {
+        let _ = RectangleLayer::from([0, 0, -1, 1]);
+    }
+}
@@ -7,18 +7,24 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+use std::cmp;
 use std::error;
 use std::fmt;
 use std::mem;
+use std::os::raw::c_void;
 use std::ptr;
 use std::sync::Arc;
 
+use device::Device;
 use format::Format;
 use format::FormatDesc;
 use image::Usage as ImageUsage;
 use instance::Instance;
 use instance::PhysicalDevice;
 use instance::QueueFamily;
+use instance::Version;
+use swapchain::PresentGravity;
+use swapchain::ScalingBehavior;
 use swapchain::display::DisplayMode;
 use swapchain::display::DisplayPlane;
 
@@ -32,10 +38,15 @@ use vk;
 /// Represents a surface on the screen.
 ///
 /// Creating a `Surface` is platform-specific.
+///
+/// A `Surface` holds a reference to the `Instance` it was created from. The instance is
+/// therefore only destroyed after all of its surfaces, even if you drop your own handle to the
+/// instance first.
 #[derive(Debug)]
 pub struct Surface {
     instance: Arc<Instance>,
     surface: vk::SurfaceKHR,
+    api: SurfaceApi,
 }
 
 impl Surface {
@@ -49,8 +60,7 @@ impl Surface {
     pub fn from_display_mode(display_mode: &DisplayMode, plane: &DisplayPlane)
                              -> Result<Arc<Surface>, SurfaceCreationError>
     {
-        unimplemented!()        // TODO:
-        /*if !display_mode.display().physical_device().instance().loaded_extensions().khr_display {
+        if !display_mode.display().physical_device().instance().loaded_extensions().khr_display {
             return Err(SurfaceCreationError::MissingExtension { name: "VK_KHR_display" });
         }
 
@@ -67,14 +77,14 @@ impl Surface {
                 pNext: ptr::null(),
                 flags: 0,   // reserved
                 displayMode: display_mode.internal_object(),
-                planeIndex: plane.index,
-                planeStackIndex: plane.properties.currentStackIndex,
+                planeIndex: plane.index(),
+                planeStackIndex: plane.current_stack_index(),
                 transform: vk::SURFACE_TRANSFORM_IDENTITY_BIT_KHR,      // TODO: let user choose
                 globalAlpha: 0.0,       // TODO: let user choose
                 alphaMode: vk::DISPLAY_PLANE_ALPHA_OPAQUE_BIT_KHR,       // TODO: let user choose
                 imageExtent: vk::Extent2D {     // TODO: let user choose
-                    width: display_mode.parameters.visibleRegion.width,
-                    height: display_mode.parameters.visibleRegion.height,
+                    width: display_mode.visible_region()[0],
+                    height: display_mode.visible_region()[1],
                 },
             };
 
@@ -87,7 +97,8 @@ impl Surface {
         Ok(Arc::new(Surface {
             instance: instance.clone(),
             surface: surface,
-        }))*/
+            api: SurfaceApi::DisplayPlane,
+        }))
     }
 
     /// Creates a `Surface` from a Win32 window.
@@ -125,6 +136,7 @@ impl Surface {
         Ok(Arc::new(Surface {
             instance: instance.clone(),
             surface: surface,
+            api: SurfaceApi::Win32,
         }))
     }
 
@@ -163,6 +175,7 @@ impl Surface {
         Ok(Arc::new(Surface {
             instance: instance.clone(),
             surface: surface,
+            api: SurfaceApi::Xcb,
         }))
     }
 
@@ -201,6 +214,7 @@ impl Surface {
         Ok(Arc::new(Surface {
             instance: instance.clone(),
             surface: surface,
+            api: SurfaceApi::Xlib,
         }))
     }
 
@@ -239,6 +253,7 @@ impl Surface {
         Ok(Arc::new(Surface {
             instance: instance.clone(),
             surface: surface,
+            api: SurfaceApi::Wayland,
         }))
     }
 
@@ -278,6 +293,7 @@ impl Surface {
         Ok(Arc::new(Surface {
             instance: instance.clone(),
             surface: surface,
+            api: SurfaceApi::Mir,
         }))
     }
 
@@ -313,9 +329,162 @@ impl Surface {
         Ok(Arc::new(Surface {
             instance: instance.clone(),
             surface: surface,
+            api: SurfaceApi::Android,
+        }))
+    }
+
+    /// Creates a `Surface` from a `CAMetalLayer`.
+    ///
+    /// This is typically used on macOS and iOS with MoltenVK.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that the `layer` is correct and stays alive for the entire
+    /// lifetime of the surface.
+    pub unsafe fn from_metal_layer<T>(instance: &Arc<Instance>, layer: *const T)
+                                      -> Result<Arc<Surface>, SurfaceCreationError>
+    {
+        let vk = instance.pointers();
+
+        if !instance.loaded_extensions().ext_metal_surface {
+            return Err(SurfaceCreationError::MissingExtension { name: "VK_EXT_metal_surface" });
+        }
+
+        let surface = {
+            let infos = vk::MetalSurfaceCreateInfoEXT {
+                sType: vk::STRUCTURE_TYPE_METAL_SURFACE_CREATE_INFO_EXT,
+                pNext: ptr::null(),
+                flags: 0,   // reserved
+                pLayer: layer as *const _,
+            };
+
+            let mut output = mem::uninitialized();
+            try!(check_errors(vk.CreateMetalSurfaceEXT(instance.internal_object(), &infos,
+                                                       ptr::null(), &mut output)));
+            output
+        };
+
+        Ok(Arc::new(Surface {
+            instance: instance.clone(),
+            surface: surface,
+            api: SurfaceApi::Metal,
         }))
     }
 
+    /// Creates a `Surface` from a DirectFB surface.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that the `dfb` and the `surface` are both correct and stay alive
+    /// for the entire lifetime of the surface.
+    pub unsafe fn from_directfb<D, S>(instance: &Arc<Instance>, dfb: *const D, surface: *const S)
+                                      -> Result<Arc<Surface>, SurfaceCreationError>
+    {
+        let vk = instance.pointers();
+
+        if !instance.loaded_extensions().ext_directfb_surface {
+            return Err(SurfaceCreationError::MissingExtension { name: "VK_EXT_directfb_surface" });
+        }
+
+        let surface = {
+            let infos = vk::DirectFBSurfaceCreateInfoEXT {
+                sType: vk::STRUCTURE_TYPE_DIRECTFB_SURFACE_CREATE_INFO_EXT,
+                pNext: ptr::null(),
+                flags: 0,   // reserved
+                dfb: dfb as *mut _,
+                surface: surface as *mut _,
+            };
+
+            let mut output = mem::uninitialized();
+            try!(check_errors(vk.CreateDirectFBSurfaceEXT(instance.internal_object(), &infos,
+                                                          ptr::null(), &mut output)));
+            output
+        };
+
+        Ok(Arc::new(Surface {
+            instance: instance.clone(),
+            surface: surface,
+            api: SurfaceApi::DirectFB,
+        }))
+    }
+
+    /// Creates a `Surface` from a QNX Screen window.
+    ///
+    /// # Safety
+    ///
+    /// The caller must ensure that the `context` and the `window` are both correct and stay
+    /// alive for the entire lifetime of the surface.
+    pub unsafe fn from_qnx_screen<C, W>(instance: &Arc<Instance>, context: *const C,
+                                        window: *const W)
+                                        -> Result<Arc<Surface>, SurfaceCreationError>
+    {
+        let vk = instance.pointers();
+
+        if !instance.loaded_extensions().qnx_screen_surface {
+            return Err(SurfaceCreationError::MissingExtension { name: "VK_QNX_screen_surface" });
+        }
+
+        let surface = {
+            let infos = vk::ScreenSurfaceCreateInfoQNX {
+                sType: vk::STRUCTURE_TYPE_SCREEN_SURFACE_CREATE_INFO_QNX,
+                pNext: ptr::null(),
+                flags: 0,   // reserved
+                context: context as *mut _,
+                window: window as *mut _,
+            };
+
+            let mut output = mem::uninitialized();
+            try!(check_errors(vk.CreateScreenSurfaceQNX(instance.internal_object(), &infos,
+                                                        ptr::null(), &mut output)));
+            output
+        };
+
+        Ok(Arc::new(Surface {
+            instance: instance.clone(),
+            surface: surface,
+            api: SurfaceApi::Qnx,
+        }))
+    }
+
+    /// Builds a `Surface` from a surface handle that was created outside of vulkano, for
+    /// example with another Vulkan library or by a windowing library that only provides raw
+    /// Vulkan handles.
+    ///
+    /// The `Surface` takes ownership of the handle: it is destroyed with `vkDestroySurfaceKHR`
+    /// when the `Surface` is dropped. The `api()` of the surface is `SurfaceApi::Raw`.
+    ///
+    /// # Safety
+    ///
+    /// - `handle` must be a valid surface that was created from `instance`.
+    /// - The handle must not be destroyed by anything else than this `Surface`, and the window
+    ///   or display that it was created from must stay alive for the entire lifetime of the
+    ///   surface.
+    /// - The surface must not already be used by another swapchain.
+    ///
+    #[inline]
+    pub unsafe fn from_raw(instance: &Arc<Instance>, handle: vk::SurfaceKHR) -> Arc<Surface> {
+        Arc::new(Surface {
+            instance: instance.clone(),
+            surface: handle,
+            api: SurfaceApi::Raw,
+        })
+    }
+
+    /// Returns the instance this surface was created with.
+    ///
+    /// This can be used to check which instance extensions are enabled before using a function
+    /// that requires one of them.
+    #[inline]
+    pub fn instance(&self) -> &Arc<Instance> {
+        &self.instance
+    }
+
+    /// Returns the kind of window system or display that this surface was created from.
+    #[inline]
+    pub fn api(&self) -> SurfaceApi {
+        self.api
+    }
+
     /// Returns true if the given queue family can draw on this surface.
     pub fn is_supported(&self, queue: &QueueFamily) -> Result<bool, OomError> {
         unsafe {
@@ -332,11 +501,20 @@ impl Surface {
 
     /// Retreives the capabilities of a surface when used by a certain device.
     ///
+    /// Returns `CapabilitiesError::SurfaceLost` if the surface is no longer valid, in which case
+    /// the surface must be recreated.
+    ///
+    /// The capabilities can change during the lifetime of the surface, and must be queried again
+    /// after `acquire_next_image` or `present` has returned `OutOfDate`. See also
+    /// `present_modes_changed_since`.
+    ///
     /// # Panic
     ///
     /// - Panicks if the device and the surface don't belong to the same instance.
     ///
-    pub fn get_capabilities(&self, device: &PhysicalDevice) -> Result<Capabilities, OomError> { // TODO: wrong error type
+    pub fn get_capabilities(&self, device: &PhysicalDevice)
+                            -> Result<Capabilities, CapabilitiesError>
+    {
         unsafe {
             assert_eq!(&*self.instance as *const _, &**device.instance() as *const _);
 
@@ -369,29 +547,31 @@ impl Surface {
                 formats
             };
 
-            let modes = {
-                let mut num = 0;
-                try!(check_errors(
-                    vk.GetPhysicalDeviceSurfacePresentModesKHR(device.internal_object(),
-                                                               self.surface, &mut num,
-                                                               ptr::null_mut())
-                ));
-
-                let mut modes = Vec::with_capacity(num as usize);
-                try!(check_errors(
-                    vk.GetPhysicalDeviceSurfacePresentModesKHR(device.internal_object(),
-                                                               self.surface, &mut num,
-                                                               modes.as_mut_ptr())
-                ));
-                modes.set_len(num as usize);
-                debug_assert!(modes.iter().find(|&&m| m == vk::PRESENT_MODE_FIFO_KHR).is_some());
-                SupportedPresentModes::from_list(modes.into_iter())
+            let modes = try!(self.present_modes(device));
+
+            let max_image_count = if caps.maxImageCount == 0 { None }
+                                  else { Some(caps.maxImageCount) };
+
+            let present_mode_min_image_counts = {
+                let extensions = self.instance.loaded_extensions();
+                let mut list = Vec::new();
+                for mode in modes.iter() {
+                    let min = if extensions.khr_get_surface_capabilities2 &&
+                                 extensions.ext_surface_maintenance1
+                    {
+                        try!(self.present_mode_capabilities(device, mode)).0.minImageCount
+                    } else {
+                        caps.minImageCount
+                    };
+                    list.push((mode, min));
+                }
+                list
             };
 
             Ok(Capabilities {
                 min_image_count: caps.minImageCount,
-                max_image_count: if caps.maxImageCount == 0 { None }
-                                 else { Some(caps.maxImageCount) },
+                max_image_count: max_image_count,
+                present_mode_min_image_counts: present_mode_min_image_counts,
                 current_extent: if caps.currentExtent.width == 0xffffffff &&
                                    caps.currentExtent.height == 0xffffffff
                 {
@@ -410,13 +590,363 @@ impl Surface {
                     debug_assert!(usage.color_attachment);  // specs say that this must be true
                     usage
                 },
-                supported_formats: formats.into_iter().map(|f| {
-                    (Format::from_num(f.format).unwrap(), ColorSpace::from_num(f.colorSpace))
+                // Color spaces that we don't know about are skipped.
+                supported_formats: formats.into_iter().filter_map(|f| {
+                    ColorSpace::from_vk(f.colorSpace).map(|c| {
+                        (Format::from_num(f.format).unwrap(), c)
+                    })
                 }).collect(),
                 present_modes: modes,
             })
         }
     }
+
+    /// Returns the scaling behaviors that can be passed in `SwapchainOptions` when creating a
+    /// swapchain with the given present mode.
+    ///
+    /// The `VK_KHR_get_surface_capabilities2` and `VK_EXT_surface_maintenance1` instance
+    /// extensions must be enabled, otherwise `MissingExtension` is returned.
+    ///
+    /// # Panic
+    ///
+    /// - Panicks if the device and the surface don't belong to the same instance.
+    ///
+    #[inline]
+    pub fn supported_present_scaling(&self, device: &PhysicalDevice, mode: PresentMode)
+                                     -> Result<SupportedScalingBehaviors, CapabilitiesError>
+    {
+        let (_, caps) = try!(self.present_mode_capabilities(device, mode));
+        Ok(SupportedScalingBehaviors::from_bits(caps.supportedPresentScaling))
+    }
+
+    /// Returns the gravities, respectively on the X and Y axis, that can be passed in
+    /// `SwapchainOptions` when creating a swapchain with the given present mode.
+    ///
+    /// The `VK_KHR_get_surface_capabilities2` and `VK_EXT_surface_maintenance1` instance
+    /// extensions must be enabled, otherwise `MissingExtension` is returned.
+    ///
+    /// # Panic
+    ///
+    /// - Panicks if the device and the surface don't belong to the same instance.
+    ///
+    #[inline]
+    pub fn supported_present_gravity(&self, device: &PhysicalDevice, mode: PresentMode)
+                                     -> Result<[SupportedPresentGravity; 2], CapabilitiesError>
+    {
+        let (_, caps) = try!(self.present_mode_capabilities(device, mode));
+        Ok([SupportedPresentGravity::from_bits(caps.supportedPresentGravityX),
+            SupportedPresentGravity::from_bits(caps.supportedPresentGravityY)])
+    }
+
+    /// Returns true if protected swapchains can be created for this surface.
+    ///
+    /// Protected swapchains have images whose content can't be read back by the host, which is
+    /// required by some platforms to display DRM-protected content.
+    ///
+    /// The `VK_KHR_get_surface_capabilities2` and `VK_KHR_surface_protected_capabilities`
+    /// instance extensions must be enabled.
+    ///
+    /// # Panic
+    ///
+    /// - Panicks if the device and the surface don't belong to the same instance.
+    ///
+    pub fn supports_protected(&self, device: &PhysicalDevice) -> Result<bool, CapabilitiesError> {
+        if !self.instance.loaded_extensions().khr_surface_protected_capabilities {
+            return Err(CapabilitiesError::MissingExtension {
+                name: "VK_KHR_surface_protected_capabilities"
+            });
+        }
+
+        unsafe {
+            let mut protected = vk::SurfaceProtectedCapabilitiesKHR {
+                sType: vk::STRUCTURE_TYPE_SURFACE_PROTECTED_CAPABILITIES_KHR,
+                pNext: ptr::null_mut(),
+                supportsProtected: vk::FALSE,
+            };
+
+            try!(self.get_capabilities2(device, ptr::null(),
+                                        &mut protected as *mut _ as *mut _));
+
+            Ok(protected.supportsProtected != vk::FALSE)
+        }
+    }
+
+    /// Returns the rectangles of the surface, as `([x, y], [width, height])`, that the physical
+    /// device presents to when it is part of a device group. The offsets can be negative.
+    ///
+    /// This is needed to split the rendering of a frame between the physical devices of a
+    /// device group. The union of the rectangles of all the physical devices covers the
+    /// surface.
+    ///
+    /// The `VK_KHR_swapchain` device extension must be enabled on `device`. If both the instance
+    /// and the physical device use Vulkan 1.1 or above, this function is part of the core API.
+    /// Otherwise the `VK_KHR_device_group` device extension must be enabled as well.
+    ///
+    /// # Panic
+    ///
+    /// - Panicks if the device and the surface don't belong to the same instance.
+    ///
+    pub fn present_rectangles(&self, device: &Device)
+                              -> Result<Vec<([i32; 2], [u32; 2])>, CapabilitiesError>
+    {
+        assert_eq!(&*self.instance as *const _, &**device.instance() as *const _);
+
+        if !device.loaded_extensions().khr_swapchain {
+            return Err(CapabilitiesError::MissingExtension { name: "VK_KHR_swapchain" });
+        }
+
+        let physical_device = device.physical_device();
+        let v1_1 = Version { major: 1, minor: 1, patch: 0 };
+        let is_core = self.instance.api_version() >= v1_1 && physical_device.api_version() >= v1_1;
+        if !is_core && !device.loaded_extensions().khr_device_group {
+            return Err(CapabilitiesError::MissingExtension { name: "VK_KHR_device_group" });
+        }
+
+        unsafe {
+            let vk = self.instance.pointers();
+
+            let mut num = 0;
+            try!(check_errors(
+                vk.GetPhysicalDevicePresentRectanglesKHR(physical_device.internal_object(),
+                                                         self.surface, &mut num, ptr::null_mut())
+            ));
+
+            let mut rects: Vec<vk::Rect2D> = Vec::with_capacity(num as usize);
+            try!(check_errors(
+                vk.GetPhysicalDevicePresentRectanglesKHR(physical_device.internal_object(),
+                                                         self.surface, &mut num,
+                                                         rects.as_mut_ptr())
+            ));
+            rects.set_len(num as usize);
+
+            Ok(rects.into_iter().map(|r| {
+                ([r.offset.x, r.offset.y], [r.extent.width, r.extent.height])
+            }).collect())
+        }
+    }
+
+    /// Returns true if the present modes supported by the surface are no longer the ones in
+    /// `previous`, which is usually the `present_modes` of capabilities queried earlier.
+    ///
+    /// The present modes can change during the lifetime of a surface, for example after the
+    /// window has been moved to another monitor or after the compositor has restarted. This
+    /// usually coincides with `acquire_next_image` or `present` returning `OutOfDate`.
+    /// Swapchain creation and recreation always query the capabilities again and return
+    /// `UnsupportedPresentMode` if the requested mode is no longer supported, but this function
+    /// lets you pick another present mode before that happens.
+    ///
+    /// # Panic
+    ///
+    /// - Panicks if the device and the surface don't belong to the same instance.
+    ///
+    pub fn present_modes_changed_since(&self, previous: &SupportedPresentModes,
+                                       device: &PhysicalDevice)
+                                       -> Result<bool, CapabilitiesError>
+    {
+        assert_eq!(&*self.instance as *const _, &**device.instance() as *const _);
+        let modes = try!(self.present_modes(device));
+        Ok(modes != *previous)
+    }
+
+    // Queries the present modes supported by the surface.
+    fn present_modes(&self, device: &PhysicalDevice)
+                     -> Result<SupportedPresentModes, CapabilitiesError>
+    {
+        unsafe {
+            let vk = self.instance.pointers();
+
+            let mut num = 0;
+            try!(check_errors(
+                vk.GetPhysicalDeviceSurfacePresentModesKHR(device.internal_object(),
+                                                           self.surface, &mut num,
+                                                           ptr::null_mut())
+            ));
+
+            let mut modes = Vec::with_capacity(num as usize);
+            try!(check_errors(
+                vk.GetPhysicalDeviceSurfacePresentModesKHR(device.internal_object(),
+                                                           self.surface, &mut num,
+                                                           modes.as_mut_ptr())
+            ));
+            modes.set_len(num as usize);
+            Ok(SupportedPresentModes::from_list(modes.into_iter()))
+        }
+    }
+
+    /// Calls `vkGetPhysicalDeviceSurfaceCapabilities2KHR` and returns the core capabilities of
+    /// the surface.
+    ///
+    /// `info_next` is the `pNext` chain of the `VkPhysicalDeviceSurfaceInfo2KHR` passed to the
+    /// function, for example a `VkSurfacePresentModeEXT`, and `caps_next` is the `pNext` chain of
+    /// the `VkSurfaceCapabilities2KHR` that receives the result. The structs of `caps_next` are
+    /// filled by the implementation and must be read by the caller.
+    ///
+    /// Returns `MissingExtension` if the `VK_KHR_get_surface_capabilities2` instance extension
+    /// isn't enabled. The caller must check the extensions required by the chained structs.
+    ///
+    /// # Panic
+    ///
+    /// - Panicks if the device and the surface don't belong to the same instance.
+    ///
+    /// # Safety
+    ///
+    /// - Both chains must be null or valid chains of structs allowed by the specs in these
+    ///   positions.
+    ///
+    #[doc(hidden)]
+    pub unsafe fn get_capabilities2(&self, device: &PhysicalDevice, info_next: *const c_void,
+                                    caps_next: *mut c_void)
+                                    -> Result<vk::SurfaceCapabilitiesKHR, CapabilitiesError>
+    {
+        assert_eq!(&*self.instance as *const _, &**device.instance() as *const _);
+
+        if !self.instance.loaded_extensions().khr_get_surface_capabilities2 {
+            return Err(CapabilitiesError::MissingExtension {
+                name: "VK_KHR_get_surface_capabilities2"
+            });
+        }
+
+        let vk = self.instance.pointers();
+
+        let infos = vk::PhysicalDeviceSurfaceInfo2KHR {
+            sType: vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_SURFACE_INFO_2_KHR,
+            pNext: info_next,
+            surface: self.surface,
+        };
+
+        let mut output = vk::SurfaceCapabilities2KHR {
+            sType: vk::STRUCTURE_TYPE_SURFACE_CAPABILITIES_2_KHR,
+            pNext: caps_next,
+            surfaceCapabilities: mem::uninitialized(),
+        };
+
+        try!(check_errors(
+            vk.GetPhysicalDeviceSurfaceCapabilities2KHR(device.internal_object(), &infos,
+                                                        &mut output)
+        ));
+
+        Ok(output.surfaceCapabilities)
+    }
+
+    /// Returns the list of formats and color spaces that a swapchain for this surface can use,
+    /// with `vkGetPhysicalDeviceSurfaceFormats2KHR`.
+    ///
+    /// Contrary to the `supported_formats` of `get_capabilities`, this lets you query additional
+    /// information for each format through extension structs, for example a
+    /// `VkImageCompressionPropertiesEXT` to know whether the format supports fixed-rate
+    /// compression.
+    ///
+    /// `info_next` is the `pNext` chain of the `VkPhysicalDeviceSurfaceInfo2KHR` passed to the
+    /// function. `format_next` is called once for each format reported by the implementation,
+    /// with the index of the format, and returns the `pNext` chain of the `VkSurfaceFormat2KHR`
+    /// that receives it, or null. The structs of these chains are filled by the implementation
+    /// and must be read by the caller once this function returns.
+    ///
+    /// Formats whose color space isn't known by vulkano are skipped. Use the `index` field of
+    /// `SurfaceFormat2` to find the chain that was returned by `format_next` for a format.
+    ///
+    /// Returns `MissingExtension` if the `VK_KHR_get_surface_capabilities2` instance extension
+    /// isn't enabled. The caller must check the extensions required by the chained structs.
+    ///
+    /// # Panic
+    ///
+    /// - Panicks if the device and the surface don't belong to the same instance.
+    ///
+    /// # Safety
+    ///
+    /// - `info_next` and the chains returned by `format_next` must be null or valid chains of
+    ///   structs allowed by the specs in these positions, and each chain must be returned only
+    ///   once.
+    ///
+    pub unsafe fn get_formats2<F>(&self, device: &PhysicalDevice, info_next: *const c_void,
+                                  mut format_next: F)
+                                  -> Result<Vec<SurfaceFormat2>, CapabilitiesError>
+        where F: FnMut(usize) -> *mut c_void
+    {
+        assert_eq!(&*self.instance as *const _, &**device.instance() as *const _);
+
+        if !self.instance.loaded_extensions().khr_get_surface_capabilities2 {
+            return Err(CapabilitiesError::MissingExtension {
+                name: "VK_KHR_get_surface_capabilities2"
+            });
+        }
+
+        let vk = self.instance.pointers();
+
+        let infos = vk::PhysicalDeviceSurfaceInfo2KHR {
+            sType: vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_SURFACE_INFO_2_KHR,
+            pNext: info_next,
+            surface: self.surface,
+        };
+
+        let mut num = 0;
+        try!(check_errors(
+            vk.GetPhysicalDeviceSurfaceFormats2KHR(device.internal_object(), &infos, &mut num,
+                                                   ptr::null_mut())
+        ));
+
+        let mut formats = (0 .. num as usize).map(|index| {
+            vk::SurfaceFormat2KHR {
+                sType: vk::STRUCTURE_TYPE_SURFACE_FORMAT_2_KHR,
+                pNext: format_next(index),
+                surfaceFormat: mem::uninitialized(),
+            }
+        }).collect::<Vec<_>>();
+
+        try!(check_errors(
+            vk.GetPhysicalDeviceSurfaceFormats2KHR(device.internal_object(), &infos, &mut num,
+                                                   formats.as_mut_ptr())
+        ));
+        formats.truncate(num as usize);
+
+        Ok(formats.into_iter().enumerate().filter_map(|(index, f)| {
+            ColorSpace::from_vk(f.surfaceFormat.colorSpace).map(|c| {
+                SurfaceFormat2 {
+                    format: Format::from_num(f.surfaceFormat.format).unwrap(),
+                    color_space: c,
+                    index: index,
+                }
+            })
+        }).collect())
+    }
+
+    // Queries the capabilities and the present scaling capabilities of the surface for a
+    // present mode.
+    fn present_mode_capabilities(&self, device: &PhysicalDevice, mode: PresentMode)
+                                 -> Result<(vk::SurfaceCapabilitiesKHR,
+                                            vk::SurfacePresentScalingCapabilitiesEXT),
+                                           CapabilitiesError>
+    {
+        if !self.instance.loaded_extensions().ext_surface_maintenance1 {
+            return Err(CapabilitiesError::MissingExtension {
+                name: "VK_EXT_surface_maintenance1"
+            });
+        }
+
+        unsafe {
+            let present_mode = vk::SurfacePresentModeEXT {
+                sType: vk::STRUCTURE_TYPE_SURFACE_PRESENT_MODE_EXT,
+                pNext: ptr::null_mut(),
+                presentMode: mode as u32,
+            };
+
+            let mut scaling = vk::SurfacePresentScalingCapabilitiesEXT {
+                sType: vk::STRUCTURE_TYPE_SURFACE_PRESENT_SCALING_CAPABILITIES_EXT,
+                pNext: ptr::null_mut(),
+                supportedPresentScaling: 0,
+                supportedPresentGravityX: 0,
+                supportedPresentGravityY: 0,
+                minScaledImageExtent: vk::Extent2D { width: 0, height: 0 },
+                maxScaledImageExtent: vk::Extent2D { width: 0, height: 0 },
+            };
+
+            let caps = try!(self.get_capabilities2(device,
+                                                   &present_mode as *const _ as *const _,
+                                                   &mut scaling as *mut _ as *mut _));
+
+            Ok((caps, scaling))
+        }
+    }
 }
 
 unsafe impl VulkanObject for Surface {
@@ -438,14 +968,89 @@ impl Drop for Surface {
     }
 }
 
-/// Error that can happen when creating a debug callback.
+/// Returns the physical devices of `instance` that have at least one queue family that can
+/// present to `surface`, in the order of `PhysicalDevice::enumerate`.
+///
+/// This is typically used to choose a physical device before creating a device and a swapchain.
+/// Use `Surface::is_supported` to find which queue families of the chosen device can present.
+///
+/// # Panic
+///
+/// - Panicks if the surface doesn't belong to `instance`.
+///
+pub fn present_capable_devices<'a>(instance: &'a Arc<Instance>, surface: &Surface)
+                                   -> Result<Vec<PhysicalDevice<'a>>, OomError>
+{
+    assert_eq!(&**instance as *const _, &*surface.instance as *const _);
+
+    let mut devices = Vec::new();
+    for device in PhysicalDevice::enumerate(instance) {
+        for family in device.queue_families() {
+            if try!(surface.is_supported(&family)) {
+                devices.push(device);
+                break;
+            }
+        }
+    }
+
+    Ok(devices)
+}
+
+/// The windowing system or display API that a surface was created from.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum SurfaceApi {
+    /// Created with `from_display_mode`.
+    DisplayPlane,
+    /// Created with `from_hwnd`.
+    Win32,
+    /// Created with `from_xcb`.
+    Xcb,
+    /// Created with `from_xlib`.
+    Xlib,
+    /// Created with `from_wayland`.
+    Wayland,
+    /// Created with `from_mir`.
+    Mir,
+    /// Created with `from_anativewindow`.
+    Android,
+    /// Created with `from_metal_layer`.
+    Metal,
+    /// Created with `from_directfb`.
+    DirectFB,
+    /// Created with `from_qnx_screen`.
+    Qnx,
+    /// Created with `from_raw`. The window system or display isn't known by vulkano.
+    Raw,
+}
+
+/// A format and color space returned by `Surface::get_formats2`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SurfaceFormat2 {
+    /// The format of the images of the swapchain.
+    pub format: Format,
+    /// The color space of the images of the swapchain.
+    pub color_space: ColorSpace,
+    /// Position of the format in the list returned by the implementation. This is the index that
+    /// was passed to the `format_next` closure.
+    pub index: usize,
+}
+
+/// Error that can happen when creating a surface.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum SurfaceCreationError {
     /// Not enough memory.
     OomError(OomError),
 
-    /// The extension required for this function was not enabled.
-    MissingExtension { name: &'static str },
+    /// The instance extension required for this function was not enabled. This is checked
+    /// before calling into the Vulkan implementation.
+    MissingExtension {
+        /// Name of the extension.
+        name: &'static str
+    },
+
+    /// The native window is already in use by another surface or by another API, and can't be
+    /// used to create a surface.
+    NativeWindowInUse,
 }
 
 impl error::Error for SurfaceCreationError {
@@ -455,6 +1060,7 @@ impl error::Error for SurfaceCreationError {
             SurfaceCreationError::OomError(_) => "not enough memory available",
             SurfaceCreationError::MissingExtension { .. } => "the extension required for this \
                                                               function was not enabled",
+            SurfaceCreationError::NativeWindowInUse => "the native window is already in use",
         }
     }
 
@@ -470,7 +1076,12 @@ impl error::Error for SurfaceCreationError {
 impl fmt::Display for SurfaceCreationError {
     #[inline]
     fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        write!(fmt, "{}", error::Error::description(self))
+        match *self {
+            SurfaceCreationError::MissingExtension { name } => {
+                write!(fmt, "the `{}` extension was not enabled", name)
+            },
+            _ => write!(fmt, "{}", error::Error::description(self)),
+        }
     }
 }
 
@@ -487,6 +1098,75 @@ impl From<Error> for SurfaceCreationError {
         match err {
             err @ Error::OutOfHostMemory => SurfaceCreationError::OomError(OomError::from(err)),
             err @ Error::OutOfDeviceMemory => SurfaceCreationError::OomError(OomError::from(err)),
+            Error::NativeWindowInUse => SurfaceCreationError::NativeWindowInUse,
+            _ => panic!("unexpected error: {:?}", err)
+        }
+    }
+}
+
+/// Error that can happen when querying the capabilities of a surface.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CapabilitiesError {
+    /// Not enough memory.
+    OomError(OomError),
+
+    /// The instance extension required for this function was not enabled. This is checked
+    /// before calling into the Vulkan implementation.
+    MissingExtension {
+        /// Name of the extension.
+        name: &'static str
+    },
+
+    /// The surface is no longer accessible and must be recreated.
+    SurfaceLost,
+}
+
+impl error::Error for CapabilitiesError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            CapabilitiesError::OomError(_) => "not enough memory available",
+            CapabilitiesError::MissingExtension { .. } => "the extension required for this \
+                                                           function was not enabled",
+            CapabilitiesError::SurfaceLost => "the surface is no longer valid",
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            CapabilitiesError::OomError(ref err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for CapabilitiesError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        match *self {
+            CapabilitiesError::MissingExtension { name } => {
+                write!(fmt, "the `{}` extension was not enabled", name)
+            },
+            _ => write!(fmt, "{}", error::Error::description(self)),
+        }
+    }
+}
+
+impl From<OomError> for CapabilitiesError {
+    #[inline]
+    fn from(err: OomError) -> CapabilitiesError {
+        CapabilitiesError::OomError(err)
+    }
+}
+
+impl From<Error> for CapabilitiesError {
+    #[inline]
+    fn from(err: Error) -> CapabilitiesError {
+        match err {
+            err @ Error::OutOfHostMemory => CapabilitiesError::OomError(OomError::from(err)),
+            err @ Error::OutOfDeviceMemory => CapabilitiesError::OomError(OomError::from(err)),
+            Error::SurfaceLost => CapabilitiesError::SurfaceLost,
             _ => panic!("unexpected error: {:?}", err)
         }
     }
@@ -505,6 +1185,17 @@ pub struct Capabilities {
     /// you may still get out of memory errors.
     pub max_image_count: Option<u32>,
 
+    /// Minimum number of images for each of the supported present modes. Always greater than or
+    /// equal to `min_image_count`.
+    ///
+    /// If the `VK_KHR_get_surface_capabilities2` and `VK_EXT_surface_maintenance1` instance
+    /// extensions are enabled, the values are reported by the implementation. Otherwise they
+    /// are all equal to `min_image_count`.
+    ///
+    /// Note that `Mailbox` usually needs at least three images to avoid blocking when acquiring
+    /// an image. `ImageCount::Preferred` takes this into account.
+    pub present_mode_min_image_counts: Vec<(PresentMode, u32)>,
+
     /// The current dimensions of the surface. `None` means that the surface's dimensions will
     /// depend on the dimensions of the swapchain that you are going to create.
     pub current_extent: Option<[u32; 2]>,
@@ -538,6 +1229,175 @@ pub struct Capabilities {
     pub present_modes: SupportedPresentModes,
 }
 
+impl Capabilities {
+    /// Returns the minimum number of images of a swapchain that uses the given present mode.
+    ///
+    /// See `present_mode_min_image_counts`. Returns `min_image_count` if the present mode isn't
+    /// in the list.
+    #[inline]
+    pub fn min_image_count_for_present_mode(&self, mode: PresentMode) -> u32 {
+        self.present_mode_min_image_counts.iter().find(|&&(m, _)| m == mode)
+            .map(|&(_, min)| min).unwrap_or(self.min_image_count)
+    }
+
+    /// Returns the maximum number of images of a swapchain that you can hold acquired at the
+    /// same time without risking a deadlock.
+    ///
+    /// `num_images` is the number of images of the swapchain, as returned by
+    /// `Swapchain::num_images()`.
+    ///
+    /// The presentation engine is allowed to hold up to `min_image_count - 1` images. Therefore
+    /// once you hold more than `num_images - min_image_count` acquired images, the next call to
+    /// `acquire_next_image` may never succeed and must use a finite timeout. This function
+    /// returns this limit plus one, which is the number of images that you can safely acquire
+    /// before presenting one of them. The result is always at least 1.
+    ///
+    /// Note that the minimum number of images can depend on the present mode. If you use
+    /// `VK_EXT_surface_maintenance1`, make sure that these capabilities have been queried for
+    /// the present mode of the swapchain.
+    #[inline]
+    pub fn max_acquired_images(&self, num_images: u32) -> u32 {
+        num_images.saturating_sub(self.min_image_count) + 1
+    }
+
+    /// Returns a supported 8-bits sRGB format with the `SrgbNonLinear` color space, if any.
+    ///
+    /// The formats are tried in this order: `B8G8R8A8Srgb`, `R8G8B8A8Srgb` and
+    /// `A8B8G8R8SrgbPack32`.
+    ///
+    /// With an sRGB format, the values written by shaders are considered linear and are
+    /// automatically converted to sRGB when they are stored in the image. If you instead use a
+    /// `Unorm` format while your shaders output linear colors, the screen interprets these
+    /// linear values as sRGB and the image looks washed out. Only use a `Unorm` format if you
+    /// perform the conversion yourself.
+    pub fn srgb_format(&self) -> Option<(Format, ColorSpace)> {
+        let preferred = [Format::B8G8R8A8Srgb, Format::R8G8B8A8Srgb, Format::A8B8G8R8SrgbPack32];
+
+        preferred.iter().cloned().find(|&format| {
+            self.supported_formats.contains(&(format, ColorSpace::SrgbNonLinear))
+        }).map(|format| (format, ColorSpace::SrgbNonLinear))
+    }
+
+    /// Returns the first pair of format and color space of `supported_formats` for which
+    /// `predicate` returns true, or `None` if there is none.
+    ///
+    /// The pairs are tested in the order in which the implementation reported them.
+    #[inline]
+    pub fn find_format<F>(&self, predicate: F) -> Option<(Format, ColorSpace)>
+        where F: Fn(Format, ColorSpace) -> bool
+    {
+        self.supported_formats.iter().cloned().find(|&(format, color_space)| {
+            predicate(format, color_space)
+        })
+    }
+
+    /// Returns the range of numbers of images that a swapchain for this surface can have.
+    #[inline]
+    pub fn image_count_range(&self) -> ImageCountRange {
+        ImageCountRange {
+            min: self.min_image_count,
+            max: self.max_image_count,
+        }
+    }
+}
+
+/// Range of numbers of images that a swapchain can have. See `Capabilities::image_count_range`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ImageCountRange {
+    /// Minimum number of images.
+    pub min: u32,
+    /// Maximum number of images, or `None` if there is no maximum.
+    pub max: Option<u32>,
+}
+
+impl ImageCountRange {
+    /// Returns true if a swapchain can have `num` images.
+    #[inline]
+    pub fn contains(&self, num: u32) -> bool {
+        num >= self.min && self.max.map(|max| num <= max).unwrap_or(true)
+    }
+
+    /// Returns the number of images closest to `num` that is in the range.
+    #[inline]
+    pub fn clamp(&self, num: u32) -> u32 {
+        let num = cmp::max(num, self.min);
+        match self.max {
+            Some(max) => cmp::min(num, max),
+            None => num,
+        }
+    }
+}
+
+impl fmt::Display for Capabilities {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        // Writes the elements of a list separated with commas, or `none` if the list is empty.
+        fn write_list<I, T>(fmt: &mut fmt::Formatter, list: I) -> Result<(), fmt::Error>
+            where I: Iterator<Item = T>, T: fmt::Debug
+        {
+            let mut empty = true;
+            for (num, elem) in list.enumerate() {
+                if num != 0 { try!(write!(fmt, ", ")); }
+                try!(write!(fmt, "{:?}", elem));
+                empty = false;
+            }
+            if empty { try!(write!(fmt, "none")); }
+            writeln!(fmt, "")
+        }
+
+        try!(writeln!(fmt, "min image count: {}", self.min_image_count));
+        match self.max_image_count {
+            Some(max) => try!(writeln!(fmt, "max image count: {}", max)),
+            None => try!(writeln!(fmt, "max image count: unlimited")),
+        }
+        try!(write!(fmt, "min image count per present mode: "));
+        try!(write_list(fmt, self.present_mode_min_image_counts.iter()));
+        match self.current_extent {
+            Some(e) => try!(writeln!(fmt, "current extent: {}x{}", e[0], e[1])),
+            None => try!(writeln!(fmt, "current extent: determined by the swapchain")),
+        }
+        try!(writeln!(fmt, "min image extent: {}x{}", self.min_image_extent[0],
+                      self.min_image_extent[1]));
+        try!(writeln!(fmt, "max image extent: {}x{}", self.max_image_extent[0],
+                      self.max_image_extent[1]));
+        try!(writeln!(fmt, "max image array layers: {}", self.max_image_array_layers));
+
+        try!(write!(fmt, "supported transforms: "));
+        try!(write_list(fmt, self.supported_transforms.iter()));
+        try!(writeln!(fmt, "current transform: {:?}", self.current_transform));
+        try!(write!(fmt, "supported composite alpha: "));
+        try!(write_list(fmt, self.supported_composite_alpha.iter()));
+
+        try!(write!(fmt, "supported usage flags: "));
+        let usage = &self.supported_usage_flags;
+        let usage_list = [
+            ("transfer_source", usage.transfer_source),
+            ("transfer_dest", usage.transfer_dest),
+            ("sampled", usage.sampled),
+            ("storage", usage.storage),
+            ("color_attachment", usage.color_attachment),
+            ("depth_stencil_attachment", usage.depth_stencil_attachment),
+            ("transient_attachment", usage.transient_attachment),
+            ("input_attachment", usage.input_attachment),
+        ];
+        try!(write_list(fmt, usage_list.iter().filter(|&&(_, s)| s).map(|&(n, _)| Name(n))));
+
+        try!(write!(fmt, "supported formats: "));
+        try!(write_list(fmt, self.supported_formats.iter()));
+        try!(write!(fmt, "present modes: "));
+        write_list(fmt, self.present_modes.iter())
+    }
+}
+
+// Wrapper whose `Debug` implementation writes the string without quotes.
+struct Name(&'static str);
+
+impl fmt::Debug for Name {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", self.0)
+    }
+}
+
 /// The way presenting a swapchain is accomplished.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[repr(u32)]
@@ -548,6 +1408,9 @@ pub enum PresentMode {
     /// The action of presenting an image puts it in wait. When the next vertical blanking period
     /// happens, the waiting image is effectively shown to the user. If an image is presented while
     /// another one is waiting, it is replaced.
+    ///
+    /// Contrary to `Immediate`, this never results in tearing. Contrary to `Fifo`, presenting
+    /// never blocks, but some of the presented images may never be shown.
     Mailbox = vk::PRESENT_MODE_MAILBOX_KHR,
 
     /// The action of presenting an image adds it to a queue of images. At each vertical blanking
@@ -559,12 +1422,39 @@ pub enum PresentMode {
     Fifo = vk::PRESENT_MODE_FIFO_KHR,
 
     /// Same as `Fifo`, except that if the queue was empty during the previous vertical blanking
-    /// period then it is equivalent to `Immediate`.
+    /// period then it is equivalent to `Immediate`. In other words, it may result in tearing but
+    /// only when the application is too slow to present an image every vertical blanking period.
+    ///
+    /// This is the `VK_PRESENT_MODE_FIFO_RELAXED_KHR` mode.
     ///
     /// This is the equivalent of OpenGL's `SwapInterval` with a value of -1.
     Relaxed = vk::PRESENT_MODE_FIFO_RELAXED_KHR,
 }
 
+impl PresentMode {
+    /// Returns the present modes to try, in order, when the lowest latency matters more than
+    /// tearing: `Mailbox`, `Immediate`, then `Fifo`.
+    ///
+    /// Meant to be passed to `SupportedPresentModes::best_match`.
+    #[inline]
+    pub fn low_latency_preference() -> &'static [PresentMode] {
+        static PREFERENCE: [PresentMode; 3] = [PresentMode::Mailbox, PresentMode::Immediate,
+                                               PresentMode::Fifo];
+        &PREFERENCE
+    }
+
+    /// Returns the present modes to try, in order, when the presentation should be synchronized
+    /// with the vertical blanking period: `Relaxed`, `Fifo`, then `Mailbox`.
+    ///
+    /// Meant to be passed to `SupportedPresentModes::best_match`.
+    #[inline]
+    pub fn vsync_preference() -> &'static [PresentMode] {
+        static PREFERENCE: [PresentMode; 3] = [PresentMode::Relaxed, PresentMode::Fifo,
+                                               PresentMode::Mailbox];
+        &PREFERENCE
+    }
+}
+
 /// List of `PresentMode`s that are supported.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct SupportedPresentModes {
@@ -612,6 +1502,15 @@ impl SupportedPresentModes {
         }
     }
 
+    /// Returns the first mode of `preference` that is supported.
+    ///
+    /// `Fifo` is always considered supported, as the specs guarantee it. Returns `None` only if
+    /// none of the modes are supported and `Fifo` isn't in the list.
+    #[inline]
+    pub fn best_match(&self, preference: &[PresentMode]) -> Option<PresentMode> {
+        preference.iter().cloned().find(|&mode| mode == PresentMode::Fifo || self.supports(mode))
+    }
+
     /// Returns an iterator to the list of supported present modes.
     #[inline]
     pub fn iter(&self) -> SupportedPresentModesIter {
@@ -636,6 +1535,16 @@ impl Iterator for SupportedPresentModesIter {
     }
 }
 
+/// A counter of a display surface that can be queried from a swapchain.
+///
+/// See `SwapchainOptions::surface_counters` and `Swapchain::counter`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum SurfaceCounter {
+    /// Number of vertical blanking periods that happened on the display.
+    Vblank = vk::SURFACE_COUNTER_VBLANK_BIT_EXT,
+}
+
 /// A transformation to apply to the image before showing it on the screen.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 #[repr(u32)]
@@ -684,8 +1593,9 @@ impl SupportedSurfaceTransforms {
         }
     }
 
+    #[doc(hidden)]
     #[inline]
-    fn from_bits(val: u32) -> SupportedSurfaceTransforms {
+    pub fn from_bits(val: u32) -> SupportedSurfaceTransforms {
         macro_rules! v {
             ($val:expr, $out:ident, $e:expr, $f:ident) => (
                 if ($val & $e) != 0 { $out.$f = true; }
@@ -822,6 +1732,15 @@ impl SupportedCompositeAlpha {
         }
     }
 
+    /// Same as `supports`. Returns true if the given `CompositeAlpha` is in this list.
+    ///
+    /// Use this to check beforehand that a mode can be passed to `Swapchain::new`, which
+    /// returns `SwapchainCreationError::UnsupportedCompositeAlpha` otherwise.
+    #[inline]
+    pub fn contains(&self, value: CompositeAlpha) -> bool {
+        self.supports(value)
+    }
+
     /// Returns an iterator to the list of supported composite alpha.
     #[inline]
     pub fn iter(&self) -> SupportedCompositeAlphaIter {
@@ -846,24 +1765,395 @@ impl Iterator for SupportedCompositeAlphaIter {
     }
 }
 
+/// List of `ScalingBehavior`s that are supported.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SupportedScalingBehaviors {
+    pub one_to_one: bool,
+    pub aspect_ratio_stretch: bool,
+    pub stretch: bool,
+}
+
+impl SupportedScalingBehaviors {
+    /// Builds a `SupportedScalingBehaviors` with all fields set to false.
+    #[inline]
+    pub fn none() -> SupportedScalingBehaviors {
+        SupportedScalingBehaviors {
+            one_to_one: false,
+            aspect_ratio_stretch: false,
+            stretch: false,
+        }
+    }
+
+    #[inline]
+    fn from_bits(val: u32) -> SupportedScalingBehaviors {
+        let mut result = SupportedScalingBehaviors::none();
+        if (val & vk::PRESENT_SCALING_ONE_TO_ONE_BIT_EXT) != 0 { result.one_to_one = true; }
+        if (val & vk::PRESENT_SCALING_ASPECT_RATIO_STRETCH_BIT_EXT) != 0 { result.aspect_ratio_stretch = true; }
+        if (val & vk::PRESENT_SCALING_STRETCH_BIT_EXT) != 0 { result.stretch = true; }
+        result
+    }
+
+    /// Returns true if the given `ScalingBehavior` is in this list.
+    #[inline]
+    pub fn supports(&self, value: ScalingBehavior) -> bool {
+        match value {
+            ScalingBehavior::OneToOne => self.one_to_one,
+            ScalingBehavior::AspectRatioStretch => self.aspect_ratio_stretch,
+            ScalingBehavior::Stretch => self.stretch,
+        }
+    }
+}
+
+/// List of `PresentGravity`s that are supported on an axis.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SupportedPresentGravity {
+    pub min: bool,
+    pub max: bool,
+    pub centered: bool,
+}
+
+impl SupportedPresentGravity {
+    /// Builds a `SupportedPresentGravity` with all fields set to false.
+    #[inline]
+    pub fn none() -> SupportedPresentGravity {
+        SupportedPresentGravity {
+            min: false,
+            max: false,
+            centered: false,
+        }
+    }
+
+    #[inline]
+    fn from_bits(val: u32) -> SupportedPresentGravity {
+        let mut result = SupportedPresentGravity::none();
+        if (val & vk::PRESENT_GRAVITY_MIN_BIT_EXT) != 0 { result.min = true; }
+        if (val & vk::PRESENT_GRAVITY_MAX_BIT_EXT) != 0 { result.max = true; }
+        if (val & vk::PRESENT_GRAVITY_CENTERED_BIT_EXT) != 0 { result.centered = true; }
+        result
+    }
+
+    /// Returns true if the given `PresentGravity` is in this list.
+    #[inline]
+    pub fn supports(&self, value: PresentGravity) -> bool {
+        match value {
+            PresentGravity::Min => self.min,
+            PresentGravity::Max => self.max,
+            PresentGravity::Centered => self.centered,
+        }
+    }
+}
+
+/// How the implementation interprets the content of the swapchain images.
+///
+/// Only `SrgbNonLinear` is available by default. The other color spaces require the
+/// `VK_EXT_swapchain_colorspace` instance extension (or `VK_AMD_display_native_hdr` for
+/// `DisplayNativeAmd`), and are only usable if they appear in the `supported_formats` of the
+/// surface's capabilities.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum ColorSpace {
     SrgbNonLinear,
+    DisplayP3NonLinear,
+    ExtendedSrgbLinear,
+    ExtendedSrgbNonLinear,
+    DisplayP3Linear,
+    DciP3NonLinear,
+    Bt709Linear,
+    Bt709NonLinear,
+    Bt2020Linear,
+    Hdr10St2084,
+    DolbyVision,
+    Hdr10Hlg,
+    AdobeRgbLinear,
+    AdobeRgbNonLinear,
+    PassThrough,
+    DisplayNativeAmd,
 }
 
 impl ColorSpace {
+    /// Builds a `ColorSpace` from a `VkColorSpaceKHR` value. Returns `None` if the value is
+    /// unknown.
+    #[inline]
+    pub fn from_vk(val: vk::ColorSpaceKHR) -> Option<ColorSpace> {
+        Some(match val {
+            vk::COLORSPACE_SRGB_NONLINEAR_KHR => ColorSpace::SrgbNonLinear,
+            vk::COLOR_SPACE_DISPLAY_P3_NONLINEAR_EXT => ColorSpace::DisplayP3NonLinear,
+            vk::COLOR_SPACE_EXTENDED_SRGB_LINEAR_EXT => ColorSpace::ExtendedSrgbLinear,
+            vk::COLOR_SPACE_EXTENDED_SRGB_NONLINEAR_EXT => ColorSpace::ExtendedSrgbNonLinear,
+            vk::COLOR_SPACE_DISPLAY_P3_LINEAR_EXT => ColorSpace::DisplayP3Linear,
+            vk::COLOR_SPACE_DCI_P3_NONLINEAR_EXT => ColorSpace::DciP3NonLinear,
+            vk::COLOR_SPACE_BT709_LINEAR_EXT => ColorSpace::Bt709Linear,
+            vk::COLOR_SPACE_BT709_NONLINEAR_EXT => ColorSpace::Bt709NonLinear,
+            vk::COLOR_SPACE_BT2020_LINEAR_EXT => ColorSpace::Bt2020Linear,
+            vk::COLOR_SPACE_HDR10_ST2084_EXT => ColorSpace::Hdr10St2084,
+            vk::COLOR_SPACE_DOLBYVISION_EXT => ColorSpace::DolbyVision,
+            vk::COLOR_SPACE_HDR10_HLG_EXT => ColorSpace::Hdr10Hlg,
+            vk::COLOR_SPACE_ADOBERGB_LINEAR_EXT => ColorSpace::AdobeRgbLinear,
+            vk::COLOR_SPACE_ADOBERGB_NONLINEAR_EXT => ColorSpace::AdobeRgbNonLinear,
+            vk::COLOR_SPACE_PASS_THROUGH_EXT => ColorSpace::PassThrough,
+            vk::COLOR_SPACE_DISPLAY_NATIVE_AMD => ColorSpace::DisplayNativeAmd,
+            _ => return None
+        })
+    }
+
+    /// Returns the `VkColorSpaceKHR` value that corresponds to this color space.
     #[inline]
-    fn from_num(val: u32) -> ColorSpace {
-        assert_eq!(val, vk::COLORSPACE_SRGB_NONLINEAR_KHR);
-        ColorSpace::SrgbNonLinear
+    pub fn to_vk(&self) -> vk::ColorSpaceKHR {
+        match *self {
+            ColorSpace::SrgbNonLinear => vk::COLORSPACE_SRGB_NONLINEAR_KHR,
+            ColorSpace::DisplayP3NonLinear => vk::COLOR_SPACE_DISPLAY_P3_NONLINEAR_EXT,
+            ColorSpace::ExtendedSrgbLinear => vk::COLOR_SPACE_EXTENDED_SRGB_LINEAR_EXT,
+            ColorSpace::ExtendedSrgbNonLinear => vk::COLOR_SPACE_EXTENDED_SRGB_NONLINEAR_EXT,
+            ColorSpace::DisplayP3Linear => vk::COLOR_SPACE_DISPLAY_P3_LINEAR_EXT,
+            ColorSpace::DciP3NonLinear => vk::COLOR_SPACE_DCI_P3_NONLINEAR_EXT,
+            ColorSpace::Bt709Linear => vk::COLOR_SPACE_BT709_LINEAR_EXT,
+            ColorSpace::Bt709NonLinear => vk::COLOR_SPACE_BT709_NONLINEAR_EXT,
+            ColorSpace::Bt2020Linear => vk::COLOR_SPACE_BT2020_LINEAR_EXT,
+            ColorSpace::Hdr10St2084 => vk::COLOR_SPACE_HDR10_ST2084_EXT,
+            ColorSpace::DolbyVision => vk::COLOR_SPACE_DOLBYVISION_EXT,
+            ColorSpace::Hdr10Hlg => vk::COLOR_SPACE_HDR10_HLG_EXT,
+            ColorSpace::AdobeRgbLinear => vk::COLOR_SPACE_ADOBERGB_LINEAR_EXT,
+            ColorSpace::AdobeRgbNonLinear => vk::COLOR_SPACE_ADOBERGB_NONLINEAR_EXT,
+            ColorSpace::PassThrough => vk::COLOR_SPACE_PASS_THROUGH_EXT,
+            ColorSpace::DisplayNativeAmd => vk::COLOR_SPACE_DISPLAY_NATIVE_AMD,
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
     use std::ptr;
+    use std::sync::Arc;
+    use format::Format;
+    use image::Usage;
+    use instance::Instance;
+    use instance::InstanceExtensions;
+    use instance::PhysicalDevice;
+    use swapchain::Capabilities;
+    use swapchain::CapabilitiesError;
+    use swapchain::ColorSpace;
+    use swapchain::ImageCountRange;
+    use swapchain::PresentGravity;
+    use swapchain::PresentMode;
+    use swapchain::ScalingBehavior;
+    use swapchain::surface::SupportedCompositeAlpha;
+    use swapchain::surface::SupportedPresentGravity;
+    use swapchain::surface::SupportedPresentModes;
+    use swapchain::surface::SupportedScalingBehaviors;
+    use vk;
+    use swapchain::SupportedSurfaceTransforms;
     use swapchain::Surface;
     use swapchain::SurfaceCreationError;
+    use swapchain::SurfaceTransform;
+    use swapchain::display::Display;
+    use swapchain::display::DisplayPlane;
+    use Error;
+
+    #[test]
+    fn present_scaling_from_bits() {
+        let scaling = SupportedScalingBehaviors::from_bits(vk::PRESENT_SCALING_STRETCH_BIT_EXT);
+        assert!(scaling.supports(ScalingBehavior::Stretch));
+        assert!(!scaling.supports(ScalingBehavior::OneToOne));
+
+        let gravity = SupportedPresentGravity::from_bits(vk::PRESENT_GRAVITY_MIN_BIT_EXT |
+                                                         vk::PRESENT_GRAVITY_CENTERED_BIT_EXT);
+        assert!(gravity.supports(PresentGravity::Min));
+        assert!(!gravity.supports(PresentGravity::Max));
+        assert!(gravity.supports(PresentGravity::Centered));
+    }
+
+    fn test_capabilities() -> Capabilities {
+        Capabilities {
+            min_image_count: 2,
+            max_image_count: None,
+            present_mode_min_image_counts: vec![(PresentMode::Fifo, 2)],
+            current_extent: Some([800, 600]),
+            min_image_extent: [1, 1],
+            max_image_extent: [4096, 4096],
+            max_image_array_layers: 1,
+            supported_transforms: SupportedSurfaceTransforms {
+                identity: true, .. SupportedSurfaceTransforms::none()
+            },
+            current_transform: SurfaceTransform::Identity,
+            supported_composite_alpha: SupportedCompositeAlpha {
+                opaque: true, .. SupportedCompositeAlpha::none()
+            },
+            supported_usage_flags: Usage {
+                color_attachment: true, transfer_dest: true, .. Usage::none()
+            },
+            supported_formats: vec![(Format::B8G8R8A8Srgb, ColorSpace::SrgbNonLinear)],
+            present_modes: SupportedPresentModes { fifo: true, .. SupportedPresentModes::none() },
+        }
+    }
+
+    #[test]
+    fn capabilities_display() {
+        let caps = test_capabilities();
+        let text = format!("{}", caps);
+        assert!(text.contains("max image count: unlimited\n"));
+        assert!(text.contains("current extent: 800x600\n"));
+        assert!(text.contains("supported transforms: Identity\n"));
+        assert!(text.contains("supported usage flags: transfer_dest, color_attachment\n"));
+        assert!(text.contains("supported formats: (B8G8R8A8Srgb, SrgbNonLinear)\n"));
+        assert!(text.contains("present modes: Fifo"));
+    }
+
+    #[test]
+    fn min_image_count_for_present_mode() {
+        let mut caps = test_capabilities();
+        caps.present_mode_min_image_counts.push((PresentMode::Mailbox, 3));
+        assert_eq!(caps.min_image_count_for_present_mode(PresentMode::Mailbox), 3);
+        assert_eq!(caps.min_image_count_for_present_mode(PresentMode::Fifo), 2);
+        assert_eq!(caps.min_image_count_for_present_mode(PresentMode::Immediate), 2);
+    }
+
+    #[test]
+    fn srgb_format() {
+        let mut caps = test_capabilities();
+        assert_eq!(caps.srgb_format(), Some((Format::B8G8R8A8Srgb, ColorSpace::SrgbNonLinear)));
+
+        caps.supported_formats = vec![(Format::B8G8R8A8Unorm, ColorSpace::SrgbNonLinear),
+                                      (Format::R8G8B8A8Srgb, ColorSpace::SrgbNonLinear)];
+        assert_eq!(caps.srgb_format(), Some((Format::R8G8B8A8Srgb, ColorSpace::SrgbNonLinear)));
+
+        caps.supported_formats = vec![(Format::B8G8R8A8Unorm, ColorSpace::SrgbNonLinear),
+                                      (Format::B8G8R8A8Srgb, ColorSpace::DisplayP3NonLinear)];
+        assert_eq!(caps.srgb_format(), None);
+    }
+
+    #[test]
+    fn find_format() {
+        let mut caps = test_capabilities();
+        caps.supported_formats = vec![(Format::R16G16B16A16Sfloat, ColorSpace::ExtendedSrgbLinear),
+                                      (Format::B8G8R8A8Unorm, ColorSpace::SrgbNonLinear),
+                                      (Format::B8G8R8A8Srgb, ColorSpace::SrgbNonLinear)];
+
+        assert_eq!(caps.find_format(|_, c| c == ColorSpace::SrgbNonLinear),
+                   Some((Format::B8G8R8A8Unorm, ColorSpace::SrgbNonLinear)));
+        assert_eq!(caps.find_format(|f, _| f == Format::B8G8R8A8Srgb),
+                   Some((Format::B8G8R8A8Srgb, ColorSpace::SrgbNonLinear)));
+        assert_eq!(caps.find_format(|f, _| f == Format::R8G8B8A8Srgb), None);
+    }
+
+    #[test]
+    fn image_count_range() {
+        let mut caps = test_capabilities();
+        let range = caps.image_count_range();
+        assert_eq!(range, ImageCountRange { min: 2, max: None });
+        assert!(!range.contains(1));
+        assert!(range.contains(2));
+        assert!(range.contains(1000));
+        assert_eq!(range.clamp(1), 2);
+        assert_eq!(range.clamp(1000), 1000);
+
+        caps.max_image_count = Some(3);
+        let range = caps.image_count_range();
+        assert!(range.contains(3));
+        assert!(!range.contains(4));
+        assert_eq!(range.clamp(4), 3);
+    }
+
+    #[test]
+    fn max_acquired_images() {
+        let caps = test_capabilities();
+        assert_eq!(caps.max_acquired_images(2), 1);
+        assert_eq!(caps.max_acquired_images(4), 3);
+        assert_eq!(caps.max_acquired_images(1), 1);
+    }
+
+    #[test]
+    fn present_mode_best_match() {
+        let modes = SupportedPresentModes { immediate: true, .. SupportedPresentModes::none() };
+        assert_eq!(modes.best_match(PresentMode::low_latency_preference()),
+                   Some(PresentMode::Immediate));
+        // `Fifo` is always supported.
+        assert_eq!(modes.best_match(PresentMode::vsync_preference()), Some(PresentMode::Fifo));
+        assert_eq!(modes.best_match(&[PresentMode::Mailbox]), None);
+    }
+
+    #[test]
+    fn color_space_vk_round_trip() {
+        let list = [
+            ColorSpace::SrgbNonLinear, ColorSpace::DisplayP3NonLinear,
+            ColorSpace::ExtendedSrgbLinear, ColorSpace::ExtendedSrgbNonLinear,
+            ColorSpace::DisplayP3Linear, ColorSpace::DciP3NonLinear, ColorSpace::Bt709Linear,
+            ColorSpace::Bt709NonLinear, ColorSpace::Bt2020Linear, ColorSpace::Hdr10St2084,
+            ColorSpace::DolbyVision, ColorSpace::Hdr10Hlg, ColorSpace::AdobeRgbLinear,
+            ColorSpace::AdobeRgbNonLinear, ColorSpace::PassThrough, ColorSpace::DisplayNativeAmd,
+        ];
+
+        for &color_space in list.iter() {
+            assert_eq!(ColorSpace::from_vk(color_space.to_vk()), Some(color_space));
+        }
+
+        assert_eq!(ColorSpace::from_vk(0x7fffffff), None);
+    }
+
+    #[test]
+    fn native_window_in_use() {
+        assert_eq!(SurfaceCreationError::from(Error::NativeWindowInUse),
+                   SurfaceCreationError::NativeWindowInUse);
+    }
+
+    #[test]
+    fn capabilities_surface_lost() {
+        assert_eq!(CapabilitiesError::from(Error::SurfaceLost), CapabilitiesError::SurfaceLost);
+    }
+
+    #[test]
+    fn surface_keeps_instance_alive() {
+        let extensions = InstanceExtensions {
+            khr_surface: true,
+            khr_display: true,
+            .. InstanceExtensions::none()
+        };
+
+        let instance = match Instance::new(None, &extensions, None) {
+            Ok(i) => i,
+            Err(_) => return
+        };
+
+        let surface = {
+            let physical = match PhysicalDevice::enumerate(&instance).next() {
+                Some(p) => p,
+                None => return
+            };
+
+            let display = match Display::enumerate(&physical).next() {
+                Some(d) => d,
+                None => return
+            };
+
+            let mode = match display.display_modes().next() {
+                Some(m) => m,
+                None => return
+            };
+
+            let plane = match DisplayPlane::enumerate(&physical).find(|p| p.supports(&display)) {
+                Some(p) => p,
+                None => return
+            };
+
+            match Surface::from_display_mode(&mode, &plane) {
+                Ok(s) => s,
+                Err(_) => return
+            }
+        };
+
+        assert_eq!(&**surface.instance() as *const _, &*instance as *const _);
+
+        let weak_instance = Arc::downgrade(&instance);
+        drop(instance);
+        assert!(weak_instance.upgrade().is_some());
+
+        drop(surface);
+        assert!(weak_instance.upgrade().is_none());
+    }
+
+    #[test]
+    fn missing_extension_display() {
+        let err = SurfaceCreationError::MissingExtension { name: "VK_KHR_xcb_surface" };
+        assert_eq!(format!("{}", err), "the `VK_KHR_xcb_surface` extension was not enabled");
+    }
 
     #[test]
     fn khr_win32_surface_ext_missing() {
@@ -918,4 +2208,31 @@ mod tests {
             _ => panic!()
         }
     }
+
+    #[test]
+    fn ext_metal_surface_ext_missing() {
+        let instance = instance!();
+        match unsafe { Surface::from_metal_layer(&instance, ptr::null::<u8>()) } {
+            Err(SurfaceCreationError::MissingExtension { .. }) => (),
+            _ => panic!()
+        }
+    }
+
+    #[test]
+    fn ext_directfb_surface_ext_missing() {
+        let instance = instance!();
+        match unsafe { Surface::from_directfb(&instance, ptr::null::<u8>(), ptr::null::<u8>()) } {
+            Err(SurfaceCreationError::MissingExtension { .. }) => (),
+            _ => panic!()
+        }
+    }
+
+    #[test]
+    fn qnx_screen_surface_ext_missing() {
+        let instance = instance!();
+        match unsafe { Surface::from_qnx_screen(&instance, ptr::null::<u8>(), ptr::null::<u8>()) } {
+            Err(SurfaceCreationError::MissingExtension { .. }) => (),
+            _ => panic!()
+        }
+    }
 }
@@ -7,27 +7,47 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+use std::cmp;
 use std::error;
 use std::fmt;
 use std::mem;
+use std::os::raw::c_void;
 use std::ptr;
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
+use std::time::Instant;
 use crossbeam::sync::MsQueue;
+use smallvec::SmallVec;
 
+use command_buffer::Submission;
+use debug_marker;
+use debug_marker::DebugMarkerError;
+use debug_marker::DebugMarkerObject;
 use device::Device;
 use device::Queue;
 use format::Format;
 use format::FormatDesc;
+use framebuffer::LayoutAttachmentDescription;
+use framebuffer::RenderPassDesc;
 use image::sys::Dimensions;
 use image::sys::UnsafeImage;
 use image::sys::Usage as ImageUsage;
 use image::swapchain::SwapchainImage;
+use instance::Version;
+use swapchain::Capabilities;
+use swapchain::CapabilitiesError;
+use swapchain::ColorSpace;
 use swapchain::CompositeAlpha;
 use swapchain::PresentMode;
+use swapchain::PresentRegion;
 use swapchain::Surface;
+use swapchain::SurfaceCounter;
 use swapchain::SurfaceTransform;
+use swapchain::surface::SupportedPresentModes;
+use sync::Fence;
+use sync::FenceWaitError;
 use sync::Semaphore;
 use sync::SharingMode;
 
@@ -56,12 +76,58 @@ pub struct Swapchain {
 
     images_semaphores: Mutex<Vec<Option<Arc<Semaphore>>>>,
 
-    // If true, that means we have used this swapchain to recreate a new swapchain. The current
-    // swapchain can no longer be used for anything except presenting already-acquired images.
+    // For each image, the latest submission that uses the resources associated with this image.
+    // See `set_image_submission`.
+    images_submissions: Mutex<Vec<Option<Arc<Submission>>>>,
+
+    // For each image, the fence passed to the latest call to `present_with_fence`. Kept alive
+    // until the image is presented again with a fence.
+    images_present_fences: Mutex<Vec<Option<Arc<Fence>>>>,
+
+    // For each image, true if it has been acquired at least once. With deferred memory
+    // allocation, the memory of an image is only allocated when it is acquired for the first time.
+    images_acquired: Mutex<Vec<bool>>,
+
+    // The name given with `set_debug_name`, which is copied to the swapchains that recreate
+    // this one.
+    debug_name: Mutex<Option<String>>,
+
+    // The latest identifier passed to `present_with_id`, or 0.
+    last_present_id: Mutex<u64>,
+
+    // Whether this swapchain has been retired by a recreation. A retired swapchain can no
+    // longer be used for anything except presenting already-acquired images.
     //
-    // We use a `Mutex` instead of an `AtomicBool` because we want to keep that locked while
-    // we acquire the image.
-    stale: Mutex<bool>,
+    // We use a `Mutex` instead of an atomic because we want to keep that locked while we
+    // acquire the image.
+    stale: Mutex<Staleness>,
+
+    // True if acquiring or presenting an image has returned `OutOfDate`. Only used to print a
+    // warning when presenting to the swapchain anyway.
+    #[cfg(debug_assertions)]
+    reported_out_of_date: AtomicBool,
+
+    // True if acquiring or presenting an image has returned `SurfaceLost`. Such a swapchain
+    // can't be recreated, as the surface itself must be recreated first.
+    surface_lost: AtomicBool,
+
+    // Number of images actually created by the implementation. May be higher than the number
+    // of images that was requested.
+    num_images: u32,
+
+    // Parameters passed to the constructor, kept around so that the swapchain can be recreated.
+    image_count: ImageCount,
+    format: Format,
+    color_space: ColorSpace,
+    dimensions: [u32; 2],
+    layers: u32,
+    usage: ImageUsage,
+    sharing: SharingMode,
+    transform: SurfaceTransform,
+    alpha: CompositeAlpha,
+    mode: PresentMode,
+    clipped: bool,
+    options: SwapchainOptions,
 }
 
 impl Swapchain {
@@ -71,11 +137,21 @@ impl Swapchain {
     /// supported by the implementation. All the parameters that you pass to `Swapchain::new`
     /// must be supported. 
     ///
+    /// The `format` and `color_space` must be one of the pairs of the `supported_formats` of the
+    /// surface's capabilities.
+    ///
     /// The `clipped` parameter indicates whether the implementation is allowed to discard 
     /// rendering operations that affect regions of the surface which aren't visible. This is
     /// important to take into account if your fragment shader has side-effects or if you want to
     /// read back the content of the image afterwards.
     ///
+    /// The `layers` parameter is the number of array layers of each image. It is usually 1, or 2
+    /// for a stereo display. It must not be greater than the `max_image_array_layers` of the
+    /// surface's capabilities. See `SwapchainImage::layer_view` to draw to a single layer.
+    ///
+    /// The `num_images` parameter can be either a `u32`, which is the same as
+    /// `ImageCount::Exact`, or an `ImageCount`. See the docs of `ImageCount`.
+    ///
     /// This function returns the swapchain plus a list of the images that belong to the
     /// swapchain. The order in which the images are returned is important for the
     /// `acquire_next_image` and `present` functions.
@@ -84,57 +160,339 @@ impl Swapchain {
     ///
     /// - Panicks if the device and the surface don't belong to the same instance.
     /// - Panicks if `color_attachment` is false in `usage`.
+    /// - Panicks if `num_images` is an `ImageCount::Exact` outside of the range supported by the
+    ///   surface.
     ///
+    // TODO: more of the parameters should be validated with an error instead of a panic
     #[inline]
-    pub fn new<F, S>(device: &Arc<Device>, surface: &Arc<Surface>, num_images: u32, format: F,
-                     dimensions: [u32; 2], layers: u32, usage: &ImageUsage, sharing: S,
+    pub fn new<N, F, S>(device: &Arc<Device>, surface: &Arc<Surface>, num_images: N, format: F,
+                     color_space: ColorSpace, dimensions: [u32; 2], layers: u32,
+                     usage: &ImageUsage, sharing: S,
                      transform: SurfaceTransform, alpha: CompositeAlpha, mode: PresentMode,
                      clipped: bool, old_swapchain: Option<&Arc<Swapchain>>)
-                     -> Result<(Arc<Swapchain>, Vec<Arc<SwapchainImage>>), OomError>
-        where F: FormatDesc, S: Into<SharingMode>
+                     -> Result<(Arc<Swapchain>, Vec<Arc<SwapchainImage>>), SwapchainCreationError>
+        where N: Into<ImageCount>, F: FormatDesc, S: Into<SharingMode>
+    {
+        Swapchain::new_inner(device, surface, num_images.into(), format.format(), color_space,
+                             dimensions, layers, usage, sharing.into(), transform, alpha, mode,
+                             clipped, old_swapchain.map(|s| &**s), &SwapchainOptions::default())
+    }
+
+    /// Builds a new swapchain with parameters automatically chosen from the capabilities of the
+    /// surface.
+    ///
+    /// This is a shortcut for `new` which picks sensible defaults:
+    ///
+    /// - One more image than the minimum, if the surface allows it.
+    /// - An sRGB format if one is supported, otherwise the first supported format. If the surface
+    ///   doesn't report any format, `SwapchainCreationError::NoSupportedFormat` is returned.
+    /// - The current dimensions of the surface if it has some, otherwise `desired_dimensions`
+    ///   clamped to the supported range.
+    /// - The `Fifo` present mode, which is always supported.
+    /// - The identity transform if supported, otherwise the current transform.
+    /// - The first supported composite alpha mode.
+    /// - A single layer, exclusive sharing, and clipping enabled.
+    ///
+    /// Use `new` if you need more control over these parameters.
+    ///
+    /// # Panic
+    ///
+    /// - Panicks if the device and the surface don't belong to the same instance.
+    /// - Panicks if `color_attachment` is false in `usage`.
+    ///
+    pub fn new_auto(device: &Arc<Device>, surface: &Arc<Surface>, desired_dimensions: [u32; 2],
+                    usage: &ImageUsage)
+                    -> Result<(Arc<Swapchain>, Vec<Arc<SwapchainImage>>), SwapchainCreationError>
+    {
+        let capabilities = try!(surface.get_capabilities(&device.physical_device()));
+        let params = try!(auto_parameters(&capabilities, desired_dimensions));
+
+        // The queue family index is ignored with the exclusive sharing mode.
+        Swapchain::new_inner(device, surface, ImageCount::Exact(params.num_images),
+                             params.format, params.color_space, params.dimensions, 1, usage,
+                             SharingMode::Exclusive(0), params.transform, params.alpha,
+                             PresentMode::Fifo, true, None, &SwapchainOptions::default())
+    }
+
+    /// Same as `new`, but with additional options that are provided by extensions.
+    ///
+    /// See the docs of `SwapchainOptions`.
+    #[inline]
+    pub fn with_options<N, F, S>(device: &Arc<Device>, surface: &Arc<Surface>, num_images: N,
+                              format: F, color_space: ColorSpace, dimensions: [u32; 2],
+                              layers: u32, usage: &ImageUsage, sharing: S,
+                              transform: SurfaceTransform, alpha: CompositeAlpha,
+                              mode: PresentMode, clipped: bool,
+                              old_swapchain: Option<&Arc<Swapchain>>, options: &SwapchainOptions)
+                              -> Result<(Arc<Swapchain>, Vec<Arc<SwapchainImage>>),
+                                        SwapchainCreationError>
+        where N: Into<ImageCount>, F: FormatDesc, S: Into<SharingMode>
+    {
+        Swapchain::new_inner(device, surface, num_images.into(), format.format(), color_space,
+                             dimensions, layers, usage, sharing.into(), transform, alpha, mode,
+                             clipped, old_swapchain.map(|s| &**s), options)
+    }
+
+    /// Recreates the swapchain with new dimensions.
+    ///
+    /// All the other parameters are the same as the ones that were used to create this
+    /// swapchain. This is typically used when `acquire_next_image` or `present` return
+    /// `OutOfDate` after the window has been resized.
+    ///
+    /// The current swapchain is always passed to the implementation as the old swapchain, which
+    /// lets the driver recycle its resources (including the memory of the images if the
+    /// dimensions didn't change). The new swapchain always returns new `SwapchainImage`s, even if
+    /// the dimensions are the same as before. Once this function has been called, the current
+    /// swapchain is retired and `acquire_next_image` will return `OutOfDate`, but images that
+    /// were already acquired can still be presented.
+    ///
+    /// A swapchain can only be recreated once. This function can safely be called from multiple
+    /// threads at the same time: the recreations are serialized, and once one of them has
+    /// succeeded the other ones return `SwapchainCreationError::AlreadyRecreated`. The same
+    /// applies when passing a swapchain as the `old_swapchain` parameter of `new`.
+    ///
+    /// If the recreation fails, the swapchain is retired anyway and `acquire_next_image` returns
+    /// `OutOfDate`, but the swapchain can be recreated again.
+    ///
+    /// # Surface loss
+    ///
+    /// If `acquire_next_image` or `present` has returned `SurfaceLost`, or if the
+    /// implementation reports that the surface is lost while recreating the swapchain, this
+    /// function returns `SwapchainCreationError::SurfaceLost`. Contrary to `OutOfDate`,
+    /// recreating the swapchain again will never succeed. Instead you should:
+    ///
+    /// - Drop the swapchain and its images, once the submissions that use them are finished.
+    /// - Create a new `Surface` for the window.
+    /// - Create a new swapchain for this surface with `Swapchain::new`, without any old
+    ///   swapchain.
+    ///
+    /// # Debug name
+    ///
+    /// If a name was given to this swapchain with `set_debug_name`, the same name is given to
+    /// the new swapchain.
+    ///
+    #[inline]
+    pub fn recreate_with_dimension(&self, dimensions: [u32; 2])
+                                   -> Result<(Arc<Swapchain>, Vec<Arc<SwapchainImage>>),
+                                             SwapchainCreationError>
+    {
+        self.recreate_inner(dimensions, self.mode)
+    }
+
+    /// Same as `recreate_with_dimension`, but first waits until the GPU no longer uses the
+    /// resources of the frames that are in flight.
+    ///
+    /// If `fences` is `Some`, this function waits until all these fences are signaled. They are
+    /// typically the fences of the submissions of the frames in flight. If `fences` is `None`,
+    /// this function waits until the whole device is idle, which is simpler but also waits for
+    /// the work that doesn't use the swapchain.
+    ///
+    /// Once this function returns, the old swapchain and its images can be dropped without
+    /// destroying objects that are still in use by the GPU, which is a common cause of errors
+    /// when a window is resized.
+    ///
+    /// Instead of using this function, you can drain the frames in flight yourself: call
+    /// `recreate_with_dimension`, and keep the old swapchain and its images alive until the
+    /// submissions that use them are finished, for example with `wait_for_image`. This avoids
+    /// stalling the CPU during the recreation.
+    ///
+    /// # Panic
+    ///
+    /// - Panicks if one of the fences doesn't belong to the device of the swapchain.
+    ///
+    pub fn recreate_safe(&self, dimensions: [u32; 2], fences: Option<&[&Fence]>)
+                         -> Result<(Arc<Swapchain>, Vec<Arc<SwapchainImage>>),
+                                   SwapchainCreationError>
+    {
+        match fences {
+            Some(fences) => { try!(self.device.wait_for_fences(fences, true, None)); },
+            None => try!(self.device.wait_raw()),
+        }
+
+        self.recreate_inner(dimensions, self.mode)
+    }
+
+    /// Same as `recreate_with_dimension`, but also changes the present mode.
+    ///
+    /// Like any swapchain creation, this queries the capabilities of the surface again instead
+    /// of relying on the ones that were valid when this swapchain was created. The present
+    /// modes supported by a surface can change, for example after the window has been moved to
+    /// another monitor. If `mode` is no longer supported, `UnsupportedPresentMode` is returned
+    /// and this swapchain can still be used or recreated with another present mode.
+    #[inline]
+    pub fn recreate_with_present_mode(&self, dimensions: [u32; 2], mode: PresentMode)
+                                      -> Result<(Arc<Swapchain>, Vec<Arc<SwapchainImage>>),
+                                                SwapchainCreationError>
+    {
+        self.recreate_inner(dimensions, mode)
+    }
+
+    fn recreate_inner(&self, dimensions: [u32; 2], mode: PresentMode)
+                      -> Result<(Arc<Swapchain>, Vec<Arc<SwapchainImage>>),
+                                SwapchainCreationError>
     {
-        Swapchain::new_inner(device, surface, num_images, format.format(), dimensions, layers,
-                             usage, sharing.into(), transform, alpha, mode, clipped, old_swapchain)
+        let result = Swapchain::new_inner(&self.device, &self.surface, self.image_count,
+                                          self.format, self.color_space, dimensions, self.layers,
+                                          &self.usage, self.sharing.clone(), self.transform,
+                                          self.alpha, mode, self.clipped, Some(self),
+                                          &self.options);
+
+        let (swapchain, images) = match result {
+            Ok(r) => r,
+            Err(SwapchainCreationError::SurfaceLost) => {
+                self.surface_lost.store(true, Ordering::SeqCst);
+                return Err(SwapchainCreationError::SurfaceLost);
+            },
+            Err(err) => return Err(err),
+        };
+
+        if let Some(ref name) = *self.debug_name.lock().unwrap() {
+            match swapchain.set_debug_name(name) {
+                Ok(()) => (),
+                Err(DebugMarkerError::OomError(err)) => {
+                    return Err(SwapchainCreationError::OomError(err));
+                },
+                // The extension was enabled when the name was given to this swapchain.
+                Err(DebugMarkerError::MissingExtension) => unreachable!(),
+            }
+        }
+
+        Ok((swapchain, images))
+    }
+
+    /// Gives a debug name to the swapchain.
+    ///
+    /// Contrary to `debug_marker::set_name`, the name is remembered and automatically given to
+    /// the swapchains that are created with `recreate_with_dimension`.
+    ///
+    /// The `VK_EXT_debug_marker` extension must be enabled on the device.
+    ///
+    /// # Panic
+    ///
+    /// - Panicks if `name` contains a nul character.
+    ///
+    pub fn set_debug_name(&self, name: &str) -> Result<(), DebugMarkerError> {
+        try!(debug_marker::set_name(self, name));
+        *self.debug_name.lock().unwrap() = Some(name.to_owned());
+        Ok(())
     }
 
-    // TODO:
-    //pub fn recreate() { ... }
+    /// Returns the name given with `set_debug_name`, if any.
+    #[inline]
+    pub fn debug_name(&self) -> Option<String> {
+        self.debug_name.lock().unwrap().clone()
+    }
 
     // TODO: images layouts should always be set to "PRESENT", since we have no way to switch the
     //       layout at present time
-    fn new_inner(device: &Arc<Device>, surface: &Arc<Surface>, num_images: u32, format: Format,
-                 dimensions: [u32; 2], layers: u32, usage: &ImageUsage, sharing: SharingMode,
-                 transform: SurfaceTransform, alpha: CompositeAlpha, mode: PresentMode,
-                 clipped: bool, old_swapchain: Option<&Arc<Swapchain>>)
-                 -> Result<(Arc<Swapchain>, Vec<Arc<SwapchainImage>>), OomError>
+    fn new_inner(device: &Arc<Device>, surface: &Arc<Surface>, image_count: ImageCount,
+                 format: Format, color_space: ColorSpace, dimensions: [u32; 2], layers: u32,
+                 usage: &ImageUsage, sharing: SharingMode, transform: SurfaceTransform,
+                 alpha: CompositeAlpha, mode: PresentMode, clipped: bool,
+                 old_swapchain: Option<&Swapchain>, options: &SwapchainOptions)
+                 -> Result<(Arc<Swapchain>, Vec<Arc<SwapchainImage>>), SwapchainCreationError>
     {
+        // A swapchain whose surface is lost can't be used as an old swapchain.
+        if old_swapchain.map(|s| s.surface_lost.load(Ordering::SeqCst)).unwrap_or(false) {
+            return Err(SwapchainCreationError::SurfaceLost);
+        }
+
         // Checking that the requested parameters match the capabilities.
         let capabilities = try!(surface.get_capabilities(&device.physical_device()));
-        // TODO: return errors instead
-        assert!(num_images >= capabilities.min_image_count);
-        if let Some(c) = capabilities.max_image_count { assert!(num_images <= c) };
-        assert!(capabilities.supported_formats.iter().find(|&&(f, _)| f == format).is_some());
-        assert!(dimensions[0] >= capabilities.min_image_extent[0]);
-        assert!(dimensions[1] >= capabilities.min_image_extent[1]);
-        assert!(dimensions[0] <= capabilities.max_image_extent[0]);
-        assert!(dimensions[1] <= capabilities.max_image_extent[1]);
-        assert!(layers >= 1 && layers <= capabilities.max_image_array_layers);
-        assert!((usage.to_usage_bits() & capabilities.supported_usage_flags.to_usage_bits()) == usage.to_usage_bits());
-        assert!(capabilities.supported_transforms.supports(transform));
-        assert!(capabilities.supported_composite_alpha.supports(alpha));
-        assert!(capabilities.present_modes.supports(mode));
+        let num_images = image_count.resolve(&capabilities, mode);
+        try!(check_capabilities(&capabilities, num_images, format, color_space, dimensions,
+                                layers, usage, transform, alpha, mode));
 
         // FIXME: check that the device and the surface belong to the same instance
         let vk = device.pointers();
         assert!(device.loaded_extensions().khr_swapchain);     // TODO: return error instead
 
         assert!(usage.color_attachment);
+        let usage_struct = *usage;
         let usage = usage.to_usage_bits();
 
-        let sharing = sharing.into();
+        let flags = options.create_flags();
+
+        if flags.split_instance_bind_regions && !device_group_is_core(device) &&
+           !device.loaded_extensions().khr_device_group
+        {
+            return Err(SwapchainCreationError::MissingExtension { name: "VK_KHR_device_group" });
+        }
+
+        if flags.protected {
+            try!(check_protected(device, surface));
+        }
+
+        if flags.deferred_memory_allocation &&
+           !device.loaded_extensions().ext_swapchain_maintenance1
+        {
+            return Err(SwapchainCreationError::MissingExtension {
+                name: "VK_EXT_swapchain_maintenance1"
+            });
+        }
+
+        let view_formats = if !flags.mutable_format {
+            None
+        } else {
+            if !device.loaded_extensions().khr_swapchain_mutable_format {
+                return Err(SwapchainCreationError::MissingExtension {
+                    name: "VK_KHR_swapchain_mutable_format"
+                });
+            }
+
+            if !device.loaded_extensions().khr_image_format_list {
+                return Err(SwapchainCreationError::MissingExtension {
+                    name: "VK_KHR_image_format_list"
+                });
+            }
+
+            let mut list = options.mutable_format_view_formats.clone();
+            if !list.contains(&format) {
+                list.push(format);
+            }
+            Some(list)
+        };
+
+        if !options.surface_counters.is_empty() && !device.loaded_extensions().ext_display_control {
+            return Err(SwapchainCreationError::MissingExtension {
+                name: "VK_EXT_display_control"
+            });
+        }
+
+        let uses_scaling = options.scaling_behavior.is_some() || options.present_gravity.is_some();
+        if uses_scaling && !device.loaded_extensions().ext_swapchain_maintenance1 {
+            return Err(SwapchainCreationError::MissingExtension {
+                name: "VK_EXT_swapchain_maintenance1"
+            });
+        }
+
+        // The old swapchain is retired as soon as it is passed to `vkCreateSwapchainKHR`, even if
+        // the creation fails. We keep its `stale` flag locked until the end of the creation, so
+        // that another thread that tries to use the same old swapchain waits for us and then
+        // either gets an error or recreates the swapchain without passing the retired handle to
+        // the implementation.
+        let mut old_stale = match old_swapchain {
+            Some(old_swapchain) => {
+                let stale = old_swapchain.stale.lock().unwrap();
+                if *stale == Staleness::Replaced {
+                    return Err(SwapchainCreationError::AlreadyRecreated);
+                }
+                Some(stale)
+            },
+            None => None,
+        };
+
+        // Only a swapchain that hasn't been retired yet can be passed to the implementation.
+        let old_handle = match (old_swapchain, &old_stale) {
+            (Some(old_swapchain), &Some(ref stale)) if **stale == Staleness::Fresh => {
+                old_swapchain.swapchain
+            },
+            _ => 0,
+        };
 
-        if let Some(ref old_swapchain) = old_swapchain {
-            *old_swapchain.stale.lock().unwrap() = false;
+        // From now on, any error leaves the old swapchain retired but allows recreating it again.
+        if let Some(ref mut stale) = old_stale {
+            **stale = Staleness::Retired;
         }
 
         let swapchain = unsafe {
@@ -144,14 +502,51 @@ impl Swapchain {
                                                      ids.as_ptr()),
             };
 
+            let scaling_infos = vk::SwapchainPresentScalingCreateInfoEXT {
+                sType: vk::STRUCTURE_TYPE_SWAPCHAIN_PRESENT_SCALING_CREATE_INFO_EXT,
+                pNext: ptr::null(),
+                scalingBehavior: options.scaling_behavior.map(|b| b as u32).unwrap_or(0),
+                presentGravityX: options.present_gravity.map(|g| g[0] as u32).unwrap_or(0),
+                presentGravityY: options.present_gravity.map(|g| g[1] as u32).unwrap_or(0),
+            };
+
+            let raw_view_formats = view_formats.as_ref().map(|list| {
+                list.iter().map(|&f| f as u32).collect::<Vec<_>>()
+            }).unwrap_or(Vec::new());
+
+            let format_list_infos = vk::ImageFormatListCreateInfoKHR {
+                sType: vk::STRUCTURE_TYPE_IMAGE_FORMAT_LIST_CREATE_INFO_KHR,
+                pNext: if uses_scaling {
+                    &scaling_infos as *const vk::SwapchainPresentScalingCreateInfoEXT as *const _
+                } else {
+                    ptr::null()
+                },
+                viewFormatCount: raw_view_formats.len() as u32,
+                pViewFormats: raw_view_formats.as_ptr(),
+            };
+
+            let counter_infos = vk::SwapchainCounterCreateInfoEXT {
+                sType: vk::STRUCTURE_TYPE_SWAPCHAIN_COUNTER_CREATE_INFO_EXT,
+                pNext: if view_formats.is_some() {
+                    &format_list_infos as *const vk::ImageFormatListCreateInfoKHR as *const _
+                } else {
+                    format_list_infos.pNext
+                },
+                surfaceCounters: options.surface_counters.iter().fold(0, |a, &c| a | c as u32),
+            };
+
             let infos = vk::SwapchainCreateInfoKHR {
                 sType: vk::STRUCTURE_TYPE_SWAPCHAIN_CREATE_INFO_KHR,
-                pNext: ptr::null(),
-                flags: 0,   // reserved
+                pNext: if !options.surface_counters.is_empty() {
+                    &counter_infos as *const vk::SwapchainCounterCreateInfoEXT as *const _
+                } else {
+                    counter_infos.pNext
+                },
+                flags: flags.to_bits(),
                 surface: surface.internal_object(),
                 minImageCount: num_images,
                 imageFormat: format as u32,
-                imageColorSpace: vk::COLORSPACE_SRGB_NONLINEAR_KHR,     // only available value
+                imageColorSpace: color_space.to_vk(),
                 imageExtent: vk::Extent2D { width: dimensions[0], height: dimensions[1] },
                 imageArrayLayers: layers,
                 imageUsage: usage,
@@ -162,52 +557,86 @@ impl Swapchain {
                 compositeAlpha: alpha as u32,
                 presentMode: mode as u32,
                 clipped: if clipped { vk::TRUE } else { vk::FALSE },
-                oldSwapchain: if let Some(ref old_swapchain) = old_swapchain {
-                    old_swapchain.swapchain
-                } else {
-                    0
-                },
+                oldSwapchain: old_handle,
             };
 
+            #[cfg(feature = "log")]
+            debug!("Creating swapchain: format = {:?}, color space = {:?}, extent = {:?}, \
+                    layers = {}, image count = {} (requested {:?}, surface supports {} to {:?}), \
+                    present mode = {:?}, transform = {:?}, composite alpha = {:?}, \
+                    usage = {:?}, flags = {:?}",
+                   format, color_space, dimensions, layers, num_images, image_count,
+                   capabilities.min_image_count, capabilities.max_image_count, mode, transform,
+                   alpha, usage_struct, flags);
+
             let mut output = mem::uninitialized();
             try!(check_errors(vk.CreateSwapchainKHR(device.internal_object(), &infos,
-                                                    ptr::null(), &mut output)));
+                                                    device.allocation_callbacks(), &mut output)));
             output
         };
 
+        // The implementation is free to create more images than what we requested, so we query
+        // the actual list of images before building the `Swapchain`.
+        let images = unsafe {
+            match swapchain_images(device, swapchain) {
+                Ok(images) => images,
+                Err(err) => {
+                    vk.DestroySwapchainKHR(device.internal_object(), swapchain,
+                                           device.allocation_callbacks());
+                    return Err(SwapchainCreationError::from(err));
+                },
+            }
+        };
+
         let swapchain = Arc::new(Swapchain {
             device: device.clone(),
             surface: surface.clone(),
             swapchain: swapchain,
             semaphores_pool: MsQueue::new(),
             images_semaphores: Mutex::new(Vec::new()),
-            stale: Mutex::new(false),
+            images_submissions: Mutex::new(Vec::new()),
+            images_present_fences: Mutex::new(Vec::new()),
+            images_acquired: Mutex::new(vec![false; images.len()]),
+            debug_name: Mutex::new(None),
+            last_present_id: Mutex::new(0),
+            stale: Mutex::new(Staleness::Fresh),
+            #[cfg(debug_assertions)]
+            reported_out_of_date: AtomicBool::new(false),
+            surface_lost: AtomicBool::new(false),
+            num_images: images.len() as u32,
+            image_count: image_count,
+            format: format,
+            color_space: color_space,
+            dimensions: dimensions,
+            layers: layers,
+            usage: usage_struct,
+            sharing: sharing,
+            transform: transform,
+            alpha: alpha,
+            mode: mode,
+            clipped: clipped,
+            options: options.clone(),
         });
 
-        let images = unsafe {
-            let mut num = 0;
-            try!(check_errors(vk.GetSwapchainImagesKHR(device.internal_object(),
-                                                       swapchain.swapchain, &mut num,
-                                                       ptr::null_mut())));
-
-            let mut images = Vec::with_capacity(num as usize);
-            try!(check_errors(vk.GetSwapchainImagesKHR(device.internal_object(),
-                                                       swapchain.swapchain, &mut num,
-                                                       images.as_mut_ptr())));
-            images.set_len(num as usize);
-            images
-        };
-
         let images = images.into_iter().enumerate().map(|(id, image)| unsafe {
-            let unsafe_image = UnsafeImage::from_raw(device, image, usage, format,
-                                                     Dimensions::Dim2d { width: dimensions[0], height: dimensions[1] }, 1, 1);
+            let dims = if layers == 1 {
+                Dimensions::Dim2d { width: dimensions[0], height: dimensions[1] }
+            } else {
+                Dimensions::Dim2dArray { width: dimensions[0], height: dimensions[1],
+                                         array_layers: layers }
+            };
+            let unsafe_image = UnsafeImage::from_raw(device, image, usage, format, dims, 1, 1);
             SwapchainImage::from_raw(unsafe_image, format, &swapchain, id as u32).unwrap()     // TODO: propagate error
         }).collect::<Vec<_>>();
 
         {
             let mut semaphores = swapchain.images_semaphores.lock().unwrap();
+            let mut submissions = swapchain.images_submissions.lock().unwrap();
+            let mut present_fences = swapchain.images_present_fences.lock().unwrap();
             for _ in 0 .. images.len() {
                 semaphores.push(None);
+                submissions.push(None);
+                present_fences.push(None);
             }
         }
 
@@ -217,9 +646,103 @@ impl Swapchain {
             swapchain.semaphores_pool.push(Arc::new(try!(Semaphore::raw(device))));
         }
 
+        if let Some(ref mut stale) = old_stale {
+            **stale = Staleness::Replaced;
+        }
+
         Ok((swapchain, images))
     }
 
+    /// Returns the formats that views of the images of this swapchain can use.
+    ///
+    /// This is the list passed as `mutable_format_view_formats` when creating the swapchain plus
+    /// the format of the swapchain, or only the format of the swapchain if the list was empty.
+    pub fn view_formats(&self) -> Vec<Format> {
+        let mut list = self.options.mutable_format_view_formats.clone();
+        if !list.contains(&self.format) {
+            list.push(self.format);
+        }
+        list
+    }
+
+    /// Returns true if the images of this swapchain can be used as the attachment with index
+    /// `attachment` of `render_pass`.
+    ///
+    /// The attachment must have one sample, and its format must be the format of the swapchain
+    /// or one of the `view_formats()` if the swapchain has a mutable format. Returns false if the
+    /// render pass doesn't have this attachment.
+    ///
+    /// Using a swapchain image with a render pass whose attachment has another format produces
+    /// an error when creating the framebuffer, or wrong colors on some implementations. Call
+    /// this function after creating or recreating the swapchain to detect the mismatch early.
+    pub fn is_compatible_with_render_pass<R>(&self, render_pass: &R, attachment: usize) -> bool
+        where R: ?Sized + RenderPassDesc
+    {
+        let description = render_pass.attachments().skip(attachment).next();
+        attachment_compatible(&self.view_formats(), description.as_ref())
+    }
+
+    /// Returns the device that owns this swapchain.
+    #[inline]
+    pub fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
+
+    /// Returns the number of array layers of the images of the swapchain.
+    #[inline]
+    pub fn layers(&self) -> u32 {
+        self.layers
+    }
+
+    /// Returns the color space that was passed when creating the swapchain.
+    #[inline]
+    pub fn color_space(&self) -> ColorSpace {
+        self.color_space
+    }
+
+    /// Returns the usage of the images that was passed when creating the swapchain.
+    #[inline]
+    pub fn usage(&self) -> ImageUsage {
+        self.usage
+    }
+
+    /// Returns the flags that were used to create the swapchain.
+    ///
+    /// This includes the flags that are implied by the other options, for example `protected`
+    /// if `SwapchainOptions::protected` was true.
+    #[inline]
+    pub fn flags(&self) -> SwapchainCreateFlags {
+        self.options.create_flags()
+    }
+
+    /// Returns true if the memory of the image with the given index has been allocated.
+    ///
+    /// This is always true, except if the swapchain was created with the
+    /// `deferred_memory_allocation` flag and the image has never been acquired. The image
+    /// can't be used in any way, including creating views of it, before its memory has been
+    /// allocated.
+    ///
+    /// # Panic
+    ///
+    /// - Panicks if `index` isn't the index of an image of the swapchain.
+    ///
+    #[inline]
+    pub fn is_image_allocated(&self, index: usize) -> bool {
+        let acquired = self.images_acquired.lock().unwrap()[index];
+        acquired || !self.flags().deferred_memory_allocation
+    }
+
+    /// Returns the number of images of the swapchain.
+    ///
+    /// This is the number of images that the implementation actually created, which is also the
+    /// length of the list returned when creating the swapchain. It may be higher than the
+    /// `num_images` that was passed when creating the swapchain, as this value is only a
+    /// minimum.
+    #[inline]
+    pub fn num_images(&self) -> u32 {
+        self.num_images
+    }
+
     /// Tries to take ownership of an image in order to draw on it.
     ///
     /// The function returns the index of the image in the array of images that was returned
@@ -227,10 +750,60 @@ impl Swapchain {
     ///
     /// If you try to draw on an image without acquiring it first, the execution will block. (TODO
     /// behavior may change).
+    ///
+    /// Holding too many acquired images at once can make this function wait forever. See
+    /// `Capabilities::max_acquired_images`.
+    #[inline]
     pub fn acquire_next_image(&self, timeout: Duration) -> Result<usize, AcquireError> {
+        self.acquire_next_image_inner(timeout, 0).map(|(id, _, _)| id)
+    }
+
+    /// Same as `acquire_next_image`, but also returns how long the call to
+    /// `vkAcquireNextImageKHR` took.
+    ///
+    /// This is the wall-clock time spent waiting for the presentation engine to release an
+    /// image. A long time usually means that the application is limited by the presentation
+    /// (for example by the refresh rate of the screen), while a short time means that the
+    /// CPU or GPU work is the bottleneck.
+    #[inline]
+    pub fn acquire_next_image_timed(&self, timeout: Duration)
+                                    -> Result<(usize, Duration), AcquireError>
+    {
+        self.acquire_next_image_inner(timeout, 0).map(|(id, _, elapsed)| (id, elapsed))
+    }
+
+    /// Same as `acquire_next_image`, but also returns a fence that is signaled once the
+    /// presentation engine has finished using the image.
+    ///
+    /// The image is acquired with a single call to `vkAcquireNextImageKHR` that signals both
+    /// the fence and the semaphore that vulkano uses internally. As with `acquire_next_image`,
+    /// the command buffers that use the image automatically wait on this semaphore, so no
+    /// additional submission is needed for the GPU side.
+    ///
+    /// Contrary to the semaphore, the fence can be waited upon from the CPU with `Fence::wait`,
+    /// or polled with `Fence::is_signaled`. This is useful if you need to access the content of the
+    /// image from the host right after acquiring it, for example to read it back, or to pace
+    /// the frames on the CPU while the GPU waits on the semaphore.
+    ///
+    /// The fence is taken from the fence pool of the device, and is put back in it when it is
+    /// destroyed.
+    pub fn acquire_next_image_with_fence(&self, timeout: Duration)
+                                         -> Result<(usize, Arc<Fence>), AcquireError>
+    {
+        let fence = try!(Fence::from_pool(&self.device));
+        let (id, _, _) = try!(self.acquire_next_image_inner(timeout, fence.internal_object()));
+        Ok((id, Arc::new(fence)))
+    }
+
+    // Same as `acquire_next_image`, but also returns true if the swapchain is suboptimal and the
+    // time spent in `vkAcquireNextImageKHR`. If `fence` isn't 0, it is signaled as well.
+    fn acquire_next_image_inner(&self, timeout: Duration, fence: vk::Fence)
+                                -> Result<(usize, bool, Duration), AcquireError>
+    {
         unsafe {
             let stale = self.stale.lock().unwrap();
-            if *stale {
+            if *stale != Staleness::Fresh {
+                self.debug_track_out_of_date();
                 return Err(AcquireError::OutOfDate);
             }
 
@@ -244,23 +817,39 @@ impl Swapchain {
                                               .saturating_add(timeout.subsec_nanos() as u64);
 
             let mut out = mem::uninitialized();
-            let r = try!(check_errors(vk.AcquireNextImageKHR(self.device.internal_object(),
-                                                             self.swapchain, timeout_ns,
-                                                             semaphore.internal_object(), 0,     // TODO: timeout
-                                                             &mut out)));
-
-            let id = match r {
-                Success::Success => out as usize,
-                Success::Suboptimal => out as usize,        // TODO: give that info to the user
+            let before = Instant::now();
+            let r = check_acquire_errors(vk.AcquireNextImageKHR(self.device.internal_object(),
+                                                                self.swapchain, timeout_ns,
+                                                                semaphore.internal_object(),
+                                                                fence, &mut out));
+            let r = match r {
+                Ok(r) => r,
+                Err(AcquireError::OutOfDate) => {
+                    self.debug_track_out_of_date();
+                    return Err(AcquireError::OutOfDate);
+                },
+                Err(AcquireError::SurfaceLost) => {
+                    self.surface_lost.store(true, Ordering::SeqCst);
+                    return Err(AcquireError::SurfaceLost);
+                },
+                Err(err) => return Err(err),
+            };
+            let elapsed = before.elapsed();
+
+            let (id, suboptimal) = match r {
+                Success::Success => (out as usize, false),
+                Success::Suboptimal => (out as usize, true),
                 Success::NotReady => return Err(AcquireError::Timeout),
                 Success::Timeout => return Err(AcquireError::Timeout),
                 s => panic!("unexpected success value: {:?}", s)
             };
 
+            semaphore.debug_track_signal();
             let mut images_semaphores = self.images_semaphores.lock().unwrap();
             images_semaphores[id] = Some(semaphore);
+            self.images_acquired.lock().unwrap()[id] = true;
 
-            Ok(id)
+            Ok((id, suboptimal, elapsed))
         }
     }
 
@@ -271,66 +860,1310 @@ impl Swapchain {
     ///
     /// The actual behavior depends on the present mode that you passed when creating the
     /// swapchain.
+    #[inline]
     pub fn present(&self, queue: &Arc<Queue>, index: usize) -> Result<(), PresentError> {
-        let vk = self.device.pointers();
+        unsafe { self.present_inner(queue, index, ptr::null()) }
+    }
 
-        let wait_semaphore = {
-            let mut images_semaphores = self.images_semaphores.lock().unwrap();
-            images_semaphores[index].take().expect("Trying to present an image that was \
-                                                    not acquired")
+    /// Same as `present`, but also signals `fence` once the presentation engine no longer uses
+    /// the image and the semaphore that the presentation waits upon.
+    ///
+    /// This is the modern replacement for associating a fence with each image with
+    /// `set_image_submission` and `wait_for_image`: once the fence is signalled, the resources
+    /// that were used to draw on the image can be reused or destroyed.
+    ///
+    /// The `VK_EXT_swapchain_maintenance1` device extension must be enabled, otherwise
+    /// `PresentError::MissingExtension` is returned and nothing is presented. The swapchain keeps
+    /// the fence alive until the same image is presented again with another fence.
+    ///
+    /// # Safety
+    ///
+    /// - The fence must belong to the same device as the swapchain.
+    /// - The fence must be unsignalled and must not be used by any pending operation.
+    ///
+    pub unsafe fn present_with_fence(&self, queue: &Arc<Queue>, index: usize, fence: Arc<Fence>)
+                                     -> Result<(), PresentError>
+    {
+        if !self.device.loaded_extensions().ext_swapchain_maintenance1 {
+            return Err(PresentError::MissingExtension {
+                name: "VK_EXT_swapchain_maintenance1",
+            });
+        }
+
+        let raw_fence = fence.internal_object();
+
+        let fence_infos = vk::SwapchainPresentFenceInfoEXT {
+            sType: vk::STRUCTURE_TYPE_SWAPCHAIN_PRESENT_FENCE_INFO_EXT,
+            pNext: ptr::null(),
+            swapchainCount: 1,
+            pFences: &raw_fence,
         };
 
-        // FIXME: the semaphore may be destroyed ; need to return it
+        try!(self.present_inner(queue, index, &fence_infos as *const _ as *const c_void));
+
+        let mut present_fences = self.images_present_fences.lock().unwrap();
+        present_fences[index] = Some(fence);
+        Ok(())
+    }
+
+    /// Same as `present`, but indicates which parts of the image have changed since the image
+    /// was last presented.
+    ///
+    /// The region is only a hint: the presentation engine is free to ignore it and to update the
+    /// whole image, so the content of the image outside of the region must still be correct.
+    ///
+    /// The `VK_KHR_incremental_present` device extension must be enabled, otherwise
+    /// `PresentError::IncrementalPresentNotEnabled` is returned and nothing is presented.
+    pub fn present_incremental(&self, queue: &Arc<Queue>, index: usize, region: &PresentRegion)
+                               -> Result<(), PresentError>
+    {
+        if !self.device.loaded_extensions().khr_incremental_present {
+            return Err(PresentError::IncrementalPresentNotEnabled);
+        }
+
+        let rectangles = region.to_vk();
+
+        let vk_region = vk::PresentRegionKHR {
+            rectangleCount: rectangles.len() as u32,
+            pRectangles: rectangles.as_ptr(),
+        };
+
+        let regions_infos = vk::PresentRegionsKHR {
+            sType: vk::STRUCTURE_TYPE_PRESENT_REGIONS_KHR,
+            pNext: ptr::null(),
+            swapchainCount: 1,
+            pRegions: &vk_region,
+        };
 
         unsafe {
-            let mut result = mem::uninitialized();
+            self.present_inner(queue, index, &regions_infos as *const _ as *const c_void)
+        }
+    }
 
-            let queue = queue.internal_object_guard();
-            let index = index as u32;
+    /// Same as `present`, but associates an identifier with the presentation.
+    ///
+    /// The identifier can then be passed to `wait_for_present` in order to wait until the image
+    /// is shown to the user. Identifiers must be strictly increasing for a given swapchain.
+    ///
+    /// The `VK_KHR_present_id` device extension must be enabled, otherwise
+    /// `PresentError::MissingExtension` is returned. If `present_id` isn't greater than the
+    /// identifier of the previous call, `PresentError::NonMonotonicPresentId` is returned. In
+    /// both cases nothing is presented.
+    pub fn present_with_id(&self, queue: &Arc<Queue>, index: usize, present_id: u64)
+                           -> Result<(), PresentError>
+    {
+        if !self.device.loaded_extensions().khr_present_id {
+            return Err(PresentError::MissingExtension { name: "VK_KHR_present_id" });
+        }
 
-            let infos = vk::PresentInfoKHR {
-                sType: vk::STRUCTURE_TYPE_PRESENT_INFO_KHR,
-                pNext: ptr::null(),
-                waitSemaphoreCount: 1,
-                pWaitSemaphores: &wait_semaphore.internal_object(),
-                swapchainCount: 1,
-                pSwapchains: &self.swapchain,
-                pImageIndices: &index,
-                pResults: &mut result,
-            };
+        let mut last_present_id = self.last_present_id.lock().unwrap();
+        if present_id <= *last_present_id {
+            return Err(PresentError::NonMonotonicPresentId {
+                last: *last_present_id,
+                requested: present_id,
+            });
+        }
 
-            try!(check_errors(vk.QueuePresentKHR(*queue, &infos)));
-            //try!(check_errors(result));       // TODO: AMD driver doesn't seem to write the result
+        let id_infos = vk::PresentIdKHR {
+            sType: vk::STRUCTURE_TYPE_PRESENT_ID_KHR,
+            pNext: ptr::null(),
+            swapchainCount: 1,
+            pPresentIds: &present_id,
+        };
+
+        unsafe {
+            try!(self.present_inner(queue, index, &id_infos as *const _ as *const c_void));
         }
 
-        self.semaphores_pool.push(wait_semaphore);
+        *last_present_id = present_id;
         Ok(())
     }
 
-    /*/// Returns the semaphore that is going to be signalled when the image is going to be ready
-    /// to be drawn upon.
+    /// Same as `present`, but associates a display timing identifier with the presentation and
+    /// optionally asks for the image to be shown no earlier than a specific time.
     ///
-    /// Returns `None` if the image was not acquired first, or was already presented.
-    // TODO: racy, as someone could present the image before using the semaphore
-    #[inline]
-    pub fn image_semaphore(&self, id: u32) -> Option<Arc<Semaphore>> {
+    /// `present_id` is the identifier that `past_presentation_timing` reports for this
+    /// presentation. `desired_present_time` is in nanoseconds, in the same time domain as the
+    /// times returned by `past_presentation_timing`. If `None`, the image is shown as soon as
+    /// possible.
+    ///
+    /// The `VK_GOOGLE_display_timing` device extension must be enabled, otherwise
+    /// `PresentError::DisplayTimingNotEnabled` is returned and nothing is presented.
+    pub fn present_with_timing(&self, queue: &Arc<Queue>, index: usize, present_id: u32,
+                               desired_present_time: Option<u64>)
+                               -> Result<(), PresentError>
+    {
+        if !self.device.loaded_extensions().google_display_timing {
+            return Err(PresentError::DisplayTimingNotEnabled);
+        }
+
+        let time = vk::PresentTimeGOOGLE {
+            presentID: present_id,
+            desiredPresentTime: desired_present_time.unwrap_or(0),
+        };
+
+        let times_infos = vk::PresentTimesInfoGOOGLE {
+            sType: vk::STRUCTURE_TYPE_PRESENT_TIMES_INFO_GOOGLE,
+            pNext: ptr::null(),
+            swapchainCount: 1,
+            pTimes: &time,
+        };
+
+        unsafe {
+            self.present_inner(queue, index, &times_infos as *const _ as *const c_void)
+        }
+    }
+
+    /// Waits until the image that was presented with the given identifier, or with a later
+    /// identifier, is shown to the user.
+    ///
+    /// See `present_with_id`. The `VK_KHR_present_wait` device extension must be enabled,
+    /// otherwise `PresentWaitError::MissingExtension` is returned.
+    pub fn wait_for_present(&self, present_id: u64, timeout: Duration)
+                            -> Result<(), PresentWaitError>
+    {
+        if !self.device.loaded_extensions().khr_present_wait {
+            return Err(PresentWaitError::MissingExtension { name: "VK_KHR_present_wait" });
+        }
+
+        let timeout_ns = timeout.as_secs().saturating_mul(1_000_000_000)
+                                          .saturating_add(timeout.subsec_nanos() as u64);
+
+        unsafe {
+            let vk = self.device.pointers();
+            let r = try!(check_errors(vk.WaitForPresentKHR(self.device.internal_object(),
+                                                           self.swapchain, present_id,
+                                                           timeout_ns)));
+
+            match r {
+                Success::Success => Ok(()),
+                Success::Suboptimal => Ok(()),
+                Success::Timeout => Err(PresentWaitError::Timeout),
+                s => panic!("unexpected success value: {:?}", s)
+            }
+        }
+    }
+
+    // Puts a semaphore back in the pool of semaphores that are used to acquire images.
+    //
+    // The semaphore must no longer have a pending signal operation, otherwise the next
+    // acquisition that uses it is invalid and can hang. In debug builds, a semaphore that is
+    // still pending is replaced with a new one and a warning is logged if the `log` feature is
+    // enabled. Release builds don't track the state of semaphores and always reuse them.
+    fn recycle_semaphore(&self, semaphore: Arc<Semaphore>) {
+        #[cfg(debug_assertions)]
+        fn check(swapchain: &Swapchain, semaphore: Arc<Semaphore>) -> Arc<Semaphore> {
+            if !semaphore.debug_has_pending_signal() {
+                return semaphore;
+            }
+
+            #[cfg(feature = "log")]
+            warn!("Semaphore {:#x} was returned to the pool of swapchain {:#x} with a pending \
+                   signal; replacing it with a new semaphore",
+                  semaphore.internal_object(), swapchain.swapchain);
+
+            match Semaphore::raw(&swapchain.device) {
+                Ok(new) => Arc::new(new),
+                Err(_) => semaphore,
+            }
+        }
+
+        #[cfg(not(debug_assertions))]
+        fn check(_: &Swapchain, semaphore: Arc<Semaphore>) -> Arc<Semaphore> {
+            semaphore
+        }
+
+        self.semaphores_pool.push(check(self, semaphore));
+    }
+
+    // Records that the swapchain has been reported as out of date. Does nothing in release
+    // builds.
+    #[inline]
+    fn debug_track_out_of_date(&self) {
+        #[cfg(debug_assertions)]
+        self.reported_out_of_date.store(true, Ordering::SeqCst);
+    }
+
+    // Logs a warning if the swapchain has been reported as out of date. Presenting to such a
+    // swapchain is allowed but wasteful, as it should be recreated. Does nothing in release
+    // builds or if the `log` feature is disabled.
+    #[inline]
+    fn debug_warn_out_of_date(&self) {
+        #[cfg(all(debug_assertions, feature = "log"))]
+        fn warn(swapchain: &Swapchain) {
+            if swapchain.reported_out_of_date.load(Ordering::SeqCst) {
+                warn!("Presenting to swapchain {:#x} which is out of date; it should be recreated",
+                      swapchain.swapchain);
+            }
+        }
+
+        #[cfg(not(all(debug_assertions, feature = "log")))]
+        fn warn(_: &Swapchain) {
+        }
+
+        warn(self)
+    }
+
+    // Presents an image. `p_next` is passed as the `pNext` of the `VkPresentInfoKHR`.
+    unsafe fn present_inner(&self, queue: &Arc<Queue>, index: usize, p_next: *const c_void)
+                            -> Result<(), PresentError>
+    {
+        let vk = self.device.pointers();
+
+        let wait_semaphore = {
+            let mut images_semaphores = self.images_semaphores.lock().unwrap();
+            images_semaphores[index].take().expect("Trying to present an image that was \
+                                                    not acquired")
+        };
+        wait_semaphore.consume_temporary_payload();
+        wait_semaphore.debug_track_wait();
+        self.debug_warn_out_of_date();
+
+        // FIXME: the semaphore may be destroyed ; need to return it
+
+        {
+            let mut result = mem::uninitialized();
+
+            let queue = queue.internal_object_guard();
+            let index = index as u32;
+
+            let infos = vk::PresentInfoKHR {
+                sType: vk::STRUCTURE_TYPE_PRESENT_INFO_KHR,
+                pNext: p_next,
+                waitSemaphoreCount: 1,
+                pWaitSemaphores: &wait_semaphore.internal_object(),
+                swapchainCount: 1,
+                pSwapchains: &self.swapchain,
+                pImageIndices: &index,
+                pResults: &mut result,
+            };
+
+            match check_errors(vk.QueuePresentKHR(*queue, &infos)) {
+                Ok(_) => (),
+                Err(Error::OutOfDate) => {
+                    self.debug_track_out_of_date();
+                    return Err(PresentError::OutOfDate);
+                },
+                Err(Error::SurfaceLost) => {
+                    self.surface_lost.store(true, Ordering::SeqCst);
+                    return Err(PresentError::SurfaceLost);
+                },
+                Err(err) => return Err(PresentError::from(err)),
+            }
+            //try!(check_errors(result));       // TODO: AMD driver doesn't seem to write the result
+        }
+
+        self.recycle_semaphore(wait_semaphore);
+        Ok(())
+    }
+
+    /// Releases images that have been acquired but that you don't want to present.
+    ///
+    /// The images can then be acquired again with `acquire_next_image`. Without this function,
+    /// an image that is acquired and never presented can never be acquired again.
+    ///
+    /// The `VK_EXT_swapchain_maintenance1` device extension must be enabled.
+    ///
+    /// # Panic
+    ///
+    /// - Panicks if one of the images wasn't acquired or has already been presented.
+    ///
+    /// # Safety
+    ///
+    /// - The images must not be in use by the GPU, and no submission must be waiting on the
+    ///   semaphore that was signalled when the images were acquired.
+    ///
+    /// The semaphores of the released images are not checked for pending waits. Since they are
+    /// never unsignalled, they are not reused by the swapchain and are destroyed as soon as the
+    /// last `Arc` pointing to them is dropped. Semaphores that are still kept alive by a pending
+    /// submission are only destroyed once that submission is dropped.
+    ///
+    pub unsafe fn release_images(&self, indices: &[u32]) -> Result<(), ReleaseImagesError> {
+        if !self.device.loaded_extensions().ext_swapchain_maintenance1 {
+            return Err(ReleaseImagesError::MissingExtension);
+        }
+
+        let mut images_semaphores = self.images_semaphores.lock().unwrap();
+        for &index in indices {
+            assert!(images_semaphores[index as usize].is_some(),
+                    "Trying to release an image that was not acquired");
+        }
+
+        let vk = self.device.pointers();
+
+        let infos = vk::ReleaseSwapchainImagesInfoEXT {
+            sType: vk::STRUCTURE_TYPE_RELEASE_SWAPCHAIN_IMAGES_INFO_EXT,
+            pNext: ptr::null(),
+            swapchain: self.swapchain,
+            imageIndexCount: indices.len() as u32,
+            pImageIndices: indices.as_ptr(),
+        };
+
+        try!(check_errors(vk.ReleaseSwapchainImagesEXT(self.device.internal_object(), &infos)));
+
+        // The semaphores of the released images stay signalled forever, so we can't put them back
+        // in the pool. We replace them with new ones instead. The caller guarantees that no
+        // submission waits on the old ones, which are destroyed once they are no longer used.
+        for &index in indices {
+            images_semaphores[index as usize] = None;
+            self.semaphores_pool.push(Arc::new(try!(Semaphore::raw(&self.device))));
+        }
+
+        Ok(())
+    }
+
+    /// Associates a submission with an image of the swapchain.
+    ///
+    /// This is meant to be used with the submission that draws on the image, so that
+    /// `wait_for_image` can later wait until the GPU no longer uses the resources that belong to
+    /// that image (command buffers, uniform buffers, etc.) before they are reused for the next
+    /// frame that draws on the same image. This is the usual way to handle multiple frames in
+    /// flight.
+    ///
+    /// Replaces the submission that was previously associated with the image, if any, and
+    /// returns it.
+    ///
+    /// # Panic
+    ///
+    /// - Panicks if `index` is out of range.
+    ///
+    #[inline]
+    pub fn set_image_submission(&self, index: usize, submission: Arc<Submission>)
+                                -> Option<Arc<Submission>>
+    {
+        let mut submissions = self.images_submissions.lock().unwrap();
+        mem::replace(&mut submissions[index], Some(submission))
+    }
+
+    /// Waits until the submission associated with an image with `set_image_submission` has
+    /// finished executing.
+    ///
+    /// Typically you call this function right after `acquire_next_image`, before modifying the
+    /// resources used by the frame that draws on the acquired image. Returns immediately if no
+    /// submission is associated with the image. On success, the submission is no longer
+    /// associated with the image.
+    ///
+    /// # Panic
+    ///
+    /// - Panicks if `index` is out of range.
+    ///
+    pub fn wait_for_image(&self, index: usize, timeout: Duration) -> Result<(), FenceWaitError> {
+        // We don't keep the mutex locked while waiting.
+        let submission = {
+            let submissions = self.images_submissions.lock().unwrap();
+            match submissions[index] {
+                Some(ref s) => s.clone(),
+                None => return Ok(()),
+            }
+        };
+
+        try!(submission.wait(timeout));
+
+        // Another submission may have been associated with the image in the meantime.
+        let mut submissions = self.images_submissions.lock().unwrap();
+        let same = match submissions[index] {
+            Some(ref s) => &**s as *const Submission == &*submission as *const Submission,
+            None => false,
+        };
+        if same {
+            submissions[index] = None;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the timing information of the presentations that have completed since the last
+    /// call to this function.
+    ///
+    /// The `VK_GOOGLE_display_timing` device extension must be enabled, otherwise
+    /// `PresentError::DisplayTimingNotEnabled` is returned. The list is empty if no presentation
+    /// has completed since the last call.
+    pub fn past_presentation_timing(&self) -> Result<Vec<PastPresentationTiming>, PresentError> {
+        if !self.device.loaded_extensions().google_display_timing {
+            return Err(PresentError::DisplayTimingNotEnabled);
+        }
+
+        let vk = self.device.pointers();
+
+        let timings = unsafe {
+            let mut num = 0;
+            try!(check_errors(vk.GetPastPresentationTimingGOOGLE(self.device.internal_object(),
+                                                                 self.swapchain, &mut num,
+                                                                 ptr::null_mut())));
+
+            let mut timings: Vec<vk::PastPresentationTimingGOOGLE> =
+                                                            Vec::with_capacity(num as usize);
+            try!(check_errors(vk.GetPastPresentationTimingGOOGLE(self.device.internal_object(),
+                                                                 self.swapchain, &mut num,
+                                                                 timings.as_mut_ptr())));
+            timings.set_len(num as usize);
+            timings
+        };
+
+        Ok(timings.into_iter().map(|t| {
+            PastPresentationTiming {
+                present_id: t.presentID,
+                desired_present_time: t.desiredPresentTime,
+                actual_present_time: t.actualPresentTime,
+                earliest_present_time: t.earliestPresentTime,
+                present_margin: t.presentMargin,
+            }
+        }).collect())
+    }
+
+    /// Returns the current value of a counter of the surface.
+    ///
+    /// For `SurfaceCounter::Vblank`, this is the number of vertical blanking periods that
+    /// happened on the display since the swapchain was created.
+    ///
+    /// The counter must have been enabled with `SwapchainOptions::surface_counters` when the
+    /// swapchain was created, otherwise `SwapchainCounterError::CounterNotEnabled` is returned.
+    pub fn counter(&self, counter: SurfaceCounter) -> Result<u64, SwapchainCounterError> {
+        if !self.options.surface_counters.contains(&counter) {
+            return Err(SwapchainCounterError::CounterNotEnabled(counter));
+        }
+
+        let vk = self.device.pointers();
+
+        unsafe {
+            let mut value = 0;
+            try!(check_errors(vk.GetSwapchainCounterEXT(self.device.internal_object(),
+                                                        self.swapchain, counter as u32,
+                                                        &mut value)));
+            Ok(value)
+        }
+    }
+
+    /*/// Returns the semaphore that is going to be signalled when the image is going to be ready
+    /// to be drawn upon.
+    ///
+    /// Returns `None` if the image was not acquired first, or was already presented.
+    // TODO: racy, as someone could present the image before using the semaphore
+    #[inline]
+    pub fn image_semaphore(&self, id: u32) -> Option<Arc<Semaphore>> {
         let semaphores = self.images_semaphores.lock().unwrap();
         semaphores[id as usize].as_ref().map(|s| s.clone())
     }*/
     // TODO: the design of this functions depends on https://github.com/KhronosGroup/Vulkan-Docs/issues/155
     #[inline]
-    pub fn image_semaphore(&self, id: u32, semaphore: Arc<Semaphore>) -> Option<Arc<Semaphore>> {
-        let mut semaphores = self.images_semaphores.lock().unwrap();
-        mem::replace(&mut semaphores[id as usize], Some(semaphore))
+    pub fn image_semaphore(&self, id: u32, semaphore: Arc<Semaphore>) -> Option<Arc<Semaphore>> {
+        let mut semaphores = self.images_semaphores.lock().unwrap();
+        mem::replace(&mut semaphores[id as usize], Some(semaphore))
+    }
+
+    /// Returns `count` semaphores that are signalled once the image with the given index is
+    /// ready to be drawn upon.
+    ///
+    /// `vkAcquireNextImageKHR` can only signal one semaphore, which only one queue can wait
+    /// upon. This function lets multiple queues wait for the same acquired image independently,
+    /// for example with semaphores passed to submissions made outside of vulkano. Each returned
+    /// semaphore must be waited upon exactly once.
+    ///
+    /// This is done by submitting to `queue` a batch that contains no command buffer, waits for
+    /// the semaphore of the acquisition and signals the returned semaphores. This has the cost
+    /// of an additional `vkQueueSubmit` call on the CPU, and adds a synchronization point to the
+    /// queue. If a single queue waits for the image, don't use this function.
+    ///
+    /// The command buffers submitted afterwards by vulkano that use the image, and the
+    /// presentation, still wait for the image to be ready as usual. To do so, one more semaphore
+    /// than `count` is signalled, which means that `count` must be lower than `u32::MAX`.
+    ///
+    /// # Panic
+    ///
+    /// - Panicks if the image hasn't been acquired or has already been used by a submission.
+    /// - Panicks if `count` is `u32::MAX`.
+    ///
+    pub fn fan_out_acquire_semaphore(&self, queue: &Arc<Queue>, index: usize, count: u32)
+                                     -> Result<Vec<Arc<Semaphore>>, OomError>
+    {
+        let total = count.checked_add(1).expect("Too many semaphores requested");
+
+        let mut semaphores = Vec::with_capacity(total as usize);
+        for _ in 0 .. total {
+            semaphores.push(Arc::new(try!(Semaphore::raw(&self.device))));
+        }
+
+        let mut images_semaphores = self.images_semaphores.lock().unwrap();
+        let wait_semaphore = images_semaphores[index].take().expect("Trying to fan out the \
+                                                                      semaphore of an image \
+                                                                      that was not acquired");
+        wait_semaphore.consume_temporary_payload();
+        wait_semaphore.debug_track_wait();
+
+        let raw_semaphores = semaphores.iter().map(|s| s.internal_object())
+                                       .collect::<SmallVec<[_; 8]>>();
+        let wait_stage = vk::PIPELINE_STAGE_ALL_COMMANDS_BIT;
+
+        let infos = vk::SubmitInfo {
+            sType: vk::STRUCTURE_TYPE_SUBMIT_INFO,
+            pNext: ptr::null(),
+            waitSemaphoreCount: 1,
+            pWaitSemaphores: &wait_semaphore.internal_object(),
+            pWaitDstStageMask: &wait_stage,
+            commandBufferCount: 0,
+            pCommandBuffers: ptr::null(),
+            signalSemaphoreCount: raw_semaphores.len() as u32,
+            pSignalSemaphores: raw_semaphores.as_ptr(),
+        };
+
+        unsafe {
+            let vk = self.device.pointers();
+            let result = check_errors(vk.QueueSubmit(*queue.internal_object_guard(), 1, &infos,
+                                                     0));
+            if let Err(err) = result {
+                images_semaphores[index] = Some(wait_semaphore);
+                return Err(OomError::from(err));
+            }
+        }
+
+        for semaphore in semaphores.iter() {
+            semaphore.debug_track_signal();
+        }
+
+        // The last semaphore replaces the one of the acquisition, so that the next submission
+        // that uses the image waits for it.
+        images_semaphores[index] = semaphores.pop();
+        self.recycle_semaphore(wait_semaphore);
+        Ok(semaphores)
+    }
+}
+
+/// Tries to acquire an image of a swapchain, and recreates the swapchain with the given
+/// dimensions if it is out of date or suboptimal.
+///
+/// Returns the swapchain that the image belongs to, the list of images of that swapchain if it
+/// has been recreated, and the index of the acquired image. If the swapchain has been recreated,
+/// you must use the new swapchain and the new images from now on.
+///
+/// The swapchain is only recreated once. If acquiring an image from the new swapchain fails as
+/// well, the error is returned.
+///
+/// An image acquired from a suboptimal swapchain can only be given back to the swapchain with
+/// `release_images`. If the `VK_EXT_swapchain_maintenance1` device extension is enabled, the
+/// image is released and the swapchain is recreated. Otherwise the image is returned along with
+/// the suboptimal swapchain, and you should present it and then recreate the swapchain yourself.
+pub fn acquire_next_image_or_recreate(swapchain: &Arc<Swapchain>, timeout: Duration,
+                                      dimensions: [u32; 2])
+                                      -> Result<(Arc<Swapchain>, Option<Vec<Arc<SwapchainImage>>>,
+                                                 usize), AcquireError>
+{
+    match swapchain.acquire_next_image_inner(timeout, 0) {
+        Ok((id, false, _)) => return Ok((swapchain.clone(), None, id)),
+        Ok((id, true, _)) => {
+            if !swapchain.device.loaded_extensions().ext_swapchain_maintenance1 {
+                return Ok((swapchain.clone(), None, id));
+            }
+
+            // The image was just acquired, so neither the GPU nor any submission uses it yet.
+            match unsafe { swapchain.release_images(&[id as u32]) } {
+                Ok(()) => (),
+                Err(ReleaseImagesError::MissingExtension) => unreachable!(),
+                Err(ReleaseImagesError::OomError(err)) => return Err(AcquireError::from(err)),
+                Err(ReleaseImagesError::SurfaceLost) => return Err(AcquireError::SurfaceLost),
+            }
+        },
+        Err(AcquireError::OutOfDate) => (),
+        Err(err) => return Err(err),
+    };
+
+    let (new_swapchain, images) = match swapchain.recreate_with_dimension(dimensions) {
+        Ok(r) => r,
+        Err(err) => return Err(AcquireError::RecreationFailed(err)),
+    };
+
+    let id = try!(new_swapchain.acquire_next_image(timeout));
+    Ok((new_swapchain, Some(images), id))
+}
+
+/// Presents images of multiple swapchains at once.
+///
+/// Each element of `images` is a swapchain and the index of an image of this swapchain, as
+/// returned by `acquire_next_image`. The images must have been acquired first.
+///
+/// Returns one result per swapchain, in the same order as `images`. `Ok(true)` means that the
+/// image has been presented but that the swapchain is suboptimal. Since a problem with one
+/// swapchain doesn't prevent the other images from being presented, this is the only way to know
+/// which of the swapchains needs to be recreated.
+///
+/// # Panic
+///
+/// - Panicks if the same swapchain appears twice in `images`.
+/// - Panicks if one of the swapchains doesn't belong to the same device as the queue.
+/// - Panicks if one of the images was not acquired.
+///
+pub fn present_multiple(queue: &Arc<Queue>, images: &[(&Swapchain, usize)])
+                        -> Vec<Result<bool, PresentError>>
+{
+    for (n, &(swapchain, _)) in images.iter().enumerate() {
+        assert_eq!(queue.device().internal_object(), swapchain.device.internal_object());
+        assert!(images[.. n].iter().all(|&(other, _)| other.swapchain != swapchain.swapchain));
+    }
+
+    let wait_semaphores = images.iter().map(|&(swapchain, index)| {
+        let mut images_semaphores = swapchain.images_semaphores.lock().unwrap();
+        images_semaphores[index].take().expect("Trying to present an image that was not acquired")
+    }).collect::<Vec<_>>();
+
+    let raw_semaphores = wait_semaphores.iter().map(|s| {
+        s.consume_temporary_payload();
+        s.debug_track_wait();
+        s.internal_object()
+    }).collect::<Vec<_>>();
+    for &(swapchain, _) in images.iter() {
+        swapchain.debug_warn_out_of_date();
+    }
+    let raw_swapchains = images.iter().map(|&(s, _)| s.swapchain).collect::<Vec<_>>();
+    let indices = images.iter().map(|&(_, i)| i as u32).collect::<Vec<_>>();
+    let mut results = vec![RESULT_NOT_WRITTEN; images.len()];
+
+    let global_result = unsafe {
+        let vk = queue.device().pointers();
+        let queue = queue.internal_object_guard();
+
+        let infos = vk::PresentInfoKHR {
+            sType: vk::STRUCTURE_TYPE_PRESENT_INFO_KHR,
+            pNext: ptr::null(),
+            waitSemaphoreCount: raw_semaphores.len() as u32,
+            pWaitSemaphores: raw_semaphores.as_ptr(),
+            swapchainCount: raw_swapchains.len() as u32,
+            pSwapchains: raw_swapchains.as_ptr(),
+            pImageIndices: indices.as_ptr(),
+            pResults: results.as_mut_ptr(),
+        };
+
+        vk.QueuePresentKHR(*queue, &infos)
+    };
+
+    // FIXME: the semaphores may be destroyed ; need to return them
+    for (&(swapchain, _), semaphore) in images.iter().zip(wait_semaphores.into_iter()) {
+        swapchain.recycle_semaphore(semaphore);
+    }
+
+    results.into_iter().zip(images.iter()).map(|(result, &(swapchain, _))| {
+        let result = present_multiple_result(result, global_result);
+
+        if result == Err(PresentError::OutOfDate) {
+            swapchain.debug_track_out_of_date();
+        }
+
+        if result == Err(PresentError::SurfaceLost) {
+            swapchain.surface_lost.store(true, Ordering::SeqCst);
+        }
+
+        result
+    }).collect()
+}
+
+// Value that `present_multiple` puts in the individual results before calling
+// `vkQueuePresentKHR`. This is `VK_RESULT_MAX_ENUM`, which is never returned by the
+// implementation.
+const RESULT_NOT_WRITTEN: vk::Result = 0x7fffffff;
+
+// Returns the result of one of the swapchains of `present_multiple`. Some drivers don't write the
+// individual results, in which case the global result of `vkQueuePresentKHR` is used. The
+// global result is only used for the swapchains whose result wasn't written, as it is the worst
+// of the individual results and would otherwise be reported for all the swapchains.
+fn present_multiple_result(result: vk::Result, global_result: vk::Result)
+                           -> Result<bool, PresentError>
+{
+    if result == RESULT_NOT_WRITTEN {
+        present_result(global_result)
+    } else {
+        present_result(result)
+    }
+}
+
+// Turns the result of a presentation into `Ok(true)` if the swapchain is suboptimal, `Ok(false)`
+// if the presentation succeeded, or an error.
+fn present_result(result: vk::Result) -> Result<bool, PresentError> {
+    match try!(check_errors(result)) {
+        Success::Success => Ok(false),
+        Success::Suboptimal => Ok(true),
+        s => panic!("unexpected success value: {:?}", s)
+    }
+}
+
+// Checks that a protected swapchain can be created for the surface.
+fn check_protected(device: &Arc<Device>, surface: &Surface) -> Result<(), SwapchainCreationError> {
+    let physical_device = device.physical_device();
+
+    let v1_1 = Version { major: 1, minor: 1, patch: 0 };
+    if device.instance().api_version() < v1_1 || physical_device.api_version() < v1_1 {
+        return Err(SwapchainCreationError::ProtectedNotSupported);
+    }
+
+    if !device.protected_memory_enabled() {
+        return Err(SwapchainCreationError::ProtectedNotSupported);
+    }
+
+    if !try!(surface.supports_protected(&physical_device)) {
+        return Err(SwapchainCreationError::ProtectedNotSupported);
+    }
+
+    Ok(())
+}
+
+// Returns true if device groups are part of the core API for this device, which is the case if
+// both the instance and the physical device use Vulkan 1.1 or above.
+#[inline]
+fn device_group_is_core(device: &Device) -> bool {
+    let v1_1 = Version { major: 1, minor: 1, patch: 0 };
+    device.instance().api_version() >= v1_1 && device.physical_device().api_version() >= v1_1
+}
+
+// Returns the list of images of a swapchain.
+unsafe fn swapchain_images(device: &Device, swapchain: vk::SwapchainKHR)
+                           -> Result<Vec<vk::Image>, Error>
+{
+    let vk = device.pointers();
+
+    let mut num = 0;
+    try!(check_errors(vk.GetSwapchainImagesKHR(device.internal_object(), swapchain, &mut num,
+                                               ptr::null_mut())));
+
+    let mut images = Vec::with_capacity(num as usize);
+    try!(check_errors(vk.GetSwapchainImagesKHR(device.internal_object(), swapchain, &mut num,
+                                               images.as_mut_ptr())));
+    images.set_len(num as usize);
+    Ok(images)
+}
+
+// State of a swapchain with regard to its recreation.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Staleness {
+    // The swapchain hasn't been passed as the old swapchain of a recreation.
+    Fresh,
+    // A recreation that used this swapchain as the old swapchain has failed. The swapchain is
+    // retired, but can be recreated again without being passed to the implementation.
+    Retired,
+    // The swapchain has been replaced by a new swapchain.
+    Replaced,
+}
+
+// Parameters chosen by `Swapchain::new_auto`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+struct AutoParameters {
+    num_images: u32,
+    format: Format,
+    color_space: ColorSpace,
+    dimensions: [u32; 2],
+    transform: SurfaceTransform,
+    alpha: CompositeAlpha,
+}
+
+// Chooses the parameters of `Swapchain::new_auto` from the capabilities of the surface.
+fn auto_parameters(capabilities: &Capabilities, desired_dimensions: [u32; 2])
+                   -> Result<AutoParameters, SwapchainCreationError>
+{
+    let num_images = match capabilities.max_image_count {
+        Some(max) => cmp::min(capabilities.min_image_count + 1, max),
+        None => capabilities.min_image_count + 1,
+    };
+
+    let (format, color_space) = match capabilities.srgb_format() {
+        Some(f) => f,
+        None => match capabilities.supported_formats.first() {
+            Some(&f) => f,
+            None => return Err(SwapchainCreationError::NoSupportedFormat),
+        },
+    };
+
+    let dimensions = capabilities.current_extent.unwrap_or_else(|| {
+        [
+            cmp::max(capabilities.min_image_extent[0],
+                     cmp::min(capabilities.max_image_extent[0], desired_dimensions[0])),
+            cmp::max(capabilities.min_image_extent[1],
+                     cmp::min(capabilities.max_image_extent[1], desired_dimensions[1])),
+        ]
+    });
+
+    let transform = if capabilities.supported_transforms.supports(SurfaceTransform::Identity) {
+        SurfaceTransform::Identity
+    } else {
+        capabilities.current_transform
+    };
+
+    Ok(AutoParameters {
+        num_images: num_images,
+        format: format,
+        color_space: color_space,
+        dimensions: dimensions,
+        transform: transform,
+        alpha: capabilities.supported_composite_alpha.iter().next().unwrap(),
+    })
+}
+
+// Checks that the parameters of a swapchain creation match the capabilities of the surface.
+// TODO: return errors instead of panicking for the remaining parameters
+fn check_capabilities(capabilities: &Capabilities, num_images: u32, format: Format,
+                      color_space: ColorSpace, dimensions: [u32; 2], layers: u32,
+                      usage: &ImageUsage, transform: SurfaceTransform, alpha: CompositeAlpha,
+                      mode: PresentMode)
+                      -> Result<(), SwapchainCreationError>
+{
+    assert!(capabilities.image_count_range().contains(num_images));
+    assert!(dimensions[0] >= capabilities.min_image_extent[0]);
+    assert!(dimensions[1] >= capabilities.min_image_extent[1]);
+    assert!(dimensions[0] <= capabilities.max_image_extent[0]);
+    assert!(dimensions[1] <= capabilities.max_image_extent[1]);
+    assert!((usage.to_usage_bits() & capabilities.supported_usage_flags.to_usage_bits()) == usage.to_usage_bits());
+
+    if layers == 0 || layers > capabilities.max_image_array_layers {
+        return Err(SwapchainCreationError::UnsupportedArrayLayers {
+            requested: layers,
+            max: capabilities.max_image_array_layers,
+        });
+    }
+
+    if !present_mode_supported(&capabilities.present_modes, mode) {
+        return Err(SwapchainCreationError::UnsupportedPresentMode(mode));
+    }
+
+    let min_image_count = capabilities.min_image_count_for_present_mode(mode);
+    if num_images < min_image_count {
+        return Err(SwapchainCreationError::InsufficientImagesForPresentMode {
+            mode: mode,
+            min: min_image_count,
+            requested: num_images,
+        });
+    }
+
+    if !capabilities.supported_formats.contains(&(format, color_space)) {
+        return Err(SwapchainCreationError::UnsupportedFormat(format, color_space));
+    }
+
+    if !capabilities.supported_transforms.supports(transform) {
+        return Err(SwapchainCreationError::UnsupportedSurfaceTransform(transform));
+    }
+
+    if !capabilities.supported_composite_alpha.contains(alpha) {
+        return Err(SwapchainCreationError::UnsupportedCompositeAlpha(alpha));
+    }
+
+    Ok(())
+}
+
+// Returns true if an image whose views can use `formats` can be bound to the attachment described
+// by `description`.
+fn attachment_compatible(formats: &[Format], description: Option<&LayoutAttachmentDescription>)
+                         -> bool
+{
+    match description {
+        Some(d) => d.samples == 1 && formats.contains(&d.format),
+        None => false,
+    }
+}
+
+// Returns true if `mode` can be used to create a swapchain.
+//
+// The specs guarantee that `Fifo` is always supported. We accept it unconditionally so that
+// swapchain creation doesn't fail on drivers that forget to report it.
+#[inline]
+fn present_mode_supported(modes: &SupportedPresentModes, mode: PresentMode) -> bool {
+    mode == PresentMode::Fifo || modes.supports(mode)
+}
+
+unsafe impl DebugMarkerObject for Swapchain {
+    #[inline]
+    fn debug_report_object_type(&self) -> vk::DebugReportObjectTypeEXT {
+        vk::DEBUG_REPORT_OBJECT_TYPE_SWAPCHAIN_KHR_EXT
+    }
+
+    #[inline]
+    fn debug_marker_handle(&self) -> u64 {
+        self.swapchain
+    }
+
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
+}
+
+impl Drop for Swapchain {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe {
+            let vk = self.device.pointers();
+            vk.DestroySwapchainKHR(self.device.internal_object(), self.swapchain,
+                                   self.device.allocation_callbacks());
+        }
+    }
+}
+
+/// Number of images to create for a swapchain.
+///
+/// In both cases this is only a minimum: the implementation is free to create more images. Use
+/// `Swapchain::num_images` to know how many images were actually created.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ImageCount {
+    /// Request exactly this minimum number of images. Creating the swapchain panics if the
+    /// number is outside of the range supported by the surface.
+    Exact(u32),
+
+    /// Request this minimum number of images if possible. The number is clamped to the range
+    /// supported by the surface for the present mode of the swapchain.
+    ///
+    /// With `Mailbox`, at least three images are requested if the surface allows it, even if
+    /// the implementation doesn't report it, as fewer images make acquiring an image block.
+    Preferred(u32),
+}
+
+impl ImageCount {
+    /// Returns the number of images to request for a surface with these capabilities, when
+    /// using the given present mode.
+    #[inline]
+    pub fn resolve(&self, capabilities: &Capabilities, mode: PresentMode) -> u32 {
+        match *self {
+            ImageCount::Exact(n) => n,
+            ImageCount::Preferred(n) => {
+                let min = preferred_min_image_count(mode,
+                                    capabilities.min_image_count_for_present_mode(mode));
+                capabilities.image_count_range().clamp(cmp::max(n, min))
+            },
+        }
+    }
+}
+
+// Returns the minimum number of images that `ImageCount::Preferred` requests for `mode`, given
+// the minimum reported by the implementation. This is only a preference, as `Mailbox` is valid
+// with fewer images.
+fn preferred_min_image_count(mode: PresentMode, min_image_count: u32) -> u32 {
+    match mode {
+        PresentMode::Mailbox => cmp::max(min_image_count, 3),
+        _ => min_image_count,
+    }
+}
+
+impl From<u32> for ImageCount {
+    #[inline]
+    fn from(num: u32) -> ImageCount {
+        ImageCount::Exact(num)
+    }
+}
+
+/// Additional parameters for the creation of a swapchain, for features that are provided by
+/// extensions.
+///
+/// Use `SwapchainOptions::default()` to get the same behavior as `Swapchain::new`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SwapchainOptions {
+    /// How the images are scaled when their dimensions don't match the dimensions of the
+    /// surface. If `None`, the behavior depends on the platform.
+    ///
+    /// Requires the `VK_EXT_swapchain_maintenance1` device extension.
+    pub scaling_behavior: Option<ScalingBehavior>,
+
+    /// Where the images are placed horizontally and vertically on the surface when they don't
+    /// cover the whole surface. If `None`, the behavior depends on the platform.
+    ///
+    /// Requires the `VK_EXT_swapchain_maintenance1` device extension.
+    pub present_gravity: Option<[PresentGravity; 2]>,
+
+    /// If true, the images of the swapchain are protected, meaning that their content can't be
+    /// read back by the host. This is required to display DRM-protected content on some
+    /// platforms.
+    ///
+    /// The surface must support protected swapchains (see `Surface::supports_protected`), and
+    /// both the instance and the physical device must use Vulkan 1.1 or above. The device must
+    /// also have been created with `Device::with_protected_memory`.
+    pub protected: bool,
+
+    /// List of formats, compatible with the format of the swapchain, that views of the images
+    /// can use. See `SwapchainImage::view_with_format`. This is typically used to draw with an
+    /// sRGB view on a swapchain whose format is UNORM. If empty, views can only use the format
+    /// of the swapchain.
+    ///
+    /// The format of the swapchain is automatically added to the list if it isn't in it.
+    ///
+    /// Requires the `VK_KHR_swapchain_mutable_format` and `VK_KHR_image_format_list` device
+    /// extensions.
+    pub mutable_format_view_formats: Vec<Format>,
+
+    /// List of counters of the surface that can be queried with `Swapchain::counter`. The
+    /// surface must be a display surface that supports these counters.
+    ///
+    /// Requires the `VK_EXT_display_control` device extension.
+    pub surface_counters: Vec<SurfaceCounter>,
+
+    /// Flags passed when creating the swapchain. See the docs of `SwapchainCreateFlags`.
+    ///
+    /// The `protected` and `mutable_format` flags are automatically set if `protected` is true
+    /// or if `mutable_format_view_formats` isn't empty.
+    pub flags: SwapchainCreateFlags,
+}
+
+impl SwapchainOptions {
+    // Returns the flags passed to the implementation, including the ones implied by the other
+    // options.
+    fn create_flags(&self) -> SwapchainCreateFlags {
+        let mut flags = self.flags;
+        flags.protected |= self.protected;
+        flags.mutable_format |= !self.mutable_format_view_formats.is_empty();
+        flags
+    }
+}
+
+/// Flags that can be passed when creating a swapchain.
+///
+/// Each flag is validated against the extensions enabled on the device when the swapchain is
+/// created, and `SwapchainCreationError::MissingExtension` or
+/// `SwapchainCreationError::ProtectedNotSupported` is returned if it can't be used.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct SwapchainCreateFlags {
+    /// The images can be bound to memory with split instance bind regions, for split-frame
+    /// rendering with a device group.
+    ///
+    /// Requires Vulkan 1.1 or the `VK_KHR_device_group` device extension.
+    pub split_instance_bind_regions: bool,
+
+    /// The images are protected. See `SwapchainOptions::protected`.
+    pub protected: bool,
+
+    /// Views of the images can use other formats than the format of the swapchain. See
+    /// `SwapchainOptions::mutable_format_view_formats`. If the list is empty, it is considered
+    /// to contain only the format of the swapchain.
+    ///
+    /// Requires the `VK_KHR_swapchain_mutable_format` and `VK_KHR_image_format_list` device
+    /// extensions.
+    pub mutable_format: bool,
+
+    /// The memory of the images is only allocated when they are acquired for the first time,
+    /// which saves memory for swapchains that may never be used.
+    ///
+    /// An image can't be used before it has been acquired once. This includes building a
+    /// framebuffer that uses it, as views of the images are only created once their memory is
+    /// allocated. See `Swapchain::is_image_allocated`.
+    ///
+    /// Requires the `VK_EXT_swapchain_maintenance1` device extension.
+    pub deferred_memory_allocation: bool,
+}
+
+impl SwapchainCreateFlags {
+    /// Builds a `SwapchainCreateFlags` with all flags set to false.
+    #[inline]
+    pub fn none() -> SwapchainCreateFlags {
+        SwapchainCreateFlags {
+            split_instance_bind_regions: false,
+            protected: false,
+            mutable_format: false,
+            deferred_memory_allocation: false,
+        }
+    }
+
+    #[doc(hidden)]
+    #[inline]
+    pub fn to_bits(&self) -> vk::SwapchainCreateFlagsKHR {
+        let mut result = 0;
+        if self.split_instance_bind_regions {
+            result |= vk::SWAPCHAIN_CREATE_SPLIT_INSTANCE_BIND_REGIONS_BIT_KHR;
+        }
+        if self.protected { result |= vk::SWAPCHAIN_CREATE_PROTECTED_BIT_KHR; }
+        if self.mutable_format { result |= vk::SWAPCHAIN_CREATE_MUTABLE_FORMAT_BIT_KHR; }
+        if self.deferred_memory_allocation {
+            result |= vk::SWAPCHAIN_CREATE_DEFERRED_MEMORY_ALLOCATION_BIT_EXT;
+        }
+        result
+    }
+}
+
+/// How an image is scaled when its dimensions don't match the dimensions of the surface.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum ScalingBehavior {
+    /// The image is not scaled. Each pixel of the image matches a pixel of the surface.
+    OneToOne = vk::PRESENT_SCALING_ONE_TO_ONE_BIT_EXT,
+
+    /// The image is scaled to the largest size that fits in the surface while keeping its
+    /// aspect ratio.
+    AspectRatioStretch = vk::PRESENT_SCALING_ASPECT_RATIO_STRETCH_BIT_EXT,
+
+    /// The image is scaled to the dimensions of the surface.
+    Stretch = vk::PRESENT_SCALING_STRETCH_BIT_EXT,
+}
+
+/// Where an image is placed on an axis of the surface when it doesn't cover the surface.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum PresentGravity {
+    /// The image is aligned with the left or top edge of the surface.
+    Min = vk::PRESENT_GRAVITY_MIN_BIT_EXT,
+
+    /// The image is aligned with the right or bottom edge of the surface.
+    Max = vk::PRESENT_GRAVITY_MAX_BIT_EXT,
+
+    /// The image is centered on the surface.
+    Centered = vk::PRESENT_GRAVITY_CENTERED_BIT_EXT,
+}
+
+/// Timing information about a presentation that has completed.
+///
+/// All the times are in nanoseconds.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PastPresentationTiming {
+    /// Identifier of the presentation that was passed by the application, or 0.
+    pub present_id: u32,
+
+    /// Time at which the application requested the image to be presented, or 0.
+    pub desired_present_time: u64,
+
+    /// Time at which the image was actually shown to the user.
+    pub actual_present_time: u64,
+
+    /// Earliest time at which the image could have been shown to the user.
+    pub earliest_present_time: u64,
+
+    /// How early the image was ready compared to the time it was needed.
+    pub present_margin: u64,
+}
+
+/// Error that can happen when creating a swapchain.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SwapchainCreationError {
+    /// Not enough memory.
+    OomError(OomError),
+
+    /// The connection to the device has been lost.
+    DeviceLost,
+
+    /// The surface is no longer accessible and must be recreated.
+    SurfaceLost,
+
+    /// The surface is already used by another swapchain or by something else than Vulkan.
+    NativeWindowInUse,
+
+    /// An extension required by one of the parameters was not enabled.
+    MissingExtension { name: &'static str },
+
+    /// The old swapchain has already been used to create another swapchain, possibly by another
+    /// thread at the same time. Use the swapchain that was created by the other call instead.
+    AlreadyRecreated,
+
+    /// The requested surface transform isn't in the `supported_transforms` of the surface's
+    /// capabilities.
+    UnsupportedSurfaceTransform(SurfaceTransform),
+
+    /// The requested composite alpha mode isn't in the `supported_composite_alpha` of the
+    /// surface's capabilities.
+    UnsupportedCompositeAlpha(CompositeAlpha),
+
+    /// A protected swapchain was requested, but the surface or the device doesn't support it, or
+    /// the device wasn't created with `Device::with_protected_memory`.
+    ProtectedNotSupported,
+
+    /// The requested pair of format and color space isn't in the `supported_formats` of the
+    /// surface's capabilities.
+    UnsupportedFormat(Format, ColorSpace),
+
+    /// The requested present mode isn't in the `present_modes` of the surface's capabilities.
+    UnsupportedPresentMode(PresentMode),
+
+    /// `new_auto` was called but the `supported_formats` of the surface's capabilities is empty.
+    NoSupportedFormat,
+
+    /// The requested number of array layers is zero or is greater than the
+    /// `max_image_array_layers` of the surface's capabilities.
+    UnsupportedArrayLayers {
+        /// The requested number of layers.
+        requested: u32,
+        /// The maximum number of layers supported by the surface.
+        max: u32,
+    },
+
+    /// The requested number of images is supported by the surface, but is lower than the
+    /// minimum number of images for the requested present mode. See
+    /// `Capabilities::present_mode_min_image_counts`.
+    InsufficientImagesForPresentMode {
+        /// The requested present mode.
+        mode: PresentMode,
+        /// The minimum number of images for this present mode.
+        min: u32,
+        /// The requested number of images.
+        requested: u32,
+    },
+}
+
+impl error::Error for SwapchainCreationError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            SwapchainCreationError::OomError(_) => "not enough memory available",
+            SwapchainCreationError::DeviceLost => "the connection to the device has been lost",
+            SwapchainCreationError::SurfaceLost => "the surface is no longer valid",
+            SwapchainCreationError::NativeWindowInUse => "the surface is already used by another \
+                                                          swapchain",
+            SwapchainCreationError::MissingExtension { .. } => "an extension required by one of \
+                                                                the parameters was not enabled",
+            SwapchainCreationError::AlreadyRecreated => "the old swapchain has already been used \
+                                                         to create another swapchain",
+            SwapchainCreationError::UnsupportedSurfaceTransform(_) => "the requested surface \
+                                                                       transform is not \
+                                                                       supported",
+            SwapchainCreationError::UnsupportedCompositeAlpha(_) => "the requested composite \
+                                                                     alpha mode is not \
+                                                                     supported",
+            SwapchainCreationError::ProtectedNotSupported => "protected swapchains are not \
+                                                              supported",
+            SwapchainCreationError::UnsupportedFormat(_, _) => "the requested format and color \
+                                                                space are not supported together",
+            SwapchainCreationError::UnsupportedPresentMode(_) => "the requested present mode is \
+                                                                  not supported",
+            SwapchainCreationError::NoSupportedFormat => "the surface doesn't support any format",
+            SwapchainCreationError::UnsupportedArrayLayers { .. } => "the requested number of \
+                                                                      array layers is not \
+                                                                      supported",
+            SwapchainCreationError::InsufficientImagesForPresentMode { .. } => {
+                "the requested number of images is too low for the requested present mode"
+            },
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            SwapchainCreationError::OomError(ref err) => Some(err),
+            _ => None
+        }
     }
 }
 
-impl Drop for Swapchain {
+impl fmt::Display for SwapchainCreationError {
     #[inline]
-    fn drop(&mut self) {
-        unsafe {
-            let vk = self.device.pointers();
-            vk.DestroySwapchainKHR(self.device.internal_object(), self.swapchain, ptr::null());
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+impl From<OomError> for SwapchainCreationError {
+    #[inline]
+    fn from(err: OomError) -> SwapchainCreationError {
+        SwapchainCreationError::OomError(err)
+    }
+}
+
+impl From<CapabilitiesError> for SwapchainCreationError {
+    #[inline]
+    fn from(err: CapabilitiesError) -> SwapchainCreationError {
+        match err {
+            CapabilitiesError::OomError(err) => SwapchainCreationError::OomError(err),
+            CapabilitiesError::MissingExtension { name } => {
+                SwapchainCreationError::MissingExtension { name: name }
+            },
+            CapabilitiesError::SurfaceLost => SwapchainCreationError::SurfaceLost,
+        }
+    }
+}
+
+impl From<Error> for SwapchainCreationError {
+    #[inline]
+    fn from(err: Error) -> SwapchainCreationError {
+        match err {
+            err @ Error::OutOfHostMemory => SwapchainCreationError::OomError(OomError::from(err)),
+            err @ Error::OutOfDeviceMemory => SwapchainCreationError::OomError(OomError::from(err)),
+            Error::DeviceLost => SwapchainCreationError::DeviceLost,
+            Error::SurfaceLost => SwapchainCreationError::SurfaceLost,
+            Error::NativeWindowInUse => SwapchainCreationError::NativeWindowInUse,
+            _ => panic!("unexpected error: {:?}", err)
         }
     }
 }
@@ -354,6 +2187,13 @@ pub enum AcquireError {
     /// The surface has changed in a way that makes the swapchain unusable. You must query the
     /// surface's new properties and recreate a new swapchain if you want to continue drawing.
     OutOfDate,
+
+    /// The swapchain needed to be recreated, but the recreation failed.
+    RecreationFailed(SwapchainCreationError),
+
+    /// The implementation returned a result code that vulkano doesn't expect from
+    /// `vkAcquireNextImageKHR`. Contains the raw `VkResult`.
+    Unknown(i32),
 }
 
 impl error::Error for AcquireError {
@@ -365,6 +2205,8 @@ impl error::Error for AcquireError {
             AcquireError::Timeout => "no image is available for acquiring yet",
             AcquireError::SurfaceLost => "the surface of this swapchain is no longer valid",
             AcquireError::OutOfDate => "the swapchain needs to be recreated",
+            AcquireError::RecreationFailed(_) => "failed to recreate the swapchain",
+            AcquireError::Unknown(_) => "the implementation returned an unexpected result code",
         }
     }
 
@@ -372,6 +2214,7 @@ impl error::Error for AcquireError {
     fn cause(&self) -> Option<&error::Error> {
         match *self {
             AcquireError::OomError(ref err) => Some(err),
+            AcquireError::RecreationFailed(ref err) => Some(err),
             _ => None
         }
     }
@@ -380,7 +2223,19 @@ impl error::Error for AcquireError {
 impl fmt::Display for AcquireError {
     #[inline]
     fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
-        write!(fmt, "{}", error::Error::description(self))
+        match *self {
+            AcquireError::Unknown(code) => {
+                write!(fmt, "{} ({})", error::Error::description(self), code)
+            },
+            _ => write!(fmt, "{}", error::Error::description(self)),
+        }
+    }
+}
+
+impl From<OomError> for AcquireError {
+    #[inline]
+    fn from(err: OomError) -> AcquireError {
+        AcquireError::OomError(err)
     }
 }
 
@@ -393,6 +2248,211 @@ impl From<Error> for AcquireError {
             Error::DeviceLost => AcquireError::DeviceLost,
             Error::SurfaceLost => AcquireError::SurfaceLost,
             Error::OutOfDate => AcquireError::OutOfDate,
+            err => AcquireError::Unknown(err as u32 as i32),
+        }
+    }
+}
+
+// Same as `check_errors`, but returns `AcquireError::Unknown` instead of panicking if the result
+// code isn't one that `vkAcquireNextImageKHR` is allowed to return. Some drivers return codes
+// that aren't in the specs.
+fn check_acquire_errors(result: vk::Result) -> Result<Success, AcquireError> {
+    match result {
+        vk::SUCCESS | vk::NOT_READY | vk::TIMEOUT | vk::SUBOPTIMAL_KHR |
+        vk::ERROR_OUT_OF_HOST_MEMORY | vk::ERROR_OUT_OF_DEVICE_MEMORY | vk::ERROR_DEVICE_LOST |
+        vk::ERROR_SURFACE_LOST_KHR | vk::ERROR_OUT_OF_DATE_KHR => {
+            check_errors(result).map_err(AcquireError::from)
+        },
+        c => Err(AcquireError::Unknown(c as i32)),
+    }
+}
+
+/// Error that can happen when calling `Swapchain::counter`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SwapchainCounterError {
+    /// Not enough memory.
+    OomError(OomError),
+
+    /// The connection to the device has been lost.
+    DeviceLost,
+
+    /// The surface has changed in a way that makes the swapchain unusable. You must query the
+    /// surface's new properties and recreate a new swapchain if you want to continue drawing.
+    OutOfDate,
+
+    /// The counter wasn't enabled when the swapchain was created.
+    CounterNotEnabled(SurfaceCounter),
+}
+
+impl error::Error for SwapchainCounterError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            SwapchainCounterError::OomError(_) => "not enough memory",
+            SwapchainCounterError::DeviceLost => "the connection to the device has been lost",
+            SwapchainCounterError::OutOfDate => "the swapchain needs to be recreated",
+            SwapchainCounterError::CounterNotEnabled(_) => "the counter wasn't enabled when the \
+                                                            swapchain was created",
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            SwapchainCounterError::OomError(ref err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for SwapchainCounterError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+impl From<OomError> for SwapchainCounterError {
+    #[inline]
+    fn from(err: OomError) -> SwapchainCounterError {
+        SwapchainCounterError::OomError(err)
+    }
+}
+
+impl From<Error> for SwapchainCounterError {
+    #[inline]
+    fn from(err: Error) -> SwapchainCounterError {
+        match err {
+            err @ Error::OutOfHostMemory => SwapchainCounterError::OomError(OomError::from(err)),
+            err @ Error::OutOfDeviceMemory => SwapchainCounterError::OomError(OomError::from(err)),
+            Error::DeviceLost => SwapchainCounterError::DeviceLost,
+            Error::OutOfDate => SwapchainCounterError::OutOfDate,
+            _ => panic!("unexpected error: {:?}", err)
+        }
+    }
+}
+
+/// Error that can happen when calling `release_images`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ReleaseImagesError {
+    /// Not enough memory.
+    OomError(OomError),
+
+    /// The surface is no longer accessible and must be recreated.
+    SurfaceLost,
+
+    /// The `VK_EXT_swapchain_maintenance1` extension was not enabled.
+    MissingExtension,
+}
+
+impl error::Error for ReleaseImagesError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            ReleaseImagesError::OomError(_) => "not enough memory",
+            ReleaseImagesError::SurfaceLost => "the surface of this swapchain is no longer valid",
+            ReleaseImagesError::MissingExtension => "the `VK_EXT_swapchain_maintenance1` \
+                                                     extension was not enabled",
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            ReleaseImagesError::OomError(ref err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for ReleaseImagesError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+impl From<OomError> for ReleaseImagesError {
+    #[inline]
+    fn from(err: OomError) -> ReleaseImagesError {
+        ReleaseImagesError::OomError(err)
+    }
+}
+
+impl From<Error> for ReleaseImagesError {
+    #[inline]
+    fn from(err: Error) -> ReleaseImagesError {
+        match err {
+            err @ Error::OutOfHostMemory => ReleaseImagesError::OomError(OomError::from(err)),
+            err @ Error::OutOfDeviceMemory => ReleaseImagesError::OomError(OomError::from(err)),
+            Error::SurfaceLost => ReleaseImagesError::SurfaceLost,
+            _ => panic!("unexpected error: {:?}", err)
+        }
+    }
+}
+
+/// Error that can happen when calling `wait_for_present`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PresentWaitError {
+    /// Not enough memory.
+    OomError(OomError),
+
+    /// The connection to the device has been lost.
+    DeviceLost,
+
+    /// The timeout of the function has been reached before the image was presented.
+    Timeout,
+
+    /// The surface is no longer accessible and must be recreated.
+    SurfaceLost,
+
+    /// The surface has changed in a way that makes the swapchain unusable. You must query the
+    /// surface's new properties and recreate a new swapchain if you want to continue drawing.
+    OutOfDate,
+
+    /// The extension required for this function was not enabled.
+    MissingExtension { name: &'static str },
+}
+
+impl error::Error for PresentWaitError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            PresentWaitError::OomError(_) => "not enough memory",
+            PresentWaitError::DeviceLost => "the connection to the device has been lost",
+            PresentWaitError::Timeout => "the image was not presented before the timeout",
+            PresentWaitError::SurfaceLost => "the surface of this swapchain is no longer valid",
+            PresentWaitError::OutOfDate => "the swapchain needs to be recreated",
+            PresentWaitError::MissingExtension { .. } => "the extension required for this \
+                                                          function was not enabled",
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            PresentWaitError::OomError(ref err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for PresentWaitError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+impl From<Error> for PresentWaitError {
+    #[inline]
+    fn from(err: Error) -> PresentWaitError {
+        match err {
+            err @ Error::OutOfHostMemory => PresentWaitError::OomError(OomError::from(err)),
+            err @ Error::OutOfDeviceMemory => PresentWaitError::OomError(OomError::from(err)),
+            Error::DeviceLost => PresentWaitError::DeviceLost,
+            Error::SurfaceLost => PresentWaitError::SurfaceLost,
+            Error::OutOfDate => PresentWaitError::OutOfDate,
             _ => panic!("unexpected error: {:?}", err)
         }
     }
@@ -414,6 +2474,31 @@ pub enum PresentError {
     /// The surface has changed in a way that makes the swapchain unusable. You must query the
     /// surface's new properties and recreate a new swapchain if you want to continue drawing.
     OutOfDate,
+
+    /// `present_incremental` was called but the `VK_KHR_incremental_present` extension was
+    /// not enabled on the device.
+    IncrementalPresentNotEnabled,
+
+    /// `present_with_timing` or `past_presentation_timing` was called but the
+    /// `VK_GOOGLE_display_timing` extension was not enabled on the device.
+    DisplayTimingNotEnabled,
+
+    /// The extension required for this function was not enabled.
+    ///
+    /// This is returned by `present_with_fence` and `present_with_id`. The older
+    /// `present_incremental`, `present_with_timing` and `past_presentation_timing` functions
+    /// return `IncrementalPresentNotEnabled` or `DisplayTimingNotEnabled` instead, which are kept
+    /// for compatibility.
+    MissingExtension { name: &'static str },
+
+    /// The identifier passed to `present_with_id` is not greater than the identifier of the
+    /// previous presentation.
+    NonMonotonicPresentId {
+        /// Identifier of the previous presentation.
+        last: u64,
+        /// Identifier that was passed.
+        requested: u64,
+    },
 }
 
 impl error::Error for PresentError {
@@ -424,6 +2509,14 @@ impl error::Error for PresentError {
             PresentError::DeviceLost => "the connection to the device has been lost",
             PresentError::SurfaceLost => "the surface of this swapchain is no longer valid",
             PresentError::OutOfDate => "the swapchain needs to be recreated",
+            PresentError::IncrementalPresentNotEnabled => "the `VK_KHR_incremental_present` \
+                                                           extension was not enabled",
+            PresentError::DisplayTimingNotEnabled => "the `VK_GOOGLE_display_timing` extension \
+                                                      was not enabled",
+            PresentError::MissingExtension { .. } => "the extension required for this function \
+                                                      was not enabled",
+            PresentError::NonMonotonicPresentId { .. } => "the present identifier is not greater \
+                                                           than the previous one",
         }
     }
 
@@ -456,3 +2549,355 @@ impl From<Error> for PresentError {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use format::Format;
+    use framebuffer::LayoutAttachmentDescription;
+    use framebuffer::LoadOp;
+    use framebuffer::StoreOp;
+    use image::Layout;
+    use image::Usage;
+    use swapchain::AcquireError;
+    use swapchain::Capabilities;
+    use swapchain::CapabilitiesError;
+    use swapchain::ColorSpace;
+    use swapchain::CompositeAlpha;
+    use swapchain::ImageCount;
+    use swapchain::PresentError;
+    use swapchain::PresentMode;
+    use swapchain::SupportedSurfaceTransforms;
+    use swapchain::SurfaceTransform;
+    use swapchain::SwapchainCreateFlags;
+    use swapchain::SwapchainCounterError;
+    use swapchain::SwapchainCreationError;
+    use swapchain::SwapchainOptions;
+    use swapchain::surface::SupportedCompositeAlpha;
+    use swapchain::surface::SupportedPresentModes;
+    use super::attachment_compatible;
+    use super::auto_parameters;
+    use super::check_acquire_errors;
+    use super::check_capabilities;
+    use super::present_mode_supported;
+    use super::RESULT_NOT_WRITTEN;
+    use super::present_multiple_result;
+    use super::present_result;
+    use Error;
+    use vk;
+
+    // Capabilities of a surface that supports the minimum required by the specs.
+    fn basic_capabilities() -> Capabilities {
+        Capabilities {
+            min_image_count: 2,
+            max_image_count: None,
+            present_mode_min_image_counts: vec![(PresentMode::Fifo, 2)],
+            current_extent: Some([800, 600]),
+            min_image_extent: [800, 600],
+            max_image_extent: [800, 600],
+            max_image_array_layers: 1,
+            supported_transforms: SupportedSurfaceTransforms {
+                identity: true, .. SupportedSurfaceTransforms::none()
+            },
+            current_transform: SurfaceTransform::Identity,
+            supported_composite_alpha: SupportedCompositeAlpha {
+                opaque: true, .. SupportedCompositeAlpha::none()
+            },
+            supported_usage_flags: Usage { color_attachment: true, .. Usage::none() },
+            supported_formats: vec![(Format::B8G8R8A8Srgb, ColorSpace::SrgbNonLinear)],
+            present_modes: SupportedPresentModes { fifo: true, .. SupportedPresentModes::none() },
+        }
+    }
+
+    #[test]
+    fn unsupported_surface_transform() {
+        let caps = basic_capabilities();
+        let usage = Usage { color_attachment: true, .. Usage::none() };
+
+        let res = check_capabilities(&caps, 2, Format::B8G8R8A8Srgb, ColorSpace::SrgbNonLinear,
+                                     [800, 600], 1, &usage, SurfaceTransform::Rotate90, CompositeAlpha::Opaque,
+                                     PresentMode::Fifo);
+        assert_eq!(res, Err(SwapchainCreationError::UnsupportedSurfaceTransform(
+                                                                    SurfaceTransform::Rotate90)));
+
+        let res = check_capabilities(&caps, 2, Format::B8G8R8A8Srgb, ColorSpace::SrgbNonLinear,
+                                     [800, 600], 1, &usage, SurfaceTransform::Identity,
+                                     CompositeAlpha::Opaque, PresentMode::Fifo);
+        assert_eq!(res, Ok(()));
+    }
+
+    #[test]
+    fn unsupported_composite_alpha() {
+        let caps = basic_capabilities();
+        assert!(caps.supported_composite_alpha.contains(CompositeAlpha::Opaque));
+        assert!(!caps.supported_composite_alpha.contains(CompositeAlpha::Inherit));
+        let usage = Usage { color_attachment: true, .. Usage::none() };
+
+        let res = check_capabilities(&caps, 2, Format::B8G8R8A8Srgb, ColorSpace::SrgbNonLinear,
+                                     [800, 600], 1, &usage, SurfaceTransform::Identity,
+                                     CompositeAlpha::Inherit, PresentMode::Fifo);
+        assert_eq!(res, Err(SwapchainCreationError::UnsupportedCompositeAlpha(
+                                                                    CompositeAlpha::Inherit)));
+    }
+
+    #[test]
+    fn render_pass_attachment_compatibility() {
+        let description = LayoutAttachmentDescription {
+            format: Format::B8G8R8A8Srgb,
+            samples: 1,
+            load: LoadOp::Clear,
+            store: StoreOp::Store,
+            initial_layout: Layout::Undefined,
+            final_layout: Layout::PresentSrc,
+        };
+
+        assert!(attachment_compatible(&[Format::B8G8R8A8Srgb], Some(&description)));
+        assert!(attachment_compatible(&[Format::B8G8R8A8Unorm, Format::B8G8R8A8Srgb],
+                                      Some(&description)));
+        assert!(!attachment_compatible(&[Format::B8G8R8A8Unorm], Some(&description)));
+        assert!(!attachment_compatible(&[Format::B8G8R8A8Srgb], None));
+
+        let multisampled = LayoutAttachmentDescription { samples: 4, .. description };
+        assert!(!attachment_compatible(&[Format::B8G8R8A8Srgb], Some(&multisampled)));
+    }
+
+    #[test]
+    fn stereo_layers() {
+        let mut caps = basic_capabilities();
+        caps.max_image_array_layers = 2;
+        let usage = Usage { color_attachment: true, .. Usage::none() };
+
+        let res = check_capabilities(&caps, 2, Format::B8G8R8A8Srgb, ColorSpace::SrgbNonLinear,
+                                     [800, 600], 2, &usage, SurfaceTransform::Identity,
+                                     CompositeAlpha::Opaque, PresentMode::Fifo);
+        assert_eq!(res, Ok(()));
+
+        let res = check_capabilities(&caps, 2, Format::B8G8R8A8Srgb, ColorSpace::SrgbNonLinear,
+                                     [800, 600], 3, &usage, SurfaceTransform::Identity,
+                                     CompositeAlpha::Opaque, PresentMode::Fifo);
+        assert_eq!(res, Err(SwapchainCreationError::UnsupportedArrayLayers {
+            requested: 3,
+            max: 2,
+        }));
+
+        let res = check_capabilities(&caps, 2, Format::B8G8R8A8Srgb, ColorSpace::SrgbNonLinear,
+                                     [800, 600], 0, &usage, SurfaceTransform::Identity,
+                                     CompositeAlpha::Opaque, PresentMode::Fifo);
+        assert_eq!(res, Err(SwapchainCreationError::UnsupportedArrayLayers {
+            requested: 0,
+            max: 2,
+        }));
+    }
+
+    #[test]
+    fn unsupported_format_color_space_pair() {
+        let mut caps = basic_capabilities();
+        caps.supported_formats.push((Format::R16G16B16A16Sfloat,
+                                     ColorSpace::ExtendedSrgbLinear));
+        let usage = Usage { color_attachment: true, .. Usage::none() };
+
+        // Both the format and the color space are supported, but not together.
+        let res = check_capabilities(&caps, 2, Format::B8G8R8A8Srgb,
+                                     ColorSpace::ExtendedSrgbLinear, [800, 600], 1, &usage,
+                                     SurfaceTransform::Identity, CompositeAlpha::Opaque,
+                                     PresentMode::Fifo);
+        assert_eq!(res, Err(SwapchainCreationError::UnsupportedFormat(
+                                                Format::B8G8R8A8Srgb,
+                                                ColorSpace::ExtendedSrgbLinear)));
+    }
+
+    #[test]
+    fn create_flags() {
+        let options = SwapchainOptions {
+            protected: true,
+            mutable_format_view_formats: vec![Format::B8G8R8A8Unorm],
+            flags: SwapchainCreateFlags {
+                deferred_memory_allocation: true,
+                .. SwapchainCreateFlags::none()
+            },
+            .. SwapchainOptions::default()
+        };
+
+        let flags = options.create_flags();
+        assert_eq!(flags, SwapchainCreateFlags {
+            protected: true,
+            mutable_format: true,
+            deferred_memory_allocation: true,
+            .. SwapchainCreateFlags::none()
+        });
+        assert_eq!(flags.to_bits(), vk::SWAPCHAIN_CREATE_PROTECTED_BIT_KHR |
+                                    vk::SWAPCHAIN_CREATE_MUTABLE_FORMAT_BIT_KHR |
+                                    vk::SWAPCHAIN_CREATE_DEFERRED_MEMORY_ALLOCATION_BIT_EXT);
+        assert_eq!(SwapchainOptions::default().create_flags(), SwapchainCreateFlags::none());
+    }
+
+    #[test]
+    fn image_count_resolve() {
+        let mut caps = basic_capabilities();
+        caps.min_image_count = 2;
+        caps.max_image_count = Some(2);
+
+        assert_eq!(ImageCount::Preferred(3).resolve(&caps, PresentMode::Fifo), 2);
+        assert_eq!(ImageCount::Preferred(1).resolve(&caps, PresentMode::Fifo), 2);
+        assert_eq!(ImageCount::Exact(3).resolve(&caps, PresentMode::Fifo), 3);
+
+        caps.max_image_count = None;
+        assert_eq!(ImageCount::Preferred(5).resolve(&caps, PresentMode::Fifo), 5);
+        assert_eq!(ImageCount::from(1), ImageCount::Exact(1));
+
+        assert_eq!(ImageCount::Preferred(2).resolve(&caps, PresentMode::Mailbox), 3);
+        assert_eq!(ImageCount::Exact(2).resolve(&caps, PresentMode::Mailbox), 2);
+
+        caps.max_image_count = Some(2);
+        assert_eq!(ImageCount::Preferred(2).resolve(&caps, PresentMode::Mailbox), 2);
+
+        caps.max_image_count = None;
+        caps.present_mode_min_image_counts.push((PresentMode::Mailbox, 4));
+        assert_eq!(ImageCount::Preferred(2).resolve(&caps, PresentMode::Mailbox), 4);
+    }
+
+    #[test]
+    fn mailbox_with_two_images() {
+        let mut caps = basic_capabilities();
+        caps.present_modes.mailbox = true;
+        let usage = Usage { color_attachment: true, .. Usage::none() };
+
+        let res = check_capabilities(&caps, 2, Format::B8G8R8A8Srgb, ColorSpace::SrgbNonLinear,
+                                     [800, 600], 1, &usage, SurfaceTransform::Identity,
+                                     CompositeAlpha::Opaque, PresentMode::Mailbox);
+        assert_eq!(res, Ok(()));
+    }
+
+    #[test]
+    fn insufficient_images_for_present_mode() {
+        let mut caps = basic_capabilities();
+        caps.present_modes.mailbox = true;
+        caps.present_mode_min_image_counts.push((PresentMode::Mailbox, 3));
+        let usage = Usage { color_attachment: true, .. Usage::none() };
+
+        let res = check_capabilities(&caps, 2, Format::B8G8R8A8Srgb, ColorSpace::SrgbNonLinear,
+                                     [800, 600], 1, &usage, SurfaceTransform::Identity,
+                                     CompositeAlpha::Opaque, PresentMode::Mailbox);
+        assert_eq!(res, Err(SwapchainCreationError::InsufficientImagesForPresentMode {
+            mode: PresentMode::Mailbox,
+            min: 3,
+            requested: 2,
+        }));
+
+        let res = check_capabilities(&caps, 3, Format::B8G8R8A8Srgb, ColorSpace::SrgbNonLinear,
+                                     [800, 600], 1, &usage, SurfaceTransform::Identity,
+                                     CompositeAlpha::Opaque, PresentMode::Mailbox);
+        assert_eq!(res, Ok(()));
+    }
+
+    #[test]
+    fn auto_parameters_basic() {
+        let params = auto_parameters(&basic_capabilities(), [1024, 768]).unwrap();
+        assert_eq!(params.num_images, 3);
+        assert_eq!(params.format, Format::B8G8R8A8Srgb);
+        assert_eq!(params.color_space, ColorSpace::SrgbNonLinear);
+        assert_eq!(params.dimensions, [800, 600]);
+        assert_eq!(params.transform, SurfaceTransform::Identity);
+        assert_eq!(params.alpha, CompositeAlpha::Opaque);
+    }
+
+    #[test]
+    fn auto_parameters_clamped() {
+        let mut caps = basic_capabilities();
+        caps.max_image_count = Some(2);
+        caps.current_extent = None;
+        caps.max_image_extent = [1000, 1000];
+        caps.supported_formats = vec![(Format::B8G8R8A8Unorm, ColorSpace::SrgbNonLinear),
+                                      (Format::R8G8B8A8Srgb, ColorSpace::SrgbNonLinear)];
+        caps.supported_transforms = SupportedSurfaceTransforms {
+            rotate90: true, .. SupportedSurfaceTransforms::none()
+        };
+        caps.current_transform = SurfaceTransform::Rotate90;
+
+        let params = auto_parameters(&caps, [1024, 700]).unwrap();
+        assert_eq!(params.num_images, 2);
+        assert_eq!(params.format, Format::R8G8B8A8Srgb);
+        assert_eq!(params.dimensions, [1000, 700]);
+        assert_eq!(params.transform, SurfaceTransform::Rotate90);
+    }
+
+    #[test]
+    fn auto_parameters_no_format() {
+        let mut caps = basic_capabilities();
+        caps.supported_formats = Vec::new();
+        assert_eq!(auto_parameters(&caps, [1024, 768]),
+                   Err(SwapchainCreationError::NoSupportedFormat));
+    }
+
+    #[test]
+    fn acquire_unknown_result() {
+        assert_eq!(check_acquire_errors(vk::ERROR_OUT_OF_DATE_KHR).err(),
+                   Some(AcquireError::OutOfDate));
+        assert_eq!(check_acquire_errors(vk::ERROR_VALIDATION_FAILED_EXT).err(),
+                   Some(AcquireError::Unknown(vk::ERROR_VALIDATION_FAILED_EXT as i32)));
+        assert_eq!(check_acquire_errors(-12345i32 as u32).err(),
+                   Some(AcquireError::Unknown(-12345)));
+        assert_eq!(format!("{}", AcquireError::Unknown(-12345)),
+                   "the implementation returned an unexpected result code (-12345)");
+    }
+
+    #[test]
+    fn capabilities_error_conversion() {
+        let err = CapabilitiesError::MissingExtension {
+            name: "VK_KHR_surface_protected_capabilities"
+        };
+        assert_eq!(SwapchainCreationError::from(err), SwapchainCreationError::MissingExtension {
+            name: "VK_KHR_surface_protected_capabilities"
+        });
+        assert_eq!(SwapchainCreationError::from(CapabilitiesError::SurfaceLost),
+                   SwapchainCreationError::SurfaceLost);
+    }
+
+    #[test]
+    fn counter_error_conversion() {
+        assert_eq!(SwapchainCounterError::from(Error::OutOfDate),
+                   SwapchainCounterError::OutOfDate);
+        assert_eq!(SwapchainCounterError::from(Error::DeviceLost),
+                   SwapchainCounterError::DeviceLost);
+    }
+
+    #[test]
+    fn present_result_codes() {
+        assert_eq!(present_result(vk::SUCCESS), Ok(false));
+        assert_eq!(present_result(vk::SUBOPTIMAL_KHR), Ok(true));
+        assert_eq!(present_result(vk::ERROR_OUT_OF_DATE_KHR), Err(PresentError::OutOfDate));
+        assert_eq!(present_result(vk::ERROR_SURFACE_LOST_KHR),
+                   Err(PresentError::SurfaceLost));
+    }
+
+    #[test]
+    fn present_multiple_results() {
+        // The status of one swapchain isn't reported for the others.
+        assert_eq!(present_multiple_result(vk::SUCCESS, vk::ERROR_OUT_OF_DATE_KHR), Ok(false));
+        assert_eq!(present_multiple_result(vk::SUBOPTIMAL_KHR, vk::SUBOPTIMAL_KHR), Ok(true));
+        assert_eq!(present_multiple_result(vk::ERROR_OUT_OF_DATE_KHR, vk::ERROR_OUT_OF_DATE_KHR),
+                   Err(PresentError::OutOfDate));
+
+        // The global result is used if the driver didn't write the individual result.
+        assert_eq!(present_multiple_result(RESULT_NOT_WRITTEN, vk::ERROR_OUT_OF_DATE_KHR),
+                   Err(PresentError::OutOfDate));
+        assert_eq!(present_multiple_result(RESULT_NOT_WRITTEN, vk::SUCCESS), Ok(false));
+    }
+
+    #[test]
+    fn unsupported_present_mode() {
+        let caps = basic_capabilities();
+        let usage = Usage { color_attachment: true, .. Usage::none() };
+
+        let res = check_capabilities(&caps, 2, Format::B8G8R8A8Srgb, ColorSpace::SrgbNonLinear,
+                                     [800, 600], 1, &usage, SurfaceTransform::Identity,
+                                     CompositeAlpha::Opaque, PresentMode::Mailbox);
+        assert_eq!(res, Err(SwapchainCreationError::UnsupportedPresentMode(PresentMode::Mailbox)));
+    }
+
+    #[test]
+    fn fifo_always_supported() {
+        let modes = SupportedPresentModes::none();
+        assert!(present_mode_supported(&modes, PresentMode::Fifo));
+        assert!(!present_mode_supported(&modes, PresentMode::Mailbox));
+    }
+}
@@ -0,0 +1,134 @@
+// Copyright (c) 2016 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::sync::Arc;
+
+use command_buffer::PrimaryCommandBuffer;
+use command_buffer::Submission;
+use command_buffer::submit_multiple;
+use device::Queue;
+use swapchain::PresentError;
+use swapchain::Swapchain;
+use swapchain::present_multiple;
+
+use OomError;
+
+/// Accumulates command buffer executions and presentations, then sends them all to a queue at
+/// once.
+///
+/// Flushing the batch calls `vkQueueSubmit` once for all the command buffers, then
+/// `vkQueuePresentKHR` once for all the presentations. This is cheaper than calling `submit` and
+/// `present` for each of them.
+///
+/// The command buffers are executed in the order in which they were added, and the
+/// presentations happen after all the command buffers.
+///
+/// # Example
+///
+/// ```ignore
+/// let (submissions, presents) = try!(SubmitBatch::new(&queue)
+///     .execute(&shadow_pass)
+///     .execute(&main_pass)
+///     .present(&swapchain, image_num)
+///     .flush());
+/// ```
+pub struct SubmitBatch<'a> {
+    queue: Arc<Queue>,
+    command_buffers: Vec<Arc<PrimaryCommandBuffer>>,
+    presents: Vec<(&'a Swapchain, usize)>,
+}
+
+impl<'a> SubmitBatch<'a> {
+    /// Builds a new empty batch that will be flushed on `queue`.
+    #[inline]
+    pub fn new(queue: &Arc<Queue>) -> SubmitBatch<'a> {
+        SubmitBatch {
+            queue: queue.clone(),
+            command_buffers: Vec::new(),
+            presents: Vec::new(),
+        }
+    }
+
+    /// Adds a command buffer to execute.
+    #[inline]
+    pub fn execute(mut self, cmd: &Arc<PrimaryCommandBuffer>) -> SubmitBatch<'a> {
+        self.command_buffers.push(cmd.clone());
+        self
+    }
+
+    /// Adds an image to present, after all the command buffers have been executed.
+    ///
+    /// The index is the same as what `acquire_next_image` returned.
+    #[inline]
+    pub fn present(mut self, swapchain: &'a Swapchain, index: usize) -> SubmitBatch<'a> {
+        self.presents.push((swapchain, index));
+        self
+    }
+
+    /// Returns the queue that the batch will be flushed on.
+    #[inline]
+    pub fn queue(&self) -> &Arc<Queue> {
+        &self.queue
+    }
+
+    /// Returns true if nothing has been added to the batch.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.command_buffers.is_empty() && self.presents.is_empty()
+    }
+
+    /// Submits all the command buffers then presents all the images.
+    ///
+    /// Returns one `Submission` per command buffer and one presentation result per image, in
+    /// the order in which they were added. See `present_multiple` for the meaning of the
+    /// presentation results.
+    ///
+    /// If the submission fails, nothing is presented.
+    ///
+    /// # Panic
+    ///
+    /// - Panicks if one of the command buffers can't be submitted to the queue (see `submit`).
+    /// - Panicks if the same swapchain has been added twice, or if one of the images wasn't
+    ///   acquired (see `present_multiple`).
+    ///
+    pub fn flush(self) -> Result<(Vec<Arc<Submission>>, Vec<Result<bool, PresentError>>),
+                                 OomError>
+    {
+        let submissions = if !self.command_buffers.is_empty() {
+            try!(submit_multiple(&self.command_buffers, &self.queue))
+        } else {
+            Vec::new()
+        };
+
+        let presents = if !self.presents.is_empty() {
+            present_multiple(&self.queue, &self.presents)
+        } else {
+            Vec::new()
+        };
+
+        Ok((submissions, presents))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sync::SubmitBatch;
+
+    #[test]
+    fn empty_batch() {
+        let (_, queue) = gfx_dev_and_queue!();
+
+        let batch = SubmitBatch::new(&queue);
+        assert!(batch.is_empty());
+
+        let (submissions, presents) = batch.flush().unwrap();
+        assert!(submissions.is_empty());
+        assert!(presents.is_empty());
+    }
+}
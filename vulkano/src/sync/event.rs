@@ -8,7 +8,6 @@
 // according to those terms.
 
 use std::mem;
-use std::ptr;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::sync::MutexGuard;
@@ -50,7 +49,7 @@ impl Event {
 
             let mut output = mem::uninitialized();
             try!(check_errors(vk.CreateEvent(device.internal_object(), &INFOS,
-                                             ptr::null(), &mut output)));
+                                             device.allocation_callbacks(), &mut output)));
             output
         };
 
@@ -149,7 +148,8 @@ impl Drop for Event {
         unsafe {
             let vk = self.device.pointers();
             let event = self.event.lock().unwrap();
-            vk.DestroyEvent(self.device.internal_object(), *event, ptr::null());
+            vk.DestroyEvent(self.device.internal_object(), *event,
+                            self.device.allocation_callbacks());
         }
     }
 }
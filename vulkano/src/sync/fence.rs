@@ -42,6 +42,10 @@ pub struct Fence<D = Arc<Device>> where D: SafeDeref<Target = Device> {
     // This variable exists so that we don't need to call `vkGetFenceStatus` or `vkWaitForFences`
     // multiple times.
     signaled: AtomicBool,
+
+    // If true, then the fence is put back in the device's fence pool when it is destroyed
+    // instead of being destroyed.
+    must_put_in_pool: bool,
 }
 
 impl<D> Fence<D> where D: SafeDeref<Target = Device> {
@@ -87,6 +91,61 @@ impl<D> Fence<D> where D: SafeDeref<Target = Device> {
         Arc::new(Fence::signaled_raw(device).unwrap())
     }
 
+    /// Takes a fence from the device's fence pool, or builds a new one if the pool is empty.
+    ///
+    /// The fence is always returned in the unsignaled state. If it is recycled from the pool,
+    /// it is reset before being returned. When the fence is destroyed, it is put back in the
+    /// pool instead of being destroyed.
+    pub fn from_pool(device: &D) -> Result<Fence<D>, OomError>
+        where D: Clone
+    {
+        let maybe_raw_fence = device.fence_pool().lock().unwrap().pop();
+
+        match maybe_raw_fence {
+            Some(raw_fence) => {
+                unsafe {
+                    // Make sure the fence isn't signaled.
+                    let vk = device.pointers();
+                    try!(check_errors(vk.ResetFences(device.internal_object(), 1, &raw_fence)));
+                }
+
+                Ok(Fence {
+                    fence: raw_fence,
+                    device: device.clone(),
+                    signaled: AtomicBool::new(false),
+                    must_put_in_pool: true,
+                })
+            },
+            None => {
+                let mut fence = try!(Fence::new_impl(device, false));
+                fence.must_put_in_pool = true;
+                Ok(fence)
+            },
+        }
+    }
+
+    /// Builds a `Fence` from a fence handle that was created by the Vulkan implementation on
+    /// behalf of the device, for example by `vkRegisterDisplayEventEXT`.
+    ///
+    /// The fence is assumed to be unsignaled and is destroyed when the `Fence` is dropped.
+    ///
+    /// # Safety
+    ///
+    /// - `fence` must be a valid fence of `device`, and ownership of it is transferred.
+    ///
+    #[doc(hidden)]
+    #[inline]
+    pub unsafe fn from_raw(device: &D, fence: vk::Fence) -> Fence<D>
+        where D: Clone
+    {
+        Fence {
+            fence: fence,
+            device: device.clone(),
+            signaled: AtomicBool::new(false),
+            must_put_in_pool: false,
+        }
+    }
+
     fn new_impl(device: &D, signaled: bool) -> Result<Fence<D>, OomError>
         where D: Clone
     {
@@ -101,7 +160,7 @@ impl<D> Fence<D> where D: SafeDeref<Target = Device> {
 
             let mut output = mem::uninitialized();
             try!(check_errors(vk.CreateFence(device.internal_object(), &infos,
-                                             ptr::null(), &mut output)));
+                                             device.allocation_callbacks(), &mut output)));
             output
         };
 
@@ -109,12 +168,22 @@ impl<D> Fence<D> where D: SafeDeref<Target = Device> {
             fence: fence,
             device: device.clone(),
             signaled: AtomicBool::new(signaled),
+            must_put_in_pool: false,
         })
     }
 
+    /// Returns the device that owns this fence.
+    #[inline]
+    pub fn device(&self) -> &D {
+        &self.device
+    }
+
     /// Returns true if the fence is signaled.
+    ///
+    /// Contrary to `wait`, this function never blocks. This makes it possible to poll the status
+    /// of a submission from a frame loop and to do other work in the meanwhile.
     #[inline]
-    pub fn ready(&self) -> Result<bool, OomError> {
+    pub fn is_signaled(&self) -> Result<bool, OomError> {
         unsafe {
             if self.signaled.load(Ordering::Relaxed) { return Ok(true); }
 
@@ -132,6 +201,12 @@ impl<D> Fence<D> where D: SafeDeref<Target = Device> {
         }
     }
 
+    /// Same as `is_signaled`, which should be preferred. This name is kept for compatibility.
+    #[inline]
+    pub fn ready(&self) -> Result<bool, OomError> {
+        self.is_signaled()
+    }
+
     /// Waits until the fence is signaled, or at least until the number of nanoseconds of the
     /// timeout has elapsed.
     ///
@@ -220,29 +295,48 @@ impl<D> Fence<D> where D: SafeDeref<Target = Device> {
     ///
     /// # Panic
     ///
-    /// Panicks if not all fences belong to the same device.
+    /// - Panicks if not all fences belong to the same device.
+    /// - Panicks if the device or host ran out of memory.
+    ///
     pub fn multi_reset<'a, I>(iter: I)
         where I: IntoIterator<Item = &'a Fence<D>>, D: 'a
     {
-        let mut device: Option<&Device> = None;
+        let fences: SmallVec<[&Fence<D>; 8]> = iter.into_iter().collect();
+
+        match Fence::reset_multiple(&fences) {
+            Ok(()) => (),
+            Err(FenceResetError::DifferentDevices) => {
+                panic!("Tried to reset multiple fences that didn't belong to the same device")
+            },
+            Err(err) => panic!("{}", err),
+        }
+    }
 
-        let fences: SmallVec<[vk::Fence; 8]> = iter.into_iter().map(|fence| {
-            match &mut device {
-                dev @ &mut None => *dev = Some(&*fence.device),
-                &mut Some(ref dev) if &**dev as *const Device == &*fence.device as *const Device => {},
-                _ => panic!("Tried to reset multiple fences that didn't belong to the same device"),
-            };
+    /// Resets multiple fences with a single call to the driver.
+    ///
+    /// Returns an error and doesn't reset any fence if not all fences belong to the same device.
+    pub fn reset_multiple(fences: &[&Fence<D>]) -> Result<(), FenceResetError> {
+        let device: &Device = match fences.first() {
+            Some(fence) => &*fence.device,
+            None => return Ok(()),
+        };
+
+        if fences.iter().any(|f| &*f.device as *const Device != device as *const Device) {
+            return Err(FenceResetError::DifferentDevices);
+        }
 
+        let raw_fences: SmallVec<[vk::Fence; 8]> = fences.iter().map(|fence| {
             fence.signaled.store(false, Ordering::Relaxed);
             fence.fence
         }).collect();
 
-        if let Some(device) = device {
-            unsafe {
-                let vk = device.pointers();
-                vk.ResetFences(device.internal_object(), fences.len() as u32, fences.as_ptr());
-            }
+        unsafe {
+            let vk = device.pointers();
+            try!(check_errors(vk.ResetFences(device.internal_object(), raw_fences.len() as u32,
+                                             raw_fences.as_ptr())));
         }
+
+        Ok(())
     }
 }
 
@@ -258,9 +352,15 @@ unsafe impl<D> VulkanObject for Fence<D> where D: SafeDeref<Target = Device> {
 impl<D> Drop for Fence<D> where D: SafeDeref<Target = Device> {
     #[inline]
     fn drop(&mut self) {
+        if self.must_put_in_pool {
+            self.device.fence_pool().lock().unwrap().push(self.fence);
+            return;
+        }
+
         unsafe {
             let vk = self.device.pointers();
-            vk.DestroyFence(self.device.internal_object(), self.fence, ptr::null());
+            vk.DestroyFence(self.device.internal_object(), self.fence,
+                            self.device.allocation_callbacks());
         }
     }
 }
@@ -316,10 +416,66 @@ impl From<Error> for FenceWaitError {
     }
 }
 
+/// Error that can be returned when resetting multiple fences.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FenceResetError {
+    /// Not enough memory to reset the fences.
+    OomError(OomError),
+
+    /// Not all the fences belong to the same device.
+    DifferentDevices,
+}
+
+impl error::Error for FenceResetError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            FenceResetError::OomError(_) => "no memory available",
+            FenceResetError::DifferentDevices => "not all the fences belong to the same device",
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            FenceResetError::OomError(ref err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for FenceResetError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+impl From<OomError> for FenceResetError {
+    #[inline]
+    fn from(err: OomError) -> FenceResetError {
+        FenceResetError::OomError(err)
+    }
+}
+
+impl From<Error> for FenceResetError {
+    #[inline]
+    fn from(err: Error) -> FenceResetError {
+        match err {
+            Error::OutOfHostMemory => FenceResetError::OomError(From::from(err)),
+            Error::OutOfDeviceMemory => FenceResetError::OomError(From::from(err)),
+            _ => panic!("Unexpected error value: {}", err as i32)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::time::Duration;
+    use std::sync::atomic::Ordering;
     use sync::Fence;
+    use sync::FenceResetError;
+    use VulkanObject;
 
     #[test]
     #[ignore]       // TODO: fails on AMD + Windows
@@ -338,6 +494,15 @@ mod tests {
         assert!(fence.ready().unwrap());
     }
 
+    #[test]
+    fn fence_is_signaled() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        let fence = Fence::signaled(&device);
+        assert!(fence.is_signaled().unwrap());
+        assert!(fence.is_signaled().unwrap());
+    }
+
     #[test]
     fn fence_signaled_wait() {
         let (device, _) = gfx_dev_and_queue!();
@@ -356,6 +521,57 @@ mod tests {
         assert!(!fence.ready().unwrap());
     }
 
+    #[test]
+    fn reset_multiple_empty() {
+        let fences: &[&Fence] = &[];
+        assert_eq!(Fence::reset_multiple(fences), Ok(()));
+    }
+
+    #[test]
+    fn reset_multiple_different_devices() {
+        let (device1, _) = gfx_dev_and_queue!();
+        let (device2, _) = gfx_dev_and_queue!();
+
+        let fence1 = Fence::signaled(&device1);
+        let fence2 = Fence::signaled(&device2);
+
+        match Fence::reset_multiple(&[&*fence1, &*fence2]) {
+            Err(FenceResetError::DifferentDevices) => (),
+            _ => panic!()
+        }
+    }
+
+    #[test]
+    fn fence_pool() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        assert_eq!(device.fence_pool().lock().unwrap().len(), 0);
+        let fence1_internal_obj = {
+            let fence = Fence::from_pool(&device).unwrap();
+            assert_eq!(device.fence_pool().lock().unwrap().len(), 0);
+            fence.internal_object()
+        };
+
+        assert_eq!(device.fence_pool().lock().unwrap().len(), 1);
+        let fence2 = Fence::from_pool(&device).unwrap();
+        assert_eq!(device.fence_pool().lock().unwrap().len(), 0);
+        assert_eq!(fence2.internal_object(), fence1_internal_obj);
+    }
+
+    #[test]
+    fn fence_pool_reset_on_reuse() {
+        let (device, _) = gfx_dev_and_queue!();
+
+        {
+            let mut fence = Fence::signaled_raw(&device).unwrap();
+            fence.must_put_in_pool = true;
+        }
+
+        let fence = Fence::from_pool(&device).unwrap();
+        assert!(!fence.signaled.load(Ordering::Relaxed));
+        assert!(!fence.ready().unwrap());
+    }
+
     #[test]
     #[should_panic = "Tried to wait for multiple fences that didn't belong to the same device"]
     fn multiwait_different_devices() {
@@ -22,14 +22,26 @@ use std::sync::Arc;
 use device::Queue;
 use vk;
 
+pub use self::batch::SubmitBatch;
 pub use self::event::Event;
 pub use self::fence::Fence;
+pub use self::fence::FenceResetError;
 pub use self::fence::FenceWaitError;
+pub use self::semaphore::ExternalSemaphoreError;
+pub use self::semaphore::ExternalSemaphoreHandleType;
 pub use self::semaphore::Semaphore;
-
+pub use self::timeline::TimelineError;
+pub use self::timeline::TimelineSemaphore;
+#[doc(hidden)]
+pub use self::timeline::timeline_feature_required;
+#[cfg(windows)]
+pub use self::semaphore::Win32Handle;
+
+mod batch;
 mod event;
 mod fence;
 mod semaphore;
+mod timeline;
 
 /// Base trait for objects that can be used as resources and must be synchronized.
 // TODO: remove
@@ -7,11 +7,29 @@
 // notice may not be copied, modified, or distributed except
 // according to those terms.
 
+use std::error;
+use std::fmt;
 use std::mem;
+use std::os::raw::c_void;
 use std::ptr;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+#[cfg(unix)]
+use std::fs::File;
+#[cfg(unix)]
+use std::os::unix::io::FromRawFd;
+#[cfg(unix)]
+use std::os::unix::io::RawFd;
+#[cfg(windows)]
+use std::marker::PhantomData;
 
+use debug_marker;
+use debug_marker::DebugMarkerError;
+use debug_marker::DebugMarkerObject;
 use device::Device;
+use instance::PhysicalDevice;
+use instance::Version;
+use Error;
 use OomError;
 use VulkanObject;
 use VulkanPointers;
@@ -22,35 +40,71 @@ use vk;
 /// 
 /// It is similar to a fence, except that it is purely on the GPU side. The CPU can't query a
 /// semaphore's status or wait for it to be signaled.
+///
+/// In debug builds, vulkano keeps track of the operations that signal and wait on each semaphore
+/// in order to detect an operation that waits on a semaphore that no prior operation signals.
+/// Such a wait would never complete and would hang the GPU. If the `log` feature is enabled, a
+/// warning is logged when this happens. Otherwise nothing is reported.
+///
+/// # Reuse
+///
+/// A semaphore can't be reset from the CPU. It returns to the unsignaled state only when an
+/// operation that waits on it is executed. Before reusing a semaphore for a new signal
+/// operation, for example after getting it back from a pool, you must make sure that every
+/// previous signal operation has been waited on. If nothing else needs to wait on it, submit an
+/// empty batch that waits on the semaphore. Signaling a semaphore that is already signaled is
+/// invalid and can hang the GPU.
 #[derive(Debug)]
 pub struct Semaphore {
     device: Arc<Device>,
     semaphore: vk::Semaphore,
+
+    // True if an operation that signals the semaphore has been submitted and no operation that
+    // waits on it has been submitted since then.
+    #[cfg(debug_assertions)]
+    pending_signal: AtomicBool,
+
+    // True if a payload has been imported temporarily and no operation that waits on the
+    // semaphore has been submitted since then.
+    temporary_payload: AtomicBool,
+
+    // List of handle types that the payload of this semaphore can be exported to. Empty if the
+    // semaphore wasn't created with `exportable`.
+    exportable_to: Vec<ExternalSemaphoreHandleType>,
 }
 
 impl Semaphore {
     /// See the docs of new().
     #[inline]
     pub fn raw(device: &Arc<Device>) -> Result<Semaphore, OomError> {
-        let vk = device.pointers();
+        unsafe { Semaphore::raw_impl(device, 0, ptr::null(), Vec::new()) }
+    }
 
-        let semaphore = unsafe {
-            // since the creation is constant, we use a `static` instead of a struct on the stack
-            static mut INFOS: vk::SemaphoreCreateInfo = vk::SemaphoreCreateInfo {
-                sType: vk::STRUCTURE_TYPE_SEMAPHORE_CREATE_INFO,
-                pNext: 0 as *const _,   // ptr::null()
-                flags: 0,   // reserved
-            };
+    // Creates a semaphore with the given creation flags and `pNext` chain. `exportable_to` must
+    // match the handle types of the `VkExportSemaphoreCreateInfoKHR` in the chain, if any.
+    unsafe fn raw_impl(device: &Arc<Device>, flags: vk::SemaphoreCreateFlags,
+                       p_next: *const c_void, exportable_to: Vec<ExternalSemaphoreHandleType>)
+                       -> Result<Semaphore, OomError>
+    {
+        let vk = device.pointers();
 
-            let mut output = mem::uninitialized();
-            try!(check_errors(vk.CreateSemaphore(device.internal_object(), &INFOS,
-                                                 ptr::null(), &mut output)));
-            output
+        let infos = vk::SemaphoreCreateInfo {
+            sType: vk::STRUCTURE_TYPE_SEMAPHORE_CREATE_INFO,
+            pNext: p_next,
+            flags: flags,
         };
 
+        let mut output = mem::uninitialized();
+        try!(check_errors(vk.CreateSemaphore(device.internal_object(), &infos,
+                                             device.allocation_callbacks(), &mut output)));
+
         Ok(Semaphore {
             device: device.clone(),
-            semaphore: semaphore,
+            semaphore: output,
+            #[cfg(debug_assertions)]
+            pending_signal: AtomicBool::new(false),
+            temporary_payload: AtomicBool::new(false),
+            exportable_to: exportable_to,
         })
     }
     
@@ -64,6 +118,444 @@ impl Semaphore {
     pub fn new(device: &Arc<Device>) -> Arc<Semaphore> {
         Arc::new(Semaphore::raw(device).unwrap())
     }
+
+    /// Builds a new semaphore and gives it a debug name.
+    ///
+    /// The `VK_EXT_debug_marker` device extension must be enabled.
+    ///
+    /// # Panic
+    ///
+    /// - Panicks if `name` contains a nul character.
+    ///
+    pub fn alloc_named(device: &Arc<Device>, name: &str) -> Result<Semaphore, DebugMarkerError> {
+        let semaphore = try!(Semaphore::raw(device));
+        try!(semaphore.set_object_name(name));
+        Ok(semaphore)
+    }
+
+    /// Builds a new semaphore whose payload can be exported to the given handle types.
+    ///
+    /// The physical device must support exporting to each of the handle types. If both the
+    /// instance and the physical device use Vulkan 1.1 or above, this functionality is part of
+    /// the core API. Otherwise the `VK_KHR_external_semaphore` device extension and the
+    /// `VK_KHR_external_semaphore_capabilities` instance extension must be enabled.
+    ///
+    /// Note that exporting or importing file descriptors and Windows handles always requires the
+    /// corresponding `VK_KHR_external_semaphore_fd` or `VK_KHR_external_semaphore_win32`
+    /// extension, as they haven't been promoted to the core API.
+    pub fn exportable(device: &Arc<Device>, handle_types: &[ExternalSemaphoreHandleType])
+                      -> Result<Semaphore, ExternalSemaphoreError>
+    {
+        if !external_semaphore_is_core(&device.physical_device()) &&
+           !device.loaded_extensions().khr_external_semaphore
+        {
+            return Err(ExternalSemaphoreError::MissingExtension {
+                name: "VK_KHR_external_semaphore"
+            });
+        }
+
+        for &handle_type in handle_types {
+            let features = try!(external_semaphore_features(&device.physical_device(),
+                                                            handle_type));
+            if (features & vk::EXTERNAL_SEMAPHORE_FEATURE_EXPORTABLE_BIT_KHR) == 0 {
+                return Err(ExternalSemaphoreError::ExportNotSupported(handle_type));
+            }
+        }
+
+        let export_infos = vk::ExportSemaphoreCreateInfoKHR {
+            sType: vk::STRUCTURE_TYPE_EXPORT_SEMAPHORE_CREATE_INFO_KHR,
+            pNext: ptr::null(),
+            handleTypes: handle_types.iter().fold(0, |bits, &ty| bits | ty as u32),
+        };
+
+        let semaphore = unsafe {
+            try!(Semaphore::raw_impl(device, 0,
+                                     &export_infos as *const vk::ExportSemaphoreCreateInfoKHR
+                                                   as *const _,
+                                     handle_types.to_vec()))
+        };
+
+        Ok(semaphore)
+    }
+
+    /// Builds a new semaphore whose payload is imported from a POSIX file descriptor.
+    ///
+    /// This is a shortcut for creating a semaphore and calling `import_fd` on it. Payloads of
+    /// type `SyncFd` can only be imported temporarily, and are therefore consumed by the first
+    /// wait operation on the semaphore. Payloads of type `OpaqueFd` are imported permanently.
+    ///
+    /// # Panic
+    ///
+    /// - Panicks if `handle_type` is neither `OpaqueFd` nor `SyncFd`.
+    ///
+    /// # Safety
+    ///
+    /// See the docs of `import_fd`.
+    #[cfg(unix)]
+    pub unsafe fn from_fd(device: &Arc<Device>, handle_type: ExternalSemaphoreHandleType,
+                          fd: RawFd) -> Result<Semaphore, ExternalSemaphoreError>
+    {
+        let semaphore = try!(Semaphore::raw(device));
+        let temporary = handle_type == ExternalSemaphoreHandleType::SyncFd;
+        try!(semaphore.import_fd(handle_type, fd, temporary));
+        Ok(semaphore)
+    }
+
+    /// Imports a payload from a POSIX file descriptor into this semaphore.
+    ///
+    /// If `temporary` is true, the imported payload replaces the current payload of the semaphore
+    /// only until the next wait operation, after which the semaphore reverts to its permanent
+    /// payload.
+    ///
+    /// The `VK_KHR_external_semaphore_fd` device extension must be enabled, even on Vulkan 1.1
+    /// where the rest of the external semaphore functionality is part of the core API. If the
+    /// physical device can't import payloads of this handle type, `ImportNotSupported` is
+    /// returned.
+    ///
+    /// # Panic
+    ///
+    /// - Panicks if `handle_type` is neither `OpaqueFd` nor `SyncFd`.
+    /// - Panicks if `handle_type` is `SyncFd` and `temporary` is false.
+    ///
+    /// # Safety
+    ///
+    /// - The file descriptor must be a valid payload of the given handle type.
+    /// - On success, the ownership of the file descriptor is transferred to the Vulkan
+    ///   implementation and it must no longer be used by the caller.
+    /// - The semaphore must not be in use by the GPU.
+    #[cfg(unix)]
+    pub unsafe fn import_fd(&self, handle_type: ExternalSemaphoreHandleType, fd: RawFd,
+                            temporary: bool) -> Result<(), ExternalSemaphoreError>
+    {
+        assert!(handle_type == ExternalSemaphoreHandleType::OpaqueFd ||
+                handle_type == ExternalSemaphoreHandleType::SyncFd);
+        assert!(handle_type != ExternalSemaphoreHandleType::SyncFd || temporary);
+
+        if !self.device.loaded_extensions().khr_external_semaphore_fd {
+            return Err(ExternalSemaphoreError::MissingExtension {
+                name: "VK_KHR_external_semaphore_fd"
+            });
+        }
+
+        try!(check_importable(&self.device.physical_device(), handle_type));
+
+        let vk = self.device.pointers();
+
+        let infos = vk::ImportSemaphoreFdInfoKHR {
+            sType: vk::STRUCTURE_TYPE_IMPORT_SEMAPHORE_FD_INFO_KHR,
+            pNext: ptr::null(),
+            semaphore: self.semaphore,
+            flags: if temporary { vk::SEMAPHORE_IMPORT_TEMPORARY_BIT_KHR } else { 0 },
+            handleType: handle_type as u32,
+            fd: fd,
+        };
+
+        try!(check_errors(vk.ImportSemaphoreFdKHR(self.device.internal_object(), &infos)));
+
+        // We have no way to know the state of the imported payload. Assume that it is or will be
+        // signalled by the exporter.
+        self.debug_track_signal();
+        self.temporary_payload.store(temporary, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Exports the payload of this semaphore to a POSIX file descriptor.
+    ///
+    /// The semaphore must have been created with `exportable` with `handle_type` in the list.
+    /// The `VK_KHR_external_semaphore_fd` device extension must be enabled.
+    ///
+    /// The caller owns the returned file descriptor, and must either close it or transfer its
+    /// ownership, for example by importing it in another semaphore. Prefer `export_fd_owned`,
+    /// which closes the file descriptor automatically.
+    ///
+    /// Exporting a `SyncFd` payload has the same effect on the semaphore as a wait operation.
+    ///
+    /// # Panic
+    ///
+    /// - Panicks if `handle_type` is neither `OpaqueFd` nor `SyncFd`.
+    ///
+    #[cfg(unix)]
+    pub fn export_fd(&self, handle_type: ExternalSemaphoreHandleType)
+                     -> Result<RawFd, ExternalSemaphoreError>
+    {
+        assert!(handle_type == ExternalSemaphoreHandleType::OpaqueFd ||
+                handle_type == ExternalSemaphoreHandleType::SyncFd);
+
+        if !self.device.loaded_extensions().khr_external_semaphore_fd {
+            return Err(ExternalSemaphoreError::MissingExtension {
+                name: "VK_KHR_external_semaphore_fd"
+            });
+        }
+
+        if !self.exportable_to.contains(&handle_type) {
+            return Err(ExternalSemaphoreError::ExportNotSupported(handle_type));
+        }
+
+        let vk = self.device.pointers();
+
+        let fd = unsafe {
+            let infos = vk::SemaphoreGetFdInfoKHR {
+                sType: vk::STRUCTURE_TYPE_SEMAPHORE_GET_FD_INFO_KHR,
+                pNext: ptr::null(),
+                semaphore: self.semaphore,
+                handleType: handle_type as u32,
+            };
+
+            let mut output = mem::uninitialized();
+            try!(check_errors(vk.GetSemaphoreFdKHR(self.device.internal_object(), &infos,
+                                                   &mut output)));
+            output
+        };
+
+        Ok(fd)
+    }
+
+    /// Same as `export_fd`, but wraps the file descriptor in a `File`, which closes it when it
+    /// is dropped.
+    ///
+    /// Use `IntoRawFd::into_raw_fd` to get the file descriptor back, for example to import it
+    /// in another semaphore.
+    ///
+    /// # Panic
+    ///
+    /// - Panicks if `handle_type` is neither `OpaqueFd` nor `SyncFd`.
+    ///
+    #[cfg(unix)]
+    #[inline]
+    pub fn export_fd_owned(&self, handle_type: ExternalSemaphoreHandleType)
+                           -> Result<File, ExternalSemaphoreError>
+    {
+        let fd = try!(self.export_fd(handle_type));
+        // The file descriptor was just created by the implementation, and we own it.
+        Ok(unsafe { File::from_raw_fd(fd) })
+    }
+
+    /// Exports the payload of this semaphore to a Windows handle.
+    ///
+    /// The semaphore must have been created with `exportable` with `handle_type` in the list.
+    /// The `VK_KHR_external_semaphore_win32` device extension must be enabled.
+    ///
+    /// The ownership rules of the returned handle depend on its type. See the docs of
+    /// `Win32Handle`.
+    ///
+    /// # Panic
+    ///
+    /// - Panicks if `handle_type` is neither `OpaqueWin32`, `OpaqueWin32Kmt` nor `D3D12Fence`.
+    ///
+    #[cfg(windows)]
+    pub fn export_win32_handle(&self, handle_type: ExternalSemaphoreHandleType)
+                               -> Result<Win32Handle, ExternalSemaphoreError>
+    {
+        assert!(handle_type.is_win32());
+
+        if !self.device.loaded_extensions().khr_external_semaphore_win32 {
+            return Err(ExternalSemaphoreError::MissingExtension {
+                name: "VK_KHR_external_semaphore_win32"
+            });
+        }
+
+        if !self.exportable_to.contains(&handle_type) {
+            return Err(ExternalSemaphoreError::ExportNotSupported(handle_type));
+        }
+
+        let vk = self.device.pointers();
+
+        let handle = unsafe {
+            let infos = vk::SemaphoreGetWin32HandleInfoKHR {
+                sType: vk::STRUCTURE_TYPE_SEMAPHORE_GET_WIN32_HANDLE_INFO_KHR,
+                pNext: ptr::null(),
+                semaphore: self.semaphore,
+                handleType: handle_type as u32,
+            };
+
+            let mut output = mem::uninitialized();
+            try!(check_errors(vk.GetSemaphoreWin32HandleKHR(self.device.internal_object(),
+                                                            &infos, &mut output)));
+            output
+        };
+
+        Ok(Win32Handle {
+            handle: handle,
+            handle_type: handle_type,
+            marker: PhantomData,
+        })
+    }
+
+    /// Imports a payload from a Windows handle into this semaphore.
+    ///
+    /// If `temporary` is true, the imported payload replaces the current payload of the semaphore
+    /// only until the next wait operation, after which the semaphore reverts to its permanent
+    /// payload.
+    ///
+    /// Contrary to file descriptors, importing a Windows handle never transfers its ownership to
+    /// the Vulkan implementation:
+    ///
+    /// - An `OpaqueWin32` or `D3D12Fence` handle is an NT handle. It stays owned by the caller,
+    ///   who must close it with `CloseHandle` once it is no longer needed. This can be done right
+    ///   after this function returns.
+    /// - An `OpaqueWin32Kmt` handle is a global share handle. It isn't reference-counted and is
+    ///   owned by whoever exported it. It must never be closed, and is only valid as long as the
+    ///   payload it designates exists.
+    ///
+    /// The `VK_KHR_external_semaphore_win32` device extension must be enabled.
+    ///
+    /// # Panic
+    ///
+    /// - Panicks if `handle_type` is neither `OpaqueWin32`, `OpaqueWin32Kmt` nor `D3D12Fence`.
+    ///
+    /// # Safety
+    ///
+    /// - The handle must be a valid payload of the given handle type.
+    /// - The semaphore must not be in use by the GPU.
+    #[cfg(windows)]
+    pub unsafe fn import_win32_handle(&self, handle_type: ExternalSemaphoreHandleType,
+                                      handle: *mut c_void, temporary: bool)
+                                      -> Result<(), ExternalSemaphoreError>
+    {
+        assert!(handle_type.is_win32());
+
+        if !self.device.loaded_extensions().khr_external_semaphore_win32 {
+            return Err(ExternalSemaphoreError::MissingExtension {
+                name: "VK_KHR_external_semaphore_win32"
+            });
+        }
+
+        try!(check_importable(&self.device.physical_device(), handle_type));
+
+        let vk = self.device.pointers();
+
+        let infos = vk::ImportSemaphoreWin32HandleInfoKHR {
+            sType: vk::STRUCTURE_TYPE_IMPORT_SEMAPHORE_WIN32_HANDLE_INFO_KHR,
+            pNext: ptr::null(),
+            semaphore: self.semaphore,
+            flags: if temporary { vk::SEMAPHORE_IMPORT_TEMPORARY_BIT_KHR } else { 0 },
+            handleType: handle_type as u32,
+            handle: handle,
+            name: ptr::null(),
+        };
+
+        try!(check_errors(vk.ImportSemaphoreWin32HandleKHR(self.device.internal_object(),
+                                                           &infos)));
+
+        // See `import_fd`.
+        self.debug_track_signal();
+        self.temporary_payload.store(temporary, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Returns the list of handle types that the payload of this semaphore can be exported to.
+    ///
+    /// The list is empty if the semaphore wasn't created with `exportable`.
+    #[inline]
+    pub fn exportable_handle_types(&self) -> &[ExternalSemaphoreHandleType] {
+        &self.exportable_to
+    }
+
+    /// Returns true if the semaphore currently holds a payload that was imported temporarily
+    /// with `import_fd` or `import_win32_handle`.
+    ///
+    /// A temporary payload is consumed by the next operation that waits on the semaphore, after
+    /// which the semaphore reverts to its permanent payload and this function returns false
+    /// again. A permanent import isn't reported, as it replaces the payload of the semaphore for
+    /// good.
+    #[inline]
+    pub fn has_imported_payload(&self) -> bool {
+        self.temporary_payload.load(Ordering::SeqCst)
+    }
+
+    /// Gives a debug name to this semaphore.
+    ///
+    /// See the docs of `debug_marker::set_name`.
+    #[inline]
+    pub fn set_object_name(&self, name: &str) -> Result<(), DebugMarkerError> {
+        debug_marker::set_name(self, name)
+    }
+
+    /// Returns true if an operation that signals this semaphore has been submitted and no
+    /// operation that waits on it has been submitted since then. Such a semaphore must not be
+    /// reused for a new signal operation.
+    ///
+    /// Always returns false in release builds.
+    #[doc(hidden)]
+    #[inline]
+    pub fn debug_has_pending_signal(&self) -> bool {
+        #[cfg(debug_assertions)]
+        fn pending(semaphore: &Semaphore) -> bool {
+            semaphore.pending_signal.load(Ordering::SeqCst)
+        }
+
+        #[cfg(not(debug_assertions))]
+        fn pending(_: &Semaphore) -> bool {
+            false
+        }
+
+        pending(self)
+    }
+
+    /// Records that an operation that signals this semaphore has been submitted.
+    ///
+    /// Does nothing in release builds.
+    #[doc(hidden)]
+    #[inline]
+    pub fn debug_track_signal(&self) {
+        #[cfg(debug_assertions)]
+        self.pending_signal.store(true, Ordering::SeqCst);
+    }
+
+    /// Records that an operation that waits on this semaphore has been submitted, which releases
+    /// the temporary payload of the semaphore, if any.
+    ///
+    /// Must be called in all builds for every wait operation that is submitted.
+    #[doc(hidden)]
+    #[inline]
+    pub fn consume_temporary_payload(&self) {
+        self.temporary_payload.store(false, Ordering::SeqCst);
+    }
+
+    /// Records that an operation that waits on this semaphore has been submitted, and logs a
+    /// warning if no operation that signals the semaphore was submitted before.
+    ///
+    /// Returns false if no signal operation was submitted before. Always returns true in release
+    /// builds. The warning is only logged if the `log` feature is enabled.
+    #[doc(hidden)]
+    #[inline]
+    pub fn debug_track_wait(&self) -> bool {
+        #[cfg(debug_assertions)]
+        fn track(semaphore: &Semaphore) -> bool {
+            if semaphore.pending_signal.swap(false, Ordering::SeqCst) {
+                return true;
+            }
+
+            #[cfg(feature = "log")]
+            warn!("Waiting on semaphore {:#x} which is never signalled; this will hang the GPU",
+                  semaphore.semaphore);
+            false
+        }
+
+        #[cfg(not(debug_assertions))]
+        fn track(_: &Semaphore) -> bool {
+            true
+        }
+
+        track(self)
+    }
+}
+
+unsafe impl DebugMarkerObject for Semaphore {
+    #[inline]
+    fn debug_report_object_type(&self) -> vk::DebugReportObjectTypeEXT {
+        vk::DEBUG_REPORT_OBJECT_TYPE_SEMAPHORE_EXT
+    }
+
+    #[inline]
+    fn debug_marker_handle(&self) -> u64 {
+        self.semaphore
+    }
+
+    #[inline]
+    fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
 }
 
 unsafe impl VulkanObject for Semaphore {
@@ -80,18 +572,370 @@ impl Drop for Semaphore {
     fn drop(&mut self) {
         unsafe {
             let vk = self.device.pointers();
-            vk.DestroySemaphore(self.device.internal_object(), self.semaphore, ptr::null());
+            vk.DestroySemaphore(self.device.internal_object(), self.semaphore,
+                                self.device.allocation_callbacks());
+        }
+    }
+}
+
+/// Type of handle that the payload of a semaphore can be exported to or imported from.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum ExternalSemaphoreHandleType {
+    /// POSIX file descriptor that only has meaning for Vulkan and compatible APIs.
+    OpaqueFd = vk::EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_FD_BIT_KHR,
+    /// NT handle that only has meaning for Vulkan and compatible APIs.
+    OpaqueWin32 = vk::EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_WIN32_BIT_KHR,
+    /// Global share handle that only has meaning for Vulkan and compatible APIs.
+    OpaqueWin32Kmt = vk::EXTERNAL_SEMAPHORE_HANDLE_TYPE_OPAQUE_WIN32_KMT_BIT_KHR,
+    /// NT handle to a Direct3D 12 fence.
+    D3D12Fence = vk::EXTERNAL_SEMAPHORE_HANDLE_TYPE_D3D12_FENCE_BIT_KHR,
+    /// POSIX file descriptor of a Linux sync file.
+    SyncFd = vk::EXTERNAL_SEMAPHORE_HANDLE_TYPE_SYNC_FD_BIT_KHR,
+}
+
+impl ExternalSemaphoreHandleType {
+    /// Returns true if handles of this type are Windows handles.
+    #[inline]
+    pub fn is_win32(&self) -> bool {
+        match *self {
+            ExternalSemaphoreHandleType::OpaqueWin32 => true,
+            ExternalSemaphoreHandleType::OpaqueWin32Kmt => true,
+            ExternalSemaphoreHandleType::D3D12Fence => true,
+            _ => false,
+        }
+    }
+}
+
+/// Windows handle exported from a semaphore with `Semaphore::export_win32_handle`.
+///
+/// Who owns the handle depends on its type:
+///
+/// - `OpaqueWin32` and `D3D12Fence` handles are NT handles that belong to you. Call `into_owned`
+///   to take the handle, then close it with `CloseHandle` once you no longer need it. Dropping
+///   the `Win32Handle` without calling `into_owned` leaks the handle.
+/// - `OpaqueWin32Kmt` handles are global share handles. They aren't reference-counted and belong
+///   to the Vulkan implementation. They stay valid only as long as the payload of the semaphore
+///   exists, which is why this object borrows the semaphore. They must never be closed, and
+///   `into_owned` refuses to hand them out.
+#[cfg(windows)]
+#[derive(Debug)]
+pub struct Win32Handle<'a> {
+    handle: *mut c_void,
+    handle_type: ExternalSemaphoreHandleType,
+    marker: PhantomData<&'a Semaphore>,
+}
+
+#[cfg(windows)]
+impl<'a> Win32Handle<'a> {
+    /// Returns the raw handle.
+    ///
+    /// You must not close the handle returned by this function.
+    #[inline]
+    pub fn handle(&self) -> *mut c_void {
+        self.handle
+    }
+
+    /// Returns the type of the handle.
+    #[inline]
+    pub fn handle_type(&self) -> ExternalSemaphoreHandleType {
+        self.handle_type
+    }
+
+    /// Returns true if the handle belongs to the caller and must be closed by it.
+    ///
+    /// Returns false for `OpaqueWin32Kmt` handles.
+    #[inline]
+    pub fn is_owned(&self) -> bool {
+        self.handle_type != ExternalSemaphoreHandleType::OpaqueWin32Kmt
+    }
+
+    /// Takes the ownership of the handle. You are then responsible for closing it.
+    ///
+    /// Returns `None` for `OpaqueWin32Kmt` handles, as they must never be closed.
+    #[inline]
+    pub fn into_owned(self) -> Option<*mut c_void> {
+        if self.is_owned() {
+            Some(self.handle)
+        } else {
+            None
+        }
+    }
+}
+
+// Queries the external semaphore features supported by a physical device for a handle type.
+fn external_semaphore_features(physical_device: &PhysicalDevice,
+                               handle_type: ExternalSemaphoreHandleType)
+                               -> Result<vk::ExternalSemaphoreFeatureFlagsKHR,
+                                         ExternalSemaphoreError>
+{
+    let instance = physical_device.instance();
+
+    // The functionality is core in Vulkan 1.1. We only need the extension if either the instance
+    // or the physical device doesn't support it.
+    let core = external_semaphore_is_core(physical_device);
+    if !core && !instance.loaded_extensions().khr_external_semaphore_capabilities {
+        return Err(ExternalSemaphoreError::MissingExtension {
+            name: "VK_KHR_external_semaphore_capabilities"
+        });
+    }
+
+    unsafe {
+        let vk = instance.pointers();
+
+        let infos = vk::PhysicalDeviceExternalSemaphoreInfoKHR {
+            sType: vk::STRUCTURE_TYPE_PHYSICAL_DEVICE_EXTERNAL_SEMAPHORE_INFO_KHR,
+            pNext: ptr::null(),
+            handleType: handle_type as u32,
+        };
+
+        let mut output = vk::ExternalSemaphorePropertiesKHR {
+            sType: vk::STRUCTURE_TYPE_EXTERNAL_SEMAPHORE_PROPERTIES_KHR,
+            pNext: ptr::null_mut(),
+            exportFromImportedHandleTypes: 0,
+            compatibleHandleTypes: 0,
+            externalSemaphoreFeatures: 0,
+        };
+
+        if core {
+            vk.GetPhysicalDeviceExternalSemaphoreProperties(physical_device.internal_object(),
+                                                            &infos, &mut output);
+        } else {
+            vk.GetPhysicalDeviceExternalSemaphorePropertiesKHR(physical_device.internal_object(),
+                                                               &infos, &mut output);
+        }
+
+        Ok(output.externalSemaphoreFeatures)
+    }
+}
+
+// Returns `ImportNotSupported` if the physical device can't import semaphore payloads of this
+// handle type.
+fn check_importable(physical_device: &PhysicalDevice, handle_type: ExternalSemaphoreHandleType)
+                    -> Result<(), ExternalSemaphoreError>
+{
+    let features = try!(external_semaphore_features(physical_device, handle_type));
+    check_importable_features(features, handle_type)
+}
+
+// Returns `ImportNotSupported` if `features`, the external semaphore features of a handle type,
+// don't allow importing payloads of this handle type.
+fn check_importable_features(features: vk::ExternalSemaphoreFeatureFlagsKHR,
+                             handle_type: ExternalSemaphoreHandleType)
+                             -> Result<(), ExternalSemaphoreError>
+{
+    if (features & vk::EXTERNAL_SEMAPHORE_FEATURE_IMPORTABLE_BIT_KHR) == 0 {
+        return Err(ExternalSemaphoreError::ImportNotSupported(handle_type));
+    }
+    Ok(())
+}
+
+// Returns true if external semaphores are part of the core API for this physical device, which
+// is the case if both the instance and the physical device use Vulkan 1.1 or above. The fd and
+// win32 handle types are not concerned, as they are still only provided by extensions.
+#[inline]
+fn external_semaphore_is_core(physical_device: &PhysicalDevice) -> bool {
+    let v1_1 = Version { major: 1, minor: 1, patch: 0 };
+    physical_device.instance().api_version() >= v1_1 && physical_device.api_version() >= v1_1
+}
+
+/// Error that can happen when creating, exporting or importing an external semaphore.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ExternalSemaphoreError {
+    /// Not enough memory.
+    OomError(OomError),
+
+    /// The extension required for this function was not enabled.
+    MissingExtension { name: &'static str },
+
+    /// The physical device doesn't support exporting a semaphore payload to this handle type.
+    ExportNotSupported(ExternalSemaphoreHandleType),
+
+    /// The physical device doesn't support importing a semaphore payload from this handle type.
+    ImportNotSupported(ExternalSemaphoreHandleType),
+
+    /// The external handle isn't a valid payload for this handle type.
+    InvalidExternalHandle,
+}
+
+impl error::Error for ExternalSemaphoreError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            ExternalSemaphoreError::OomError(_) => "not enough memory available",
+            ExternalSemaphoreError::MissingExtension { .. } => "the extension required for this \
+                                                                function was not enabled",
+            ExternalSemaphoreError::ExportNotSupported(_) => "exporting to this handle type is \
+                                                              not supported",
+            ExternalSemaphoreError::ImportNotSupported(_) => "importing from this handle type is \
+                                                              not supported",
+            ExternalSemaphoreError::InvalidExternalHandle => "the external handle is not valid",
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            ExternalSemaphoreError::OomError(ref err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for ExternalSemaphoreError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+impl From<OomError> for ExternalSemaphoreError {
+    #[inline]
+    fn from(err: OomError) -> ExternalSemaphoreError {
+        ExternalSemaphoreError::OomError(err)
+    }
+}
+
+impl From<Error> for ExternalSemaphoreError {
+    #[inline]
+    fn from(err: Error) -> ExternalSemaphoreError {
+        match err {
+            err @ Error::OutOfHostMemory => ExternalSemaphoreError::OomError(OomError::from(err)),
+            err @ Error::OutOfDeviceMemory => ExternalSemaphoreError::OomError(OomError::from(err)),
+            Error::InvalidExternalHandle => ExternalSemaphoreError::InvalidExternalHandle,
+            _ => panic!("unexpected error: {:?}", err)
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use debug_marker::DebugMarkerError;
+    use sync::ExternalSemaphoreError;
+    use sync::ExternalSemaphoreHandleType;
     use sync::Semaphore;
+    use super::check_importable_features;
+    use vk;
 
     #[test]
     fn semaphore_create() {
         let (device, _) = gfx_dev_and_queue!();
         let _ = Semaphore::new(&device);
     }
+
+    #[test]
+    fn ext_debug_marker_missing() {
+        let (device, _) = gfx_dev_and_queue!();
+        match Semaphore::alloc_named(&device, "semaphore") {
+            Err(DebugMarkerError::MissingExtension) => (),
+            _ => panic!()
+        }
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn debug_track_wait_without_signal() {
+        let (device, _) = gfx_dev_and_queue!();
+        let semaphore = Semaphore::new(&device);
+        assert!(!semaphore.debug_track_wait());
+        semaphore.debug_track_signal();
+        assert!(semaphore.debug_track_wait());
+        assert!(!semaphore.debug_track_wait());
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    fn debug_pending_signal() {
+        let (device, _) = gfx_dev_and_queue!();
+        let semaphore = Semaphore::new(&device);
+        assert!(!semaphore.debug_has_pending_signal());
+        semaphore.debug_track_signal();
+        assert!(semaphore.debug_has_pending_signal());
+        semaphore.debug_track_wait();
+        assert!(!semaphore.debug_has_pending_signal());
+    }
+
+    #[test]
+    fn not_exportable_by_default() {
+        let (device, _) = gfx_dev_and_queue!();
+        let semaphore = Semaphore::new(&device);
+        assert!(semaphore.exportable_handle_types().is_empty());
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn export_fd_owned_ext_missing() {
+        let (device, _) = gfx_dev_and_queue!();
+        let semaphore = Semaphore::new(&device);
+
+        match semaphore.export_fd_owned(ExternalSemaphoreHandleType::OpaqueFd) {
+            Err(ExternalSemaphoreError::MissingExtension { .. }) => (),
+            _ => panic!()
+        }
+    }
+
+    #[test]
+    fn no_imported_payload_by_default() {
+        let (device, _) = gfx_dev_and_queue!();
+        let semaphore = Semaphore::new(&device);
+        assert!(!semaphore.has_imported_payload());
+    }
+
+    #[test]
+    fn khr_external_semaphore_ext_missing() {
+        let (device, _) = gfx_dev_and_queue!();
+        match Semaphore::exportable(&device, &[ExternalSemaphoreHandleType::OpaqueFd]) {
+            Err(ExternalSemaphoreError::MissingExtension { .. }) => (),
+            _ => panic!()
+        }
+    }
+
+    #[test]
+    fn win32_handle_types() {
+        assert!(ExternalSemaphoreHandleType::OpaqueWin32.is_win32());
+        assert!(ExternalSemaphoreHandleType::OpaqueWin32Kmt.is_win32());
+        assert!(ExternalSemaphoreHandleType::D3D12Fence.is_win32());
+        assert!(!ExternalSemaphoreHandleType::OpaqueFd.is_win32());
+        assert!(!ExternalSemaphoreHandleType::SyncFd.is_win32());
+    }
+
+    #[test]
+    #[cfg(windows)]
+    fn khr_external_semaphore_win32_ext_missing() {
+        let (device, _) = gfx_dev_and_queue!();
+        let semaphore = Semaphore::new(&device);
+        let kmt = ExternalSemaphoreHandleType::OpaqueWin32Kmt;
+        match semaphore.export_win32_handle(kmt) {
+            Err(ExternalSemaphoreError::MissingExtension { .. }) => (),
+            _ => panic!()
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn khr_external_semaphore_fd_ext_missing() {
+        let (device, _) = gfx_dev_and_queue!();
+        match unsafe { Semaphore::from_fd(&device, ExternalSemaphoreHandleType::OpaqueFd, -1) } {
+            Err(ExternalSemaphoreError::MissingExtension { .. }) => (),
+            _ => panic!()
+        }
+    }
+
+    #[test]
+    fn import_not_supported() {
+        let sync_fd = ExternalSemaphoreHandleType::SyncFd;
+        assert_eq!(check_importable_features(0, sync_fd),
+                   Err(ExternalSemaphoreError::ImportNotSupported(sync_fd)));
+        assert_eq!(check_importable_features(vk::EXTERNAL_SEMAPHORE_FEATURE_EXPORTABLE_BIT_KHR,
+                                             sync_fd),
+                   Err(ExternalSemaphoreError::ImportNotSupported(sync_fd)));
+    }
+
+    #[test]
+    fn import_supported() {
+        let features = vk::EXTERNAL_SEMAPHORE_FEATURE_EXPORTABLE_BIT_KHR |
+                       vk::EXTERNAL_SEMAPHORE_FEATURE_IMPORTABLE_BIT_KHR;
+        assert_eq!(check_importable_features(features, ExternalSemaphoreHandleType::OpaqueFd),
+                   Ok(()));
+    }
 }
@@ -0,0 +1,362 @@
+// Copyright (c) 2016 The vulkano developers
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>,
+// at your option. All files in the project carrying such
+// notice may not be copied, modified, or distributed except
+// according to those terms.
+
+use std::error;
+use std::fmt;
+use std::mem;
+use std::ptr;
+use std::sync::Arc;
+use std::time::Duration;
+use smallvec::SmallVec;
+
+use device::Device;
+use instance::DeviceExtensions;
+use instance::PhysicalDevice;
+use instance::Version;
+use Error;
+use OomError;
+use Success;
+use VulkanObject;
+use VulkanPointers;
+use check_errors;
+use vk;
+
+/// A semaphore whose payload is a 64-bits counter that only ever increases.
+///
+/// Contrary to a regular `Semaphore`, the host can read the current value of the counter and
+/// signal the semaphore by setting the counter to a higher value.
+///
+/// If both the instance and the physical device use Vulkan 1.2 or above, timeline semaphores
+/// are part of the core API. Otherwise the `VK_KHR_timeline_semaphore` device extension must be
+/// enabled. In both cases, vulkano automatically enables the `timelineSemaphore` feature when
+/// creating the device.
+#[derive(Debug)]
+pub struct TimelineSemaphore {
+    device: Arc<Device>,
+    semaphore: vk::Semaphore,
+}
+
+impl TimelineSemaphore {
+    /// See the docs of new().
+    pub fn raw(device: &Arc<Device>, initial_value: u64)
+               -> Result<TimelineSemaphore, TimelineError>
+    {
+        if !timeline_feature_required(&device.physical_device(), device.loaded_extensions()) {
+            return Err(TimelineError::MissingExtension { name: "VK_KHR_timeline_semaphore" });
+        }
+
+        let vk = device.pointers();
+
+        let semaphore = unsafe {
+            let type_infos = vk::SemaphoreTypeCreateInfoKHR {
+                sType: vk::STRUCTURE_TYPE_SEMAPHORE_TYPE_CREATE_INFO_KHR,
+                pNext: ptr::null(),
+                semaphoreType: vk::SEMAPHORE_TYPE_TIMELINE_KHR,
+                initialValue: initial_value,
+            };
+
+            let infos = vk::SemaphoreCreateInfo {
+                sType: vk::STRUCTURE_TYPE_SEMAPHORE_CREATE_INFO,
+                pNext: &type_infos as *const _ as *const _,
+                flags: 0,   // reserved
+            };
+
+            let mut output = mem::uninitialized();
+            try!(check_errors(vk.CreateSemaphore(device.internal_object(), &infos,
+                                                 device.allocation_callbacks(), &mut output)));
+            output
+        };
+
+        Ok(TimelineSemaphore {
+            device: device.clone(),
+            semaphore: semaphore,
+        })
+    }
+
+    /// Builds a new timeline semaphore whose counter starts at `initial_value`.
+    ///
+    /// # Panic
+    ///
+    /// - Panicks if timeline semaphores are not supported (see the docs of `TimelineSemaphore`).
+    /// - Panicks if the device or host ran out of memory.
+    ///
+    #[inline]
+    pub fn new(device: &Arc<Device>, initial_value: u64) -> Arc<TimelineSemaphore> {
+        Arc::new(TimelineSemaphore::raw(device, initial_value).unwrap())
+    }
+
+    /// Returns the device that owns this semaphore.
+    #[inline]
+    pub fn device(&self) -> &Arc<Device> {
+        &self.device
+    }
+
+    /// Returns the current value of the counter.
+    pub fn value(&self) -> Result<u64, TimelineError> {
+        unsafe {
+            let vk = self.device.pointers();
+
+            let mut output = 0;
+            if timeline_is_core(&self.device.physical_device()) {
+                try!(check_errors(vk.GetSemaphoreCounterValue(self.device.internal_object(),
+                                                              self.semaphore, &mut output)));
+            } else {
+                try!(check_errors(vk.GetSemaphoreCounterValueKHR(self.device.internal_object(),
+                                                                 self.semaphore, &mut output)));
+            }
+            Ok(output)
+        }
+    }
+
+    /// Sets the counter to `value` from the host.
+    ///
+    /// This is distinct from signalling the semaphore from a queue: the new value is visible
+    /// immediately to the operations that wait on the semaphore.
+    ///
+    /// `value` must be strictly greater than the current value of the counter, otherwise
+    /// `TimelineError::NonMonotonic` is returned. Note that vulkano doesn't know about the
+    /// signal operations that are still pending on a queue: `value` must also be lower than the
+    /// value that these operations will set.
+    pub fn signal_host(&self, value: u64) -> Result<(), TimelineError> {
+        try!(check_host_signal_value(try!(self.value()), value));
+
+        unsafe {
+            let vk = self.device.pointers();
+
+            let infos = vk::SemaphoreSignalInfoKHR {
+                sType: vk::STRUCTURE_TYPE_SEMAPHORE_SIGNAL_INFO_KHR,
+                pNext: ptr::null(),
+                semaphore: self.semaphore,
+                value: value,
+            };
+
+            if timeline_is_core(&self.device.physical_device()) {
+                try!(check_errors(vk.SignalSemaphore(self.device.internal_object(), &infos)));
+            } else {
+                try!(check_errors(vk.SignalSemaphoreKHR(self.device.internal_object(), &infos)));
+            }
+            Ok(())
+        }
+    }
+
+    /// Waits until the counter of each semaphore, or of at least one of them if `wait_all` is
+    /// false, is greater than or equal to the value associated to it.
+    ///
+    /// Returns `Ok(false)` if the timeout expired, and `Ok(true)` if the wait succeeded or if
+    /// `semaphores` is empty. Passing `None` waits forever.
+    ///
+    /// All the semaphores must belong to the same device, otherwise
+    /// `TimelineError::DifferentDevices` is returned.
+    pub fn wait_many(semaphores: &[(&TimelineSemaphore, u64)], wait_all: bool,
+                     timeout: Option<Duration>) -> Result<bool, TimelineError>
+    {
+        let device = match semaphores.first() {
+            Some(&(semaphore, _)) => &semaphore.device,
+            None => return Ok(true),
+        };
+
+        if semaphores.iter().any(|&(s, _)| s.device.internal_object() != device.internal_object()) {
+            return Err(TimelineError::DifferentDevices);
+        }
+
+        let raw_semaphores: SmallVec<[vk::Semaphore; 8]> = semaphores.iter()
+                                                                 .map(|&(s, _)| s.semaphore)
+                                                                 .collect();
+        let values: SmallVec<[u64; 8]> = semaphores.iter().map(|&(_, v)| v).collect();
+
+        let timeout_ns = match timeout {
+            Some(timeout) => timeout.as_secs().saturating_mul(1_000_000_000)
+                                              .saturating_add(timeout.subsec_nanos() as u64),
+            None => u64::max_value(),
+        };
+
+        let r = unsafe {
+            let vk = device.pointers();
+
+            let infos = vk::SemaphoreWaitInfoKHR {
+                sType: vk::STRUCTURE_TYPE_SEMAPHORE_WAIT_INFO_KHR,
+                pNext: ptr::null(),
+                flags: if wait_all { 0 } else { vk::SEMAPHORE_WAIT_ANY_BIT_KHR },
+                semaphoreCount: raw_semaphores.len() as u32,
+                pSemaphores: raw_semaphores.as_ptr(),
+                pValues: values.as_ptr(),
+            };
+
+            if timeline_is_core(&device.physical_device()) {
+                try!(check_errors(vk.WaitSemaphores(device.internal_object(), &infos,
+                                                    timeout_ns)))
+            } else {
+                try!(check_errors(vk.WaitSemaphoresKHR(device.internal_object(), &infos,
+                                                       timeout_ns)))
+            }
+        };
+
+        match r {
+            Success::Success => Ok(true),
+            Success::Timeout => Ok(false),
+            s => panic!("unexpected success value: {:?}", s)
+        }
+    }
+}
+
+unsafe impl VulkanObject for TimelineSemaphore {
+    type Object = vk::Semaphore;
+
+    #[inline]
+    fn internal_object(&self) -> vk::Semaphore {
+        self.semaphore
+    }
+}
+
+impl Drop for TimelineSemaphore {
+    #[inline]
+    fn drop(&mut self) {
+        unsafe {
+            let vk = self.device.pointers();
+            vk.DestroySemaphore(self.device.internal_object(), self.semaphore,
+                                self.device.allocation_callbacks());
+        }
+    }
+}
+
+// Returns true if timeline semaphores are part of the core API for this physical device, which
+// is the case if both the instance and the physical device use Vulkan 1.2 or above.
+#[inline]
+fn timeline_is_core(physical_device: &PhysicalDevice) -> bool {
+    let v1_2 = Version { major: 1, minor: 2, patch: 0 };
+    physical_device.instance().api_version() >= v1_2 && physical_device.api_version() >= v1_2
+}
+
+/// Returns true if timeline semaphores can be used with a device created with these extensions,
+/// in which case the `timelineSemaphore` feature must be enabled when creating the device.
+#[doc(hidden)]
+#[inline]
+pub fn timeline_feature_required(physical_device: &PhysicalDevice,
+                                 extensions: &DeviceExtensions) -> bool
+{
+    timeline_is_core(physical_device) || extensions.khr_timeline_semaphore
+}
+
+// Checks that signalling a counter whose value is `current` with `value` from the host makes
+// the counter increase.
+fn check_host_signal_value(current: u64, value: u64) -> Result<(), TimelineError> {
+    if value <= current {
+        return Err(TimelineError::NonMonotonic { current: current, requested: value });
+    }
+
+    Ok(())
+}
+
+/// Error that can happen when using a timeline semaphore.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum TimelineError {
+    /// Not enough memory.
+    OomError(OomError),
+
+    /// The extension required for this function was not enabled.
+    MissingExtension { name: &'static str },
+
+    /// The value passed to `signal_host` is not greater than the current value of the counter.
+    NonMonotonic {
+        /// Current value of the counter.
+        current: u64,
+        /// Value that was passed.
+        requested: u64,
+    },
+
+    /// The connection to the device has been lost.
+    DeviceLost,
+
+    /// The semaphores passed to `wait_many` don't all belong to the same device.
+    DifferentDevices,
+}
+
+impl error::Error for TimelineError {
+    #[inline]
+    fn description(&self) -> &str {
+        match *self {
+            TimelineError::OomError(_) => "not enough memory available",
+            TimelineError::MissingExtension { .. } => "the extension required for this function \
+                                                       was not enabled",
+            TimelineError::NonMonotonic { .. } => "the new value of the counter is not greater \
+                                                   than its current value",
+            TimelineError::DeviceLost => "the connection to the device has been lost",
+            TimelineError::DifferentDevices => "the semaphores don't all belong to the same \
+                                                device",
+        }
+    }
+
+    #[inline]
+    fn cause(&self) -> Option<&error::Error> {
+        match *self {
+            TimelineError::OomError(ref err) => Some(err),
+            _ => None
+        }
+    }
+}
+
+impl fmt::Display for TimelineError {
+    #[inline]
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> Result<(), fmt::Error> {
+        write!(fmt, "{}", error::Error::description(self))
+    }
+}
+
+impl From<OomError> for TimelineError {
+    #[inline]
+    fn from(err: OomError) -> TimelineError {
+        TimelineError::OomError(err)
+    }
+}
+
+impl From<Error> for TimelineError {
+    #[inline]
+    fn from(err: Error) -> TimelineError {
+        match err {
+            err @ Error::OutOfHostMemory => TimelineError::OomError(OomError::from(err)),
+            err @ Error::OutOfDeviceMemory => TimelineError::OomError(OomError::from(err)),
+            Error::DeviceLost => TimelineError::DeviceLost,
+            _ => panic!("unexpected error: {:?}", err)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sync::TimelineError;
+    use sync::TimelineSemaphore;
+    use super::check_host_signal_value;
+
+    #[test]
+    fn khr_timeline_semaphore_ext_missing() {
+        let (device, _) = gfx_dev_and_queue!();
+        match TimelineSemaphore::raw(&device, 0) {
+            Err(TimelineError::MissingExtension { .. }) => (),
+            _ => panic!()
+        }
+    }
+
+    #[test]
+    fn wait_many_empty() {
+        assert_eq!(TimelineSemaphore::wait_many(&[], true, None), Ok(true));
+    }
+
+    #[test]
+    fn host_signal_non_monotonic() {
+        assert_eq!(check_host_signal_value(5, 3),
+                   Err(TimelineError::NonMonotonic { current: 5, requested: 3 }));
+        assert_eq!(check_host_signal_value(5, 5),
+                   Err(TimelineError::NonMonotonic { current: 5, requested: 5 }));
+    }
+
+    #[test]
+    fn host_signal_increasing() {
+        assert_eq!(check_host_signal_value(5, 6), Ok(()));
+    }
+}